@@ -0,0 +1,321 @@
+//! Decoding of arbitrary logs with a registry of known ABIs
+use crate::EthLogDecode;
+use ethers_core::{
+    abi::{Abi, Error, Event, EventExt, LogParam, RawLog, Token},
+    types::{Address, Log, TransactionReceipt, H256, I256},
+};
+use std::{collections::HashMap, fmt};
+
+/// A registry of the events of known contracts, decoding logs emitted by any of them.
+///
+/// Logs are matched by their first topic, the selector of the event, against the events of all
+/// registered ABIs. Since the same event can be declared by several contracts, e.g. the
+/// `Transfer` event of ERC20 and ERC721 tokens, logs emitted at an address bound to a contract
+/// with [`EventDecoder::address`] are decoded with the ABI of that contract first. Logs which
+/// match no known event, including logs of anonymous events, are returned as raw logs.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_contract::{abigen, DecodedLog, EventDecoder};
+/// use ethers_core::types::TransactionReceipt;
+///
+/// abigen!(
+///     ERC20,
+///     r#"[
+///         event Transfer(address indexed from, address indexed to, uint256 value)
+///         event Approval(address indexed owner, address indexed spender, uint256 value)
+///     ]"#,
+/// );
+///
+/// # fn foo(receipt: TransactionReceipt) -> Result<(), ethers_core::abi::Error> {
+/// let decoder = EventDecoder::new().abi("ERC20", &ERC20_ABI);
+/// for log in decoder.decode_receipt(&receipt) {
+///     match log {
+///         DecodedLog::Decoded(event) => {
+///             println!("{event}");
+///             let event: ERC20Events = event.decode()?;
+///         }
+///         DecodedLog::Raw(log) => println!("unknown log {:?}", log.topics),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EventDecoder {
+    /// The registered events by their selector, with the name of their contract
+    events: HashMap<H256, Vec<(String, Event)>>,
+    /// The contracts bound to an address
+    addresses: HashMap<Address, String>,
+}
+
+impl EventDecoder {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the events of the `abi` of the contract `name`
+    pub fn abi(mut self, name: impl Into<String>, abi: &Abi) -> Self {
+        let name = name.into();
+        for event in abi.events().filter(|event| !event.anonymous) {
+            let events = self.events.entry(event.signature()).or_default();
+            if !events.iter().any(|(contract, e)| *contract == name && e == event) {
+                events.push((name.clone(), event.clone()));
+            }
+        }
+        self
+    }
+
+    /// Binds the contract `name` to `address`, decoding the logs emitted at `address` with the
+    /// events of `name` first
+    pub fn address(mut self, address: Address, name: impl Into<String>) -> Self {
+        self.addresses.insert(address, name.into());
+        self
+    }
+
+    /// Returns the number of registered events
+    pub fn len(&self) -> usize {
+        self.events.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if no events are registered
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Decodes `log` with the registered events, returning the raw log if no event matches
+    pub fn decode(&self, log: &Log) -> DecodedLog {
+        let candidates = match log.topics.first().and_then(|topic| self.events.get(topic)) {
+            Some(candidates) => candidates,
+            None => return DecodedLog::Raw(log.clone()),
+        };
+
+        // try the events of the contract bound to the address first
+        let bound = self.addresses.get(&log.address);
+        let (preferred, others): (Vec<_>, Vec<_>) =
+            candidates.iter().partition(|(contract, _)| Some(contract) == bound);
+
+        preferred
+            .into_iter()
+            .chain(others)
+            .find_map(|(contract, event)| {
+                let raw = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+                let parsed = event.parse_log(raw).ok()?;
+                Some(DecodedEvent {
+                    contract: contract.clone(),
+                    name: event.name.clone(),
+                    signature: event.abi_signature(),
+                    params: parsed.params,
+                    log: log.clone(),
+                })
+            })
+            .map(DecodedLog::Decoded)
+            .unwrap_or_else(|| DecodedLog::Raw(log.clone()))
+    }
+
+    /// Decodes all `logs`, in order
+    pub fn decode_logs<'a>(&self, logs: impl IntoIterator<Item = &'a Log>) -> Vec<DecodedLog> {
+        logs.into_iter().map(|log| self.decode(log)).collect()
+    }
+
+    /// Decodes all logs emitted by the transaction of `receipt`, in order
+    pub fn decode_receipt(&self, receipt: &TransactionReceipt) -> Vec<DecodedLog> {
+        self.decode_logs(&receipt.logs)
+    }
+}
+
+/// A log decoded by an [`EventDecoder`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedLog {
+    /// The log matched a registered event
+    Decoded(DecodedEvent),
+    /// The log matched no registered event
+    Raw(Log),
+}
+
+impl DecodedLog {
+    /// Returns the decoded event, if the log matched a registered event
+    pub fn as_decoded(&self) -> Option<&DecodedEvent> {
+        match self {
+            DecodedLog::Decoded(event) => Some(event),
+            DecodedLog::Raw(_) => None,
+        }
+    }
+
+    /// Returns the log
+    pub fn log(&self) -> &Log {
+        match self {
+            DecodedLog::Decoded(event) => &event.log,
+            DecodedLog::Raw(log) => log,
+        }
+    }
+}
+
+/// An event decoded from a log by an [`EventDecoder`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedEvent {
+    /// The name of the contract declaring the event
+    pub contract: String,
+    /// The name of the event
+    pub name: String,
+    /// The human-readable signature of the event, e.g. `Transfer(address,address,uint256)`
+    pub signature: String,
+    /// The decoded parameters of the event, in declaration order
+    pub params: Vec<LogParam>,
+    /// The decoded log
+    pub log: Log,
+}
+
+impl DecodedEvent {
+    /// Returns the value of the parameter `name`
+    pub fn param(&self, name: &str) -> Option<&Token> {
+        self.params.iter().find(|param| param.name == name).map(|param| &param.value)
+    }
+
+    /// Decodes the log into a typed event, such as an event struct or an abigen-generated
+    /// `*Events` enum
+    pub fn decode<E: EthLogDecode>(&self) -> Result<E, Error> {
+        E::decode_log(&RawLog { topics: self.log.topics.clone(), data: self.log.data.to_vec() })
+    }
+}
+
+impl fmt::Display for DecodedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}(", self.contract, self.name)?;
+        for (idx, param) in self.params.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", param.name, DisplayToken(&param.value))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Formats integers in decimal and addresses and bytes as `0x`-prefixed hex
+struct DisplayToken<'a>(&'a Token);
+
+impl fmt::Display for DisplayToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Token::Address(address) => write!(f, "{address:?}"),
+            Token::Bytes(bytes) | Token::FixedBytes(bytes) => write!(f, "0x{}", hex::encode(bytes)),
+            Token::Uint(value) => write!(f, "{value}"),
+            Token::Int(value) => write!(f, "{}", I256::from_raw(*value)),
+            Token::Bool(value) => write!(f, "{value}"),
+            Token::String(value) => write!(f, "{value:?}"),
+            Token::Array(tokens) | Token::FixedArray(tokens) => {
+                write!(f, "[")?;
+                fmt_tokens(f, tokens)?;
+                write!(f, "]")
+            }
+            Token::Tuple(tokens) => {
+                write!(f, "(")?;
+                fmt_tokens(f, tokens)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn fmt_tokens(f: &mut fmt::Formatter<'_>, tokens: &[Token]) -> fmt::Result {
+    for (idx, token) in tokens.iter().enumerate() {
+        if idx > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", DisplayToken(token))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthEvent;
+    use ethers_core::{
+        abi::{encode, parse_abi},
+        types::{H160, U256},
+    };
+
+    #[derive(Clone, Debug, PartialEq, Eq, EthEvent)]
+    struct Transfer {
+        #[ethevent(indexed)]
+        from: Address,
+        #[ethevent(indexed)]
+        to: Address,
+        value: U256,
+    }
+
+    fn transfer_log(address: Address, value: U256) -> Log {
+        let from = H160([1; 20]);
+        let to = H160([2; 20]);
+        Log {
+            address,
+            topics: vec![Transfer::signature(), from.into(), to.into()],
+            data: encode(&[Token::Uint(value)]).into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decodes_known_events() {
+        let erc20 = parse_abi(&[
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "event Approval(address indexed owner, address indexed spender, uint256 value)",
+        ])
+        .unwrap();
+        // ERC721 declares the same event, with the token id indexed
+        let erc721 = parse_abi(&[
+            "event Transfer(address indexed from, address indexed to, uint256 indexed tokenId)",
+        ])
+        .unwrap();
+        let token = H160([3; 20]);
+        let decoder = EventDecoder::new().abi("ERC721", &erc721).abi("ERC20", &erc20);
+        assert_eq!(decoder.len(), 3);
+
+        let log = transfer_log(token, 100u64.into());
+        let event = decoder.decode(&log).as_decoded().cloned().unwrap();
+        assert_eq!(event.contract, "ERC20");
+        assert_eq!(event.signature, "Transfer(address,address,uint256)");
+        assert_eq!(event.param("value"), Some(&Token::Uint(100u64.into())));
+        assert_eq!(
+            event.to_string(),
+            format!(
+                "ERC20.Transfer(from: 0x{}, to: 0x{}, value: 100)",
+                "01".repeat(20),
+                "02".repeat(20)
+            )
+        );
+        let transfer: Transfer = event.decode().unwrap();
+        assert_eq!(transfer.value, 100u64.into());
+
+        let unknown = Log { topics: vec![H256::repeat_byte(9)], ..log };
+        assert_eq!(decoder.decode(&unknown), DecodedLog::Raw(unknown));
+    }
+
+    #[test]
+    fn displays_tokens() {
+        let tokens = Token::Tuple(vec![
+            Token::Int(I256::from(-5).into_raw()),
+            Token::Bytes(vec![0xab, 0xcd]),
+            Token::Array(vec![Token::Bool(true), Token::String("a".to_string())]),
+        ]);
+        assert_eq!(DisplayToken(&tokens).to_string(), r#"(-5, 0xabcd, [true, "a"])"#);
+    }
+
+    #[test]
+    fn prefers_bound_contract() {
+        let abi =
+            parse_abi(&["event Transfer(address indexed from, address indexed to, uint256 value)"])
+                .unwrap();
+        let token = H160([3; 20]);
+        let decoder = EventDecoder::new().abi("A", &abi).abi("B", &abi).address(token, "B");
+
+        let logs = [transfer_log(token, 1u64.into()), transfer_log(H160([4; 20]), 1u64.into())];
+        let decoded = decoder.decode_logs(&logs);
+        assert_eq!(decoded[0].as_decoded().unwrap().contract, "B");
+        assert_eq!(decoded[1].as_decoded().unwrap().contract, "A");
+    }
+}
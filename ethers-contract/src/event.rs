@@ -2,10 +2,11 @@
 
 use crate::{
     event_core::parse_log, log::LogMeta, stream::EventStream, ContractError, EthLogDecode,
+    EventSync, EventSyncCheckpoint,
 };
 use ethers_core::{
     abi::Address,
-    types::{BlockNumber, Filter, Log, Topic, ValueOrArray, H256},
+    types::{BlockNumber, Filter, Log, Topic, ValueOrArray, H256, U64},
 };
 use ethers_providers::{FilterWatcher, Middleware, PubsubClient, SubscriptionStream};
 use std::{borrow::Borrow, marker::PhantomData};
@@ -267,3 +268,54 @@ where
         Ok(events)
     }
 }
+
+impl<B, M, D> Event<B, M, D>
+where
+    B: Borrow<M>,
+    M: Middleware,
+    D: EthLogDecode,
+{
+    /// Returns a stream of all events from `block` on, which backfills the history and then
+    /// follows new blocks, yielding the events of blocks orphaned by a reorg as
+    /// [`SyncedEvent::Removed`](crate::SyncedEvent::Removed).
+    ///
+    /// The block range of the filter is ignored. See [`EventSync`] for details.
+    ///
+    /// # Example
+    // Ignore because `ethers-contract-derive` macros do not work in doctests in `ethers-contract`.
+    /// ```ignore
+    /// # async fn test<M: ethers_providers::Middleware>(contract: ethers_contract::Contract<M>) {
+    /// # use ethers_core::types::*;
+    /// # use futures_util::stream::StreamExt;
+    /// # use ethers_contract::{EthEvent, SyncedEvent};
+    /// #[derive(Clone, Debug, EthEvent)]
+    /// pub struct Transfer {
+    ///     #[ethevent(indexed)]
+    ///     pub from: Address,
+    ///     #[ethevent(indexed)]
+    ///     pub to: Address,
+    ///     pub value: U256,
+    /// }
+    ///
+    /// let event = contract.event::<Transfer>();
+    /// let mut stream = event.sync_from(15_000_000u64);
+    /// while let Some(Ok(event)) = stream.next().await {
+    ///     match event {
+    ///         SyncedEvent::Added(transfer, meta) => { /* apply */ }
+    ///         SyncedEvent::Removed(transfer, meta) => { /* revert */ }
+    ///     }
+    ///     // persist the progress
+    ///     let checkpoint = serde_json::to_string(&stream.checkpoint()).unwrap();
+    /// }
+    /// # }
+    /// ```
+    pub fn sync_from(&self, block: impl Into<U64>) -> EventSync<'_, M, D> {
+        EventSync::from_block(self.provider.borrow(), self.filter.clone(), block.into())
+    }
+
+    /// Resumes a stream created with [`Self::sync_from`] from a checkpoint returned by
+    /// [`EventSync::checkpoint`]
+    pub fn sync_from_checkpoint(&self, checkpoint: EventSyncCheckpoint) -> EventSync<'_, M, D> {
+        EventSync::new(self.provider.borrow(), self.filter.clone(), checkpoint)
+    }
+}
@@ -0,0 +1,585 @@
+use crate::{event_core::parse_log, log::LogMeta, ContractError, EthLogDecode};
+use ethers_core::types::{Block, Filter, Log, TxHash, U256, U64};
+use ethers_providers::{
+    interval, ChainBlock, LogQuery, LogQueryCheckpoint, LogQueryError, Middleware,
+    ReorgEvent, ReorgTracker, DEFAULT_POLL_INTERVAL, DEFAULT_REORG_WINDOW,
+};
+use futures_util::{stream::Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The page size the backfill starts with
+const DEFAULT_PAGE_SIZE: u64 = 10_000;
+
+/// An event yielded by an [`EventSync`] stream
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncedEvent<D> {
+    /// An event of the canonical chain
+    Added(D, LogMeta),
+    /// An event yielded earlier whose block has been orphaned by a reorg
+    Removed(D, LogMeta),
+}
+
+impl<D> SyncedEvent<D> {
+    /// Returns the event
+    pub fn event(&self) -> &D {
+        match self {
+            SyncedEvent::Added(event, _) | SyncedEvent::Removed(event, _) => event,
+        }
+    }
+
+    /// Returns the metadata of the log of the event
+    pub fn meta(&self) -> &LogMeta {
+        match self {
+            SyncedEvent::Added(_, meta) | SyncedEvent::Removed(_, meta) => meta,
+        }
+    }
+
+    /// Returns `true` if the event was removed by a reorg
+    pub fn is_removed(&self) -> bool {
+        matches!(self, SyncedEvent::Removed(..))
+    }
+}
+
+/// The progress of an [`EventSync`] stream, which can be persisted to resume the stream later.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSyncCheckpoint {
+    /// The position the stream resumes from, with the page size the backfill had adapted to
+    pub position: LogQueryCheckpoint,
+    /// The recent blocks which may still be orphaned, with their logs, empty while the stream
+    /// backfills history
+    pub blocks: Vec<ChainBlock>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type SyncFut<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+type SyncFut<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// A poll of the tracker, which returns the tracker once done
+type TrackerFut<'a, E> = SyncFut<'a, (ReorgTracker, Result<TrackerPoll, E>)>;
+
+/// The result of a poll of the tracker
+enum TrackerPoll {
+    /// The events of the new blocks
+    Events(Vec<ReorgEvent>),
+    /// The head block is further ahead than the reorg window, the blocks in between are
+    /// backfilled instead
+    Behind(U64),
+}
+
+enum EventSyncState<'a, M: Middleware> {
+    Initial,
+    LoadHead(SyncFut<'a, Result<U64, M::Error>>),
+    /// Backfills the final blocks up to the given block
+    Backfill(Box<LogQuery<'a, M::Provider>>, U64),
+    LoadStartBlock(SyncFut<'a, Result<Option<Block<TxHash>>, M::Error>>),
+    WaitForInterval,
+    Poll(TrackerFut<'a, M::Error>),
+    Yield,
+}
+
+/// Streams the events of a filter from a past block on, backfilling the history and then
+/// following the chain, see [`Event::sync_from`](crate::Event::sync_from).
+///
+/// Blocks older than the reorg window are considered final and backfilled with a paginated
+/// [`LogQuery`]. The blocks of the window and all new blocks are followed with a
+/// [`ReorgTracker`], which yields the events of orphaned blocks again as
+/// [`SyncedEvent::Removed`]. Both phases join at the same block, so no event is skipped or
+/// yielded twice. If the chain advanced by more than the reorg window since the last poll, e.g.
+/// because the stream was not polled for a while, the blocks before the window are backfilled
+/// again.
+///
+/// The progress can be saved with [`EventSync::checkpoint`] and resumed with
+/// [`Event::sync_from_checkpoint`](crate::Event::sync_from_checkpoint). A checkpoint taken while
+/// the events of a reorg are yielded resumes before the reorg, which is then reported again.
+///
+/// Errors are yielded without ending the stream, the next poll retries the failed request.
+#[must_use = "streams do nothing unless polled"]
+pub struct EventSync<'a, M: Middleware, D> {
+    client: &'a M,
+    filter: Filter,
+    window: usize,
+    max_page_size: Option<u64>,
+    interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
+    checkpoint: EventSyncCheckpoint,
+    /// Logs of the block the checkpoint points into are skipped up to this index
+    skip: Option<(U64, U256)>,
+    /// The tracker, taken by the pending poll
+    tracker: Option<ReorgTracker>,
+    events: VecDeque<ReorgEvent>,
+    /// The event whose logs are being yielded
+    current: Option<ReorgEvent>,
+    logs: VecDeque<Log>,
+    state: EventSyncState<'a, M>,
+    datatype: PhantomData<fn() -> D>,
+}
+
+impl<'a, M, D> EventSync<'a, M, D>
+where
+    M: Middleware,
+    D: EthLogDecode,
+{
+    pub(crate) fn new(client: &'a M, filter: Filter, checkpoint: EventSyncCheckpoint) -> Self {
+        let skip = checkpoint.position.log_index.map(|index| (checkpoint.position.block, index));
+        Self {
+            client,
+            filter,
+            window: DEFAULT_REORG_WINDOW,
+            max_page_size: None,
+            interval: Box::new(interval(DEFAULT_POLL_INTERVAL)),
+            checkpoint,
+            skip,
+            tracker: None,
+            events: VecDeque::new(),
+            current: None,
+            logs: VecDeque::new(),
+            state: EventSyncState::Initial,
+            datatype: PhantomData,
+        }
+    }
+
+    pub(crate) fn from_block(client: &'a M, filter: Filter, block: U64) -> Self {
+        let position = LogQueryCheckpoint { block, log_index: None, page_size: DEFAULT_PAGE_SIZE };
+        Self::new(client, filter, EventSyncCheckpoint { position, blocks: Vec::new() })
+    }
+
+    /// Sets the interval at which new blocks are polled
+    pub fn interval(mut self, duration: Duration) -> Self {
+        self.interval = Box::new(interval(duration));
+        self
+    }
+
+    /// Sets the number of recent blocks which may still be orphaned by a reorg
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Sets the page size the backfill starts with
+    pub fn page_size(mut self, page_size: u64) -> Self {
+        self.checkpoint.position.page_size = page_size;
+        self
+    }
+
+    /// Sets the size the pages of the backfill may grow to while they return few logs
+    pub fn max_page_size(mut self, max_page_size: u64) -> Self {
+        self.max_page_size = Some(max_page_size);
+        self
+    }
+
+    /// Returns the current progress of the stream
+    pub fn checkpoint(&self) -> EventSyncCheckpoint {
+        self.checkpoint.clone()
+    }
+
+    /// Creates the tracker following the chain after the blocks of the checkpoint
+    fn start_tracker(&mut self, blocks: Vec<ChainBlock>) -> EventSyncState<'a, M> {
+        let tracker =
+            ReorgTracker::new(self.window).filter(self.filter.clone()).with_blocks(blocks);
+        self.poll_tracker(tracker)
+    }
+
+    fn poll_tracker(&self, mut tracker: ReorgTracker) -> EventSyncState<'a, M> {
+        let client = self.client;
+        let window = self.window as u64;
+        EventSyncState::Poll(Box::pin(async move {
+            let res = match client.get_block_number().await {
+                Ok(head) if tracker.head().map_or(false, |block| head > block.number() + window) => {
+                    Ok(TrackerPoll::Behind(head))
+                }
+                Ok(head) => tracker.poll_to(client, head).await.map(TrackerPoll::Events),
+                Err(err) => Err(err),
+            };
+            (tracker, res)
+        }))
+    }
+
+    /// Backfills the final blocks before `head`, or starts following the chain if there are none
+    fn sync_to(&mut self, head: U64) -> EventSyncState<'a, M> {
+        // blocks outside of the reorg window are final and backfilled, the genesis block is always
+        // backfilled as the tracker starts after a block
+        let start = self.checkpoint.position.block;
+        let end = head.saturating_sub((self.window as u64).into());
+        if start.is_zero() || start <= end {
+            let filter = self.filter.clone().from_block(start).to_block(end);
+            let mut query = LogQuery::new(self.client.provider(), &filter);
+            if let Some(max) = self.max_page_size {
+                query = query.with_max_page_size(max);
+            }
+            self.skip = None;
+            EventSyncState::Backfill(
+                Box::new(query.with_checkpoint(self.checkpoint.position.clone())),
+                end,
+            )
+        } else {
+            self.load_start_block()
+        }
+    }
+
+    fn load_start_block(&self) -> EventSyncState<'a, M> {
+        let client = self.client;
+        let number = self.checkpoint.position.block.saturating_sub(1.into());
+        EventSyncState::LoadStartBlock(Box::pin(async move { client.get_block(number).await }))
+    }
+
+    /// Takes the logs of the next event to yield, returns `false` if there is none
+    fn next_event(&mut self) -> bool {
+        let Some(event) = self.events.pop_front() else { return false };
+        let logs = match &event {
+            ReorgEvent::NewBlock(block) => block.logs.clone(),
+            ReorgEvent::Reorged { removed, added } => {
+                // removals are yielded from the newest to the oldest
+                let removed = removed.iter().rev().flat_map(|block| block.logs.iter().rev());
+                removed.chain(added.iter().flat_map(|block| &block.logs)).cloned().collect()
+            }
+        };
+        let skip = self.skip.take();
+        self.logs = logs
+            .into_iter()
+            .filter(|log| match (skip, log.block_number, log.log_index) {
+                (Some((block, index)), Some(number), Some(log_index)) => {
+                    number != block || log_index > index
+                }
+                _ => true,
+            })
+            .collect();
+        self.current = Some(event);
+        true
+    }
+
+    /// Records that all logs of `event` were yielded
+    fn complete(&mut self, event: ReorgEvent) {
+        let added = match event {
+            ReorgEvent::NewBlock(block) => vec![block],
+            ReorgEvent::Reorged { added, .. } => added,
+        };
+        let Some(first) = added.first().map(ChainBlock::number) else { return };
+        let blocks = &mut self.checkpoint.blocks;
+        blocks.retain(|block| block.number() < first);
+        for block in added {
+            self.checkpoint.position.block = block.number() + 1;
+            // only the parts needed to detect a reorg are kept
+            let header = Block {
+                number: block.block.number,
+                hash: block.block.hash,
+                parent_hash: block.block.parent_hash,
+                ..Default::default()
+            };
+            blocks.push(ChainBlock { block: header, logs: block.logs });
+        }
+        self.checkpoint.position.log_index = None;
+        let excess = blocks.len().saturating_sub(self.window);
+        blocks.drain(..excess);
+    }
+
+    fn decode(log: Log) -> Result<SyncedEvent<D>, ContractError<M>> {
+        let meta = LogMeta::from(&log);
+        let removed = log.removed == Some(true);
+        let event = parse_log(log)?;
+        Ok(if removed { SyncedEvent::Removed(event, meta) } else { SyncedEvent::Added(event, meta) })
+    }
+}
+
+impl<'a, M, D> Stream for EventSync<'a, M, D>
+where
+    M: Middleware + 'a,
+    D: EthLogDecode,
+{
+    type Item = Result<SyncedEvent<D>, ContractError<M>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            this.state = match &mut this.state {
+                EventSyncState::Initial => {
+                    if this.checkpoint.blocks.is_empty() {
+                        let client = this.client;
+                        EventSyncState::LoadHead(Box::pin(
+                            async move { client.get_block_number().await },
+                        ))
+                    } else {
+                        // the checkpoint was taken while following the chain
+                        let blocks = this.checkpoint.blocks.clone();
+                        this.start_tracker(blocks)
+                    }
+                }
+                EventSyncState::LoadHead(fut) => match futures_util::ready!(fut.as_mut().poll(cx))
+                {
+                    Ok(head) => this.sync_to(head),
+                    Err(err) => {
+                        this.state = EventSyncState::Initial;
+                        return Poll::Ready(Some(Err(ContractError::from_middleware_error(err))))
+                    }
+                },
+                EventSyncState::Backfill(query, end) => {
+                    let next = futures_util::ready!(query.poll_next_unpin(cx));
+                    if let Some(checkpoint) = query.checkpoint() {
+                        this.checkpoint.position = checkpoint;
+                    }
+                    match next {
+                        Some(Ok(log)) => return Poll::Ready(Some(Self::decode(log))),
+                        Some(Err(
+                            LogQueryError::LoadLastBlockError(err) |
+                            LogQueryError::LoadLogsError(err),
+                        )) => return Poll::Ready(Some(Err(err.into()))),
+                        // the query ends early if the node rejects a single block
+                        None if this.checkpoint.position.block <= *end => EventSyncState::Initial,
+                        None => this.load_start_block(),
+                    }
+                }
+                EventSyncState::LoadStartBlock(fut) => {
+                    match futures_util::ready!(fut.as_mut().poll(cx)) {
+                        Ok(Some(block)) if block.hash.is_some() => {
+                            // the logs of the start block were backfilled
+                            this.start_tracker(vec![ChainBlock { block, logs: Vec::new() }])
+                        }
+                        // the start block is not mined yet
+                        Ok(_) => EventSyncState::WaitForInterval,
+                        Err(err) => {
+                            this.state = EventSyncState::WaitForInterval;
+                            return Poll::Ready(Some(Err(ContractError::from_middleware_error(err))))
+                        }
+                    }
+                }
+                EventSyncState::WaitForInterval => {
+                    let _ready = futures_util::ready!(this.interval.poll_next_unpin(cx));
+                    match this.tracker.take() {
+                        Some(tracker) => this.poll_tracker(tracker),
+                        None => this.load_start_block(),
+                    }
+                }
+                EventSyncState::Poll(fut) => {
+                    let (tracker, res) = futures_util::ready!(fut.as_mut().poll(cx));
+                    this.tracker = Some(tracker);
+                    match res {
+                        Ok(TrackerPoll::Events(events)) => {
+                            this.events.extend(events);
+                            EventSyncState::Yield
+                        }
+                        Ok(TrackerPoll::Behind(head)) => {
+                            // the tracker restarts after the backfilled blocks
+                            this.tracker = None;
+                            this.checkpoint.blocks.clear();
+                            this.sync_to(head)
+                        }
+                        Err(err) => {
+                            this.state = EventSyncState::WaitForInterval;
+                            return Poll::Ready(Some(Err(ContractError::from_middleware_error(err))))
+                        }
+                    }
+                }
+                EventSyncState::Yield => {
+                    if let Some(log) = this.logs.pop_front() {
+                        if let Some(ReorgEvent::NewBlock(block)) = &this.current {
+                            this.checkpoint.position.block = block.number();
+                            this.checkpoint.position.log_index = log.log_index;
+                        }
+                        if this.logs.is_empty() {
+                            if let Some(event) = this.current.take() {
+                                this.complete(event);
+                            }
+                        }
+                        return Poll::Ready(Some(Self::decode(log)))
+                    }
+                    if let Some(event) = this.current.take() {
+                        this.complete(event);
+                    }
+                    if this.next_event() {
+                        continue
+                    }
+                    EventSyncState::WaitForInterval
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthEvent;
+    use ethers_core::{
+        abi::{encode, Token},
+        types::{Address, H256},
+    };
+    use ethers_providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+
+    #[derive(Clone, Debug, PartialEq, Eq, EthEvent)]
+    struct Transfer {
+        #[ethevent(indexed)]
+        from: Address,
+        value: U256,
+    }
+
+    type Sync<'a> = EventSync<'a, Provider<MockProvider>, Transfer>;
+
+    fn block(number: u64, hash: u64, parent: u64) -> Block<TxHash> {
+        Block {
+            number: Some(number.into()),
+            hash: Some(H256::from_low_u64_be(hash)),
+            parent_hash: H256::from_low_u64_be(parent),
+            ..Default::default()
+        }
+    }
+
+    fn log(block: u64, hash: u64, value: u64) -> Log {
+        Log {
+            topics: vec![Transfer::signature(), H256::zero()],
+            data: encode(&[Token::Uint(value.into())]).into(),
+            block_number: Some(block.into()),
+            block_hash: Some(H256::from_low_u64_be(hash)),
+            transaction_hash: Some(H256::zero()),
+            transaction_index: Some(0.into()),
+            log_index: Some(0.into()),
+            ..Default::default()
+        }
+    }
+
+    fn values(events: &[SyncedEvent<Transfer>]) -> Vec<(bool, u64)> {
+        events.iter().map(|event| (event.is_removed(), event.event().value.as_u64())).collect()
+    }
+
+    #[tokio::test]
+    async fn backfills_and_follows_reorgs() {
+        let (provider, mock) = Provider::mocked();
+        // following the chain, block 5 is replaced
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(5, 0xb5, 50)]).unwrap();
+        mock.push(block(5, 0xb5, 0xa4)).unwrap();
+        mock.push(block(6, 0xb6, 0xb5)).unwrap();
+        mock.push(U64::from(6)).unwrap();
+        // the blocks of the reorg window
+        mock.push::<Vec<Log>, _>(vec![log(5, 0xa5, 5)]).unwrap();
+        mock.push(block(5, 0xa5, 0xa4)).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(4, 0xa4, 4)]).unwrap();
+        mock.push(block(4, 0xa4, 0xa3)).unwrap();
+        mock.push(U64::from(5)).unwrap();
+        mock.push(block(3, 0xa3, 0xa2)).unwrap();
+        // the backfill of the final blocks
+        mock.push::<Vec<Log>, _>(vec![log(1, 0xa1, 1)]).unwrap();
+        mock.push(U64::from(5)).unwrap();
+        mock.push(U64::from(5)).unwrap();
+
+        let mut sync = Sync::from_block(&provider, Filter::new(), 0.into())
+            .window(2)
+            .interval(Duration::from_millis(1));
+        let events = sync.by_ref().take(3).collect::<Vec<_>>().await;
+        let events = events.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values(&events), vec![(false, 1), (false, 4), (false, 5)]);
+
+        let checkpoint = sync.checkpoint();
+        assert_eq!(
+            checkpoint.position,
+            LogQueryCheckpoint { block: 6.into(), log_index: None, page_size: DEFAULT_PAGE_SIZE }
+        );
+        let hashes = checkpoint.blocks.iter().map(ChainBlock::hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![H256::from_low_u64_be(0xa4), H256::from_low_u64_be(0xa5)]);
+
+        let events = sync.by_ref().take(2).collect::<Vec<_>>().await;
+        let events = events.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values(&events), vec![(true, 5), (false, 50)]);
+        let checkpoint = sync.checkpoint();
+        assert_eq!(checkpoint.position.block, 7.into());
+        let hashes = checkpoint.blocks.iter().map(ChainBlock::hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![H256::from_low_u64_be(0xb5), H256::from_low_u64_be(0xb6)]);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_checkpoint() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(6, 0xa6, 6)]).unwrap();
+        mock.push(block(6, 0xa6, 0xa5)).unwrap();
+        mock.push(U64::from(6)).unwrap();
+
+        let checkpoint = EventSyncCheckpoint {
+            position: LogQueryCheckpoint { block: 6.into(), log_index: None, page_size: 10 },
+            blocks: vec![ChainBlock { block: block(5, 0xa5, 0xa4), logs: vec![log(5, 0xa5, 5)] }],
+        };
+        let checkpoint = serde_json::to_string(&checkpoint).unwrap();
+
+        // continues following the chain without backfilling again
+        let mut sync =
+            Sync::new(&provider, Filter::new(), serde_json::from_str(&checkpoint).unwrap());
+        let event = sync.next().await.unwrap().unwrap();
+        assert_eq!(values(&[event]), vec![(false, 6)]);
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+    }
+
+    fn checkpoint(block: u64) -> EventSyncCheckpoint {
+        EventSyncCheckpoint {
+            position: LogQueryCheckpoint { block: (block + 1).into(), log_index: None, page_size: 10 },
+            blocks: vec![ChainBlock {
+                block: Block { number: Some(block.into()), ..Default::default() },
+                logs: vec![],
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_events_on_error_while_catching_up() {
+        let (provider, mock) = Provider::mocked();
+        // the next poll continues at block 7
+        mock.push::<Vec<Log>, _>(vec![log(7, 0xa7, 7)]).unwrap();
+        mock.push(block(7, 0xa7, 0xa6)).unwrap();
+        mock.push(U64::from(7)).unwrap();
+        // block 7 can't be fetched yet
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        }));
+        mock.push::<Vec<Log>, _>(vec![log(6, 0xa6, 6)]).unwrap();
+        mock.push(block(6, 0xa6, 0xa5)).unwrap();
+        mock.push(U64::from(7)).unwrap();
+
+        let mut checkpoint = checkpoint(5);
+        checkpoint.blocks[0].block.hash = Some(H256::from_low_u64_be(0xa5));
+        let mut sync = Sync::new(&provider, Filter::new(), checkpoint)
+            .window(2)
+            .interval(Duration::from_millis(1));
+        let events = sync.by_ref().take(2).collect::<Vec<_>>().await;
+        let events = events.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values(&events), vec![(false, 6), (false, 7)]);
+        assert_eq!(sync.checkpoint().position.block, 8.into());
+    }
+
+    #[tokio::test]
+    async fn backfills_when_behind_the_window() {
+        let (provider, mock) = Provider::mocked();
+        // following the chain after the backfilled blocks
+        mock.push::<Vec<Log>, _>(vec![log(10, 0xa10, 10)]).unwrap();
+        mock.push(block(10, 0xa10, 0xa9)).unwrap();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push(block(9, 0xa9, 0xa8)).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(block(8, 0xa8, 0xa7)).unwrap();
+        // the backfill of blocks 6 to 8
+        mock.push::<Vec<Log>, _>(vec![log(7, 0xa7, 7)]).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        // the chain moved on by more than the window
+        mock.push(U64::from(10)).unwrap();
+
+        let mut sync = Sync::new(&provider, Filter::new(), checkpoint(5))
+            .window(2)
+            .interval(Duration::from_millis(1));
+        let events = sync.by_ref().take(2).collect::<Vec<_>>().await;
+        let events = events.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(values(&events), vec![(false, 7), (false, 10)]);
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", [Filter::new().from_block(6).to_block(8)]).unwrap();
+        let hashes = sync.checkpoint().blocks.iter().map(ChainBlock::hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![H256::from_low_u64_be(0xa9), H256::from_low_u64_be(0xa10)]);
+    }
+}
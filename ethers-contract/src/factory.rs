@@ -4,8 +4,9 @@ use ethers_core::{
     abi::{Abi, Token, Tokenize},
     types::{
         transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, NameOrAddress,
-        TransactionReceipt, TransactionRequest, U256, U64,
+        TransactionReceipt, TransactionRequest, H160, H256, U256, U64,
     },
+    utils::get_create2_address,
 };
 use ethers_providers::{
     call_raw::{CallBuilder, RawCall},
@@ -17,6 +18,15 @@ use ethers_core::types::Eip1559TransactionRequest;
 
 use std::{borrow::Borrow, marker::PhantomData, sync::Arc};
 
+/// The deterministic deployment proxy, deploying the init code following a 32 byte salt in its
+/// calldata with CREATE2. It is deployed at the same address on most chains.
+///
+/// See <https://github.com/Arachnid/deterministic-deployment-proxy>
+pub const CREATE2_FACTORY: Address = H160([
+    0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88, 0x92, 0x0c, 0xa7, 0x8f, 0xbf, 0x26,
+    0xc0, 0xb4, 0x95, 0x6c,
+]);
+
 /// `ContractDeployer` is a [`ContractDeploymentTx`] object with an
 /// [`Arc`] middleware. This type alias exists to preserve backwards
 /// compatibility with less-abstract Contracts.
@@ -139,6 +149,18 @@ where
         self
     }
 
+    /// Deploys the contract deterministically with CREATE2 through the [`CREATE2_FACTORY`],
+    /// see [`Deployer::create2`]
+    pub fn create2(mut self, salt: impl Into<H256>) -> Self {
+        self.deployer = self.deployer.create2(salt);
+        self
+    }
+
+    /// Returns the address the contract is deployed at with CREATE2, if it is
+    pub fn create2_address(&self) -> Option<Address> {
+        self.deployer.create2_address()
+    }
+
     /// Dry runs the deployment of the contract
     ///
     /// Note: this function _does not_ send a transaction from your account
@@ -193,6 +215,7 @@ pub struct Deployer<B, M> {
     client: B,
     confs: usize,
     block: BlockNumber,
+    create2_address: Option<Address>,
     _m: PhantomData<M>,
 }
 
@@ -207,6 +230,7 @@ where
             client: self.client.clone(),
             confs: self.confs,
             block: self.block,
+            create2_address: self.create2_address,
             _m: PhantomData,
         }
     }
@@ -241,6 +265,29 @@ where
         self
     }
 
+    /// Deploys the contract deterministically with CREATE2 through the [`CREATE2_FACTORY`],
+    /// at an address depending only on the `salt` and the init code, i.e. the bytecode and the
+    /// constructor arguments.
+    ///
+    /// The address is returned by [`create2_address`](Self::create2_address). Deploying the
+    /// same init code with the same salt twice fails, as the address is already taken.
+    pub fn create2(mut self, salt: impl Into<H256>) -> Self {
+        let salt = salt.into();
+        let data = self.tx.data().cloned().unwrap_or_default();
+        // the init code follows the salt of a previous call
+        let init_code = if self.create2_address.is_some() { &data[32..] } else { &data[..] };
+
+        self.create2_address = Some(get_create2_address(CREATE2_FACTORY, salt, init_code));
+        self.tx.set_data([salt.as_bytes(), init_code].concat().into());
+        self.tx.set_to(CREATE2_FACTORY);
+        self
+    }
+
+    /// Returns the address the contract is deployed at with CREATE2, if it is
+    pub fn create2_address(&self) -> Option<Address> {
+        self.create2_address
+    }
+
     /// Dry runs the deployment of the contract
     ///
     /// Note: this function _does not_ send a transaction from your account
@@ -293,7 +340,12 @@ where
             .ok()
             .flatten()
             .ok_or(ContractError::ContractNotDeployed)?;
-        let address = receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?;
+        let address = match self.create2_address {
+            // the factory reverts if the deployment failed
+            Some(address) if receipt.status == Some(1.into()) => address,
+            Some(_) => return Err(ContractError::ContractNotDeployed),
+            None => receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?,
+        };
 
         let contract = ContractInstance::new(address, self.abi, self.client);
         Ok((contract, receipt))
@@ -410,6 +462,7 @@ where
             tx,
             confs: 1,
             block: BlockNumber::Latest,
+            create2_address: None,
             _m: PhantomData,
         })
     }
@@ -429,3 +482,34 @@ where
         self.deploy_tokens(constructor_args.into_tokens())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::Provider;
+
+    #[test]
+    fn create2_deploys_through_factory() {
+        let (provider, _mock) = Provider::mocked();
+        let init_code = Bytes::from(vec![0x60, 0x80, 0x60, 0x40, 0x52]);
+        let factory = ContractFactory::new(Abi::default(), init_code.clone(), Arc::new(provider));
+        let deployer = factory.deploy(()).unwrap();
+
+        // computed independently as keccak256(0xff ++ factory ++ salt ++ keccak256(init code))
+        let deployer = deployer.create2(H256::zero());
+        assert_eq!(
+            deployer.create2_address(),
+            Some("0x0e41aa54d633ee06dc2ee16beeb53478281b096c".parse().unwrap())
+        );
+
+        // a second call replaces the salt instead of prepending another one
+        let salt = H256::from_low_u64_be(1);
+        let deployer = deployer.create2(salt);
+        assert_eq!(
+            deployer.create2_address(),
+            Some("0x05e96fa12b872ff96d73a5a6dded5010c406712c".parse().unwrap())
+        );
+        assert_eq!(deployer.tx.to(), Some(&CREATE2_FACTORY.into()));
+        assert_eq!(deployer.tx.data(), Some(&[salt.as_bytes(), &init_code].concat().into()));
+    }
+}
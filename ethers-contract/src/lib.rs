@@ -26,6 +26,9 @@ pub use event_core::{parse_log, EthEvent};
 mod log;
 pub use log::{decode_logs, EthLogDecode, LogMeta};
 
+mod decoder;
+pub use decoder::{DecodedEvent, DecodedLog, EventDecoder};
+
 pub mod stream;
 
 #[cfg(feature = "abigen")]
@@ -77,6 +80,9 @@ if_providers! {
     mod event;
     pub use event::Event;
 
+    mod event_sync;
+    pub use event_sync::{EventSync, EventSyncCheckpoint, SyncedEvent};
+
     #[path = "contract.rs"]
     mod _contract;
     pub use _contract::{Contract, ContractInstance};
@@ -85,11 +91,24 @@ if_providers! {
     pub use call::{ContractCall, ContractError, FunctionCall};
 
     mod factory;
-    pub use factory::{ContractDeployer, ContractDeploymentTx, ContractFactory, DeploymentTxFactory};
+    pub use factory::{
+        ContractDeployer, ContractDeploymentTx, ContractFactory, DeploymentTxFactory,
+        CREATE2_FACTORY,
+    };
+
+    #[cfg(feature = "ethers-solc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ethers-solc")))]
+    mod linker;
+    #[cfg(feature = "ethers-solc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ethers-solc")))]
+    pub use linker::{LibraryLinker, LinkerError};
 
     #[cfg(all(feature = "abigen"))]
     #[cfg_attr(docsrs, doc(cfg(feature = "abigen")))]
-    pub use multicall::{error::MulticallError, Call, Multicall, MulticallContract};
+    pub use multicall::{
+        error::MulticallError, Call, CallHandle, ChunkLimits, Multicall, MulticallContract,
+        DEFAULT_CALL_GAS,
+    };
 
     /// This module exposes low lever builder structures which are only consumed by the
     /// type-safe ABI bindings generators.
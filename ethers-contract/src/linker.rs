@@ -0,0 +1,313 @@
+use crate::{ContractError, DeploymentTxFactory};
+
+use ethers_core::types::{Address, Bytes, H256};
+use ethers_providers::Middleware;
+use ethers_solc::artifacts::{CompactBytecode, CompactContractBytecode};
+
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+};
+
+/// Error thrown when linking and deploying libraries
+#[derive(Debug, thiserror::Error)]
+pub enum LinkerError<M: Middleware> {
+    /// Thrown when deploying a library fails
+    #[error(transparent)]
+    ContractError(#[from] ContractError<M>),
+
+    /// Thrown when the artifact of a library referenced by the bytecode was not provided
+    #[error("no artifact or address for library `{0}`")]
+    MissingLibrary(String),
+
+    /// Thrown when an artifact has no bytecode
+    #[error("artifact of `{0}` has no bytecode")]
+    MissingBytecode(String),
+
+    /// Thrown when placeholders remain in the bytecode after linking
+    #[error("bytecode of `{0}` could not be fully linked")]
+    Unlinked(String),
+}
+
+/// Links the libraries a compiled contract references into its bytecode, deploying the libraries
+/// which were not deployed yet.
+///
+/// Libraries are identified by their fully qualified name `<file>:<library>`, as they appear in
+/// the `link_references` of the bytecode. Libraries referencing other libraries are linked and
+/// deployed first.
+///
+/// With a [`create2`](Self::create2) salt, the libraries are deployed deterministically through
+/// the [`CREATE2_FACTORY`](crate::CREATE2_FACTORY), and libraries already deployed at
+/// their address are reused.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_contract::LibraryLinker;
+/// use ethers_providers::{Http, Provider};
+/// use ethers_solc::Project;
+/// use std::{convert::TryFrom, sync::Arc};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+/// let output = Project::builder().build()?.compile()?;
+/// let contract = output.find_first("Greeter").unwrap().clone().into_contract_bytecode();
+/// let math = output.find_first("Math").unwrap().clone().into_contract_bytecode();
+///
+/// let mut linker = LibraryLinker::<_, Provider<Http>>::new(contract, client)
+///     .library("src/Math.sol:Math", math)
+///     .create2([0u8; 32]);
+/// let factory = linker.link().await?;
+/// println!("libraries: {:?}", linker.libraries());
+/// let greeter = factory.deploy("hello".to_string())?.send().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LibraryLinker<B, M> {
+    client: B,
+    contract: CompactContractBytecode,
+    artifacts: BTreeMap<String, CompactContractBytecode>,
+    deployed: BTreeMap<String, Address>,
+    salt: Option<H256>,
+    confs: usize,
+    legacy: bool,
+    _m: PhantomData<M>,
+}
+
+impl<B, M> LibraryLinker<B, M>
+where
+    B: Borrow<M> + Clone,
+    M: Middleware,
+{
+    /// Creates a linker for the compiled `contract`, deploying the libraries with `client`
+    pub fn new(contract: CompactContractBytecode, client: B) -> Self {
+        Self {
+            client,
+            contract,
+            artifacts: BTreeMap::new(),
+            deployed: BTreeMap::new(),
+            salt: None,
+            confs: 1,
+            legacy: false,
+            _m: PhantomData,
+        }
+    }
+
+    /// Adds the compiled library `name`, deployed if it is referenced
+    pub fn library(mut self, name: impl Into<String>, artifact: CompactContractBytecode) -> Self {
+        self.artifacts.insert(name.into(), artifact);
+        self
+    }
+
+    /// Links the library `name` to the library already deployed at `address`
+    pub fn deployed_library(mut self, name: impl Into<String>, address: Address) -> Self {
+        self.deployed.insert(name.into(), address);
+        self
+    }
+
+    /// Deploys the libraries with CREATE2 using `salt`, reusing the ones already deployed
+    pub fn create2(mut self, salt: impl Into<H256>) -> Self {
+        self.salt = Some(salt.into());
+        self
+    }
+
+    /// Sets the number of confirmations to wait for the library deployment transactions
+    pub fn confirmations(mut self, confirmations: usize) -> Self {
+        self.confs = confirmations;
+        self
+    }
+
+    /// Uses legacy transactions to deploy the libraries
+    pub fn legacy(mut self) -> Self {
+        self.legacy = true;
+        self
+    }
+
+    /// Returns the addresses of the libraries, deployed or provided, by their fully qualified name
+    pub fn libraries(&self) -> &BTreeMap<String, Address> {
+        &self.deployed
+    }
+
+    /// Deploys the referenced libraries which were not deployed yet and returns a factory of the
+    /// contract with the linked bytecode.
+    pub async fn link(&mut self) -> Result<DeploymentTxFactory<B, M>, LinkerError<M>> {
+        while let Some(name) = self.next_library()? {
+            let artifact = &self.artifacts[&name];
+            let bytecode = self.linked(&name, artifact)?;
+            let abi = artifact.abi.clone().unwrap_or_default();
+            let address = self.deploy(abi, bytecode).await?;
+            self.deployed.insert(name, address);
+        }
+
+        let bytecode = self.linked("contract", &self.contract)?;
+        let abi = self.contract.abi.clone().unwrap_or_default();
+        Ok(DeploymentTxFactory::new(abi, bytecode, self.client.clone()))
+    }
+
+    /// Deploys a linked library, returning its address
+    async fn deploy(
+        &self,
+        abi: ethers_core::abi::Abi,
+        bytecode: Bytes,
+    ) -> Result<Address, LinkerError<M>> {
+        let mut deployer = DeploymentTxFactory::new(abi, bytecode, self.client.clone())
+            .deploy(())?
+            .confirmations(self.confs);
+        if self.legacy {
+            deployer = deployer.legacy();
+        }
+
+        let deployer = match self.salt {
+            Some(salt) => deployer.create2(salt),
+            None => return Ok(deployer.send().await?.address()),
+        };
+        let address = deployer.create2_address().expect("deployed with CREATE2");
+        let code = deployer
+            .client()
+            .get_code(address, None)
+            .await
+            .map_err(ContractError::from_middleware_error)?;
+        if code.is_empty() {
+            deployer.send().await?;
+        }
+        Ok(address)
+    }
+
+    /// Returns a library which is referenced, directly or through other libraries, and not
+    /// deployed yet, but whose own references are all deployed.
+    fn next_library(&self) -> Result<Option<String>, LinkerError<M>> {
+        let mut stack = self.unlinked(self.bytecode("contract", &self.contract)?);
+        let mut visited = BTreeSet::new();
+        while let Some(name) = stack.pop() {
+            if !visited.insert(name.clone()) {
+                continue
+            }
+            let artifact =
+                self.artifacts.get(&name).ok_or_else(|| LinkerError::MissingLibrary(name.clone()))?;
+            let references = self.unlinked(self.bytecode(&name, artifact)?);
+            if references.is_empty() {
+                return Ok(Some(name))
+            }
+            stack.extend(references);
+        }
+        Ok(None)
+    }
+
+    /// Returns the fully qualified names of the libraries referenced by `bytecode` which are not
+    /// deployed yet
+    fn unlinked(&self, bytecode: &CompactBytecode) -> Vec<String> {
+        bytecode
+            .link_references
+            .iter()
+            .flat_map(|(file, libraries)| {
+                libraries.keys().map(move |library| format!("{file}:{library}"))
+            })
+            .filter(|name| !self.deployed.contains_key(name))
+            .collect()
+    }
+
+    /// Returns the bytecode of the artifact `name` with the deployed libraries linked
+    fn linked(
+        &self,
+        name: &str,
+        artifact: &CompactContractBytecode,
+    ) -> Result<Bytes, LinkerError<M>> {
+        let mut bytecode = self.bytecode(name, artifact)?.clone();
+        for (file, libraries) in bytecode.link_references.clone() {
+            for library in libraries.into_keys() {
+                if let Some(address) = self.deployed.get(&format!("{file}:{library}")) {
+                    bytecode.link(&file, library, *address);
+                }
+            }
+        }
+        bytecode.object.resolve();
+        bytecode.object.into_bytes().ok_or_else(|| LinkerError::Unlinked(name.to_string()))
+    }
+
+    fn bytecode<'a>(
+        &self,
+        name: &str,
+        artifact: &'a CompactContractBytecode,
+    ) -> Result<&'a CompactBytecode, LinkerError<M>> {
+        artifact.bytecode.as_ref().ok_or_else(|| LinkerError::MissingBytecode(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{MockProvider, Provider};
+    use ethers_solc::artifacts::{BytecodeObject, Offsets};
+    use std::sync::Arc;
+
+    type Linker = LibraryLinker<Arc<Provider<MockProvider>>, Provider<MockProvider>>;
+
+    fn artifact(code: &str, references: &[(&str, &str)]) -> CompactContractBytecode {
+        let mut link_references: BTreeMap<String, BTreeMap<String, Vec<Offsets>>> =
+            BTreeMap::new();
+        for (file, library) in references {
+            link_references
+                .entry(file.to_string())
+                .or_default()
+                .insert(library.to_string(), vec![]);
+        }
+        CompactContractBytecode {
+            abi: None,
+            bytecode: Some(CompactBytecode {
+                object: BytecodeObject::Unlinked(code.to_string()),
+                source_map: None,
+                link_references,
+            }),
+            deployed_bytecode: None,
+        }
+    }
+
+    fn placeholder(name: &str) -> String {
+        format!("__{}__", ethers_solc::utils::library_hash_placeholder(name))
+    }
+
+    #[test]
+    fn links_nested_libraries() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let math = Address::repeat_byte(0x11);
+        let strings = artifact(&format!("60{}", placeholder("src/Math.sol:Math")), &[(
+            "src/Math.sol",
+            "Math",
+        )]);
+        let contract = artifact(
+            &format!("61{}62{}", placeholder("src/Strings.sol:Strings"), placeholder("src/Math.sol:Math")),
+            &[("src/Strings.sol", "Strings"), ("src/Math.sol", "Math")],
+        );
+
+        let linker = Linker::new(contract, Arc::new(provider))
+            .library("src/Strings.sol:Strings", strings.clone())
+            .deployed_library("src/Math.sol:Math", math);
+
+        // `Strings` is deployed first, being referenced by the contract, with `Math` linked
+        assert_eq!(linker.next_library().unwrap(), Some("src/Strings.sol:Strings".to_string()));
+        let strings_code = linker.linked("Strings", &strings).unwrap();
+        assert_eq!(strings_code, Bytes::from([&[0x60], math.as_bytes()].concat()));
+        assert!(matches!(
+            linker.linked("contract", &linker.contract),
+            Err(LinkerError::Unlinked(_))
+        ));
+
+        let linker = linker.deployed_library("src/Strings.sol:Strings", Address::repeat_byte(0x22));
+        assert_eq!(linker.next_library().unwrap(), None);
+        let code = linker.linked("contract", &linker.contract).unwrap();
+        assert_eq!(
+            code,
+            Bytes::from([&[0x61], &[0x22; 20][..], &[0x62], math.as_bytes()].concat())
+        );
+    }
+
+    #[test]
+    fn requires_library_artifacts() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let contract = artifact(&placeholder("src/Math.sol:Math"), &[("src/Math.sol", "Math")]);
+        let linker = Linker::new(contract, Arc::new(provider));
+        assert!(matches!(linker.next_library(), Err(LinkerError::MissingLibrary(_))));
+    }
+}
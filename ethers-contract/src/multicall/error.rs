@@ -21,6 +21,10 @@ pub enum MulticallError<M: Middleware> {
     /// Contract call reverted when not allowed
     #[error("Illegal revert: Multicall2 call reverted when it wasn't allowed to.")]
     IllegalRevert,
+
+    /// Call can't be aggregated
+    #[error("Invalid call: the target must be an address and the call must have calldata.")]
+    InvalidCall,
 }
 
 impl<M: Middleware> From<abi::Error> for MulticallError<M> {
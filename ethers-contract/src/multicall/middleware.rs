@@ -15,13 +15,26 @@ use ethers_core::{
     },
 };
 use ethers_providers::{spoof::State, Middleware, PendingTransaction, RawCall};
-use std::{fmt, result::Result as StdResult, sync::Arc};
+use futures_util::future::try_join_all;
+use std::{
+    fmt,
+    marker::PhantomData,
+    result::Result as StdResult,
+    sync::{Arc, Mutex},
+};
 
 pub use super::contract::Multicall3 as MulticallContract;
 
 /// Type alias for `Result<T, MulticallError<M>>`
 pub type Result<T, M> = StdResult<T, super::error::MulticallError<M>>;
 
+/// The gas assumed for calls without a gas limit when chunking by gas, see
+/// [`Multicall::max_chunk_gas`]
+pub const DEFAULT_CALL_GAS: u64 = 100_000;
+
+/// The result of a call, shared between the [`Multicall`] and the [`CallHandle`] of the call
+type CallSlot = Arc<Mutex<Option<StdResult<Token, Bytes>>>>;
+
 /// Helper struct for managing calls to be made to the `function` in smart contract `target`
 /// with `data`.
 #[derive(Clone, Debug)]
@@ -29,8 +42,93 @@ pub struct Call {
     target: Address,
     data: Bytes,
     value: U256,
+    gas: Option<U256>,
     allow_failure: bool,
     function: Function,
+    slot: Option<CallSlot>,
+}
+
+impl Call {
+    /// The gas counted for this call when chunking by gas
+    fn chunk_gas(&self) -> u64 {
+        self.gas.map_or(DEFAULT_CALL_GAS, |gas| gas.low_u64())
+    }
+}
+
+/// The limits of the chunks the calls of a [`Multicall`] are split into, every chunk being sent in
+/// its own aggregate call. No limit is set by default, sending all calls at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChunkLimits {
+    /// The maximum number of calls in a chunk
+    pub max_calls: Option<usize>,
+    /// The maximum size of the calldata of the calls in a chunk, in bytes
+    pub max_calldata_size: Option<usize>,
+    /// The maximum sum of the gas limits of the calls in a chunk
+    pub max_gas: Option<u64>,
+}
+
+impl ChunkLimits {
+    /// Splits `calls` into chunks within the limits, keeping their order. Every chunk holds at
+    /// least one call, even if that call alone exceeds a limit.
+    fn split<'a>(&self, calls: &'a [Call]) -> Vec<&'a [Call]> {
+        let mut chunks = Vec::new();
+        let (mut start, mut size, mut gas) = (0, 0usize, 0u64);
+        for (i, call) in calls.iter().enumerate() {
+            let call_gas = call.chunk_gas();
+            let exceeds = self.max_calls.map_or(false, |max| i - start >= max) ||
+                self.max_calldata_size.map_or(false, |max| size + call.data.len() > max) ||
+                self.max_gas.map_or(false, |max| gas.saturating_add(call_gas) > max);
+            if exceeds && i > start {
+                chunks.push(&calls[start..i]);
+                (start, size, gas) = (i, 0, 0);
+            }
+            size += call.data.len();
+            gas = gas.saturating_add(call_gas);
+        }
+        if start < calls.len() || chunks.is_empty() {
+            chunks.push(&calls[start..]);
+        }
+        chunks
+    }
+}
+
+/// A handle to the result of a call added with [`Multicall::add_call_handle`], available once the
+/// calls were made with [`Multicall::call`], [`Multicall::call_array`] or [`Multicall::call_raw`].
+pub struct CallHandle<M, D> {
+    slot: CallSlot,
+    _marker: PhantomData<fn() -> (M, D)>,
+}
+
+impl<M: Middleware, D: Detokenize> CallHandle<M, D> {
+    /// Returns the decoded result of the call, or `None` if the calls were not made yet.
+    ///
+    /// Fails with [`ContractError::Revert`] if the call was allowed to fail and did, or returned
+    /// no data.
+    pub fn get(&self) -> Option<StdResult<D, ContractError<M>>> {
+        let result = self.slot.lock().unwrap().clone()?;
+        Some(match result {
+            Ok(token) => D::from_tokens(vec![token]).map_err(Into::into),
+            Err(data) => Err(ContractError::Revert(data)),
+        })
+    }
+
+    /// True if the calls were made and the result is available
+    pub fn is_resolved(&self) -> bool {
+        self.slot.lock().unwrap().is_some()
+    }
+}
+
+// Manually implement Clone and Debug to avoid trait bounds.
+impl<M, D> Clone for CallHandle<M, D> {
+    fn clone(&self) -> Self {
+        Self { slot: self.slot.clone(), _marker: PhantomData }
+    }
+}
+
+impl<M, D> fmt::Debug for CallHandle<M, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallHandle").field("result", &self.slot).finish()
+    }
 }
 
 /// A Multicall is an abstraction for sending batched calls/transactions to the Ethereum blockchain.
@@ -148,6 +246,9 @@ pub struct Multicall<M> {
     /// The state overrides of the Multicall aggregate
     pub state: Option<State>,
 
+    /// The limits of the chunks the calls are split into when calling them.
+    pub chunk_limits: ChunkLimits,
+
     /// The internal call vector.
     calls: Vec<Call>,
 }
@@ -162,6 +263,7 @@ impl<M> Clone for Multicall<M> {
             block: self.block,
             calls: self.calls.clone(),
             state: self.state.clone(),
+            chunk_limits: self.chunk_limits,
         }
     }
 }
@@ -174,6 +276,7 @@ impl<M> fmt::Debug for Multicall<M> {
             .field("legacy", &self.legacy)
             .field("block", &self.block)
             .field("state", &self.state)
+            .field("chunk_limits", &self.chunk_limits)
             .field("calls", &self.calls)
             .finish()
     }
@@ -222,6 +325,7 @@ impl<M: Middleware> Multicall<M> {
             legacy: false,
             block: None,
             state: None,
+            chunk_limits: ChunkLimits::default(),
             calls: vec![],
             contract,
         })
@@ -272,6 +376,7 @@ impl<M: Middleware> Multicall<M> {
             legacy: false,
             block: None,
             state: None,
+            chunk_limits: ChunkLimits::default(),
             calls: vec![],
             contract,
         })
@@ -318,6 +423,40 @@ impl<M: Middleware> Multicall<M> {
         self
     }
 
+    /// Sets the maximum number of calls sent in a single aggregate call.
+    ///
+    /// When calling, the calls are split into chunks within all limits, which are sent
+    /// concurrently. Transactions broadcast with [`send`] are never split.
+    ///
+    /// [`send`]: #method.send
+    pub fn max_calls_per_chunk(mut self, max_calls: usize) -> Self {
+        self.chunk_limits.max_calls = Some(max_calls);
+        self
+    }
+
+    /// Sets the maximum size of the calldata of the calls sent in a single aggregate call, in
+    /// bytes, to stay under the request size limit of the node.
+    ///
+    /// See [`max_calls_per_chunk`] for details.
+    ///
+    /// [`max_calls_per_chunk`]: #method.max_calls_per_chunk
+    pub fn max_chunk_size(mut self, max_calldata_size: usize) -> Self {
+        self.chunk_limits.max_calldata_size = Some(max_calldata_size);
+        self
+    }
+
+    /// Sets the maximum sum of the gas limits of the calls sent in a single aggregate call, to
+    /// stay under the `eth_call` gas cap of the node.
+    ///
+    /// Calls count with the gas limit of their [`ContractCall`], or [`DEFAULT_CALL_GAS`] if it has
+    /// none. See [`max_calls_per_chunk`] for details.
+    ///
+    /// [`max_calls_per_chunk`]: #method.max_calls_per_chunk
+    pub fn max_chunk_gas(mut self, max_gas: u64) -> Self {
+        self.chunk_limits.max_gas = Some(max_gas);
+        self
+    }
+
     /// Appends a `call` to the list of calls of the Multicall instance.
     ///
     /// Version specific details:
@@ -333,27 +472,98 @@ impl<M: Middleware> Multicall<M> {
         call: ContractCall<M, D>,
         allow_failure: bool,
     ) -> &mut Self {
+        self.push_call(call, allow_failure, None);
+        self
+    }
+
+    /// Appends a `call` to the list of calls of the Multicall instance, returning a handle to its
+    /// typed result.
+    ///
+    /// Unlike [`call`], which decodes the results of all calls into a single tuple, every handle
+    /// resolves to the result of its own call once the calls were made. See [`add_call`] for the
+    /// meaning of `allow_failure`.
+    ///
+    /// Returns [`error::MulticallError::InvalidCall`] if the call can't be aggregated, i.e. its
+    /// target is an ENS name instead of an address, or it has no calldata.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use ethers_core::types::{Address, U256};
+    /// # use ethers_contract::{Contract, Multicall};
+    /// # use ethers_providers::{Provider, Http};
+    /// # use std::{convert::TryFrom, sync::Arc};
+    /// # let client = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+    /// # let abi = ethers_core::abi::parse_abi(&[
+    /// #     "function getReserves() view returns (uint112, uint112, uint32)",
+    /// # ])?;
+    /// # let pools: Vec<Address> = vec![];
+    /// let mut multicall = Multicall::new(client.clone(), None).await?.max_calls_per_chunk(500);
+    /// let handles: Vec<_> = pools
+    ///     .iter()
+    ///     .map(|pool| {
+    ///         let pool = Contract::new(*pool, abi.clone(), client.clone());
+    ///         let call = pool.method::<_, (u128, u128, u32)>("getReserves", ()).unwrap();
+    ///         multicall.add_call_handle(call, true)
+    ///     })
+    ///     .collect::<Result<_, _>>()?;
+    ///
+    /// multicall.call_raw().await?;
+    /// for handle in handles {
+    ///     let (reserve0, reserve1, _) = handle.get().unwrap()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`call`]: #method.call
+    /// [`add_call`]: #method.add_call
+    /// [`error::MulticallError::InvalidCall`]: error::MulticallError::InvalidCall
+    pub fn add_call_handle<D: Detokenize>(
+        &mut self,
+        call: ContractCall<M, D>,
+        allow_failure: bool,
+    ) -> Result<CallHandle<M, D>, M> {
+        let slot = CallSlot::default();
+        if !self.push_call(call, allow_failure, Some(slot.clone())) {
+            return Err(error::MulticallError::InvalidCall)
+        }
+        Ok(CallHandle { slot, _marker: PhantomData })
+    }
+
+    /// Appends `call` to the list of calls, returns `false` if it can't be aggregated.
+    fn push_call<D: Detokenize>(
+        &mut self,
+        call: ContractCall<M, D>,
+        allow_failure: bool,
+        slot: Option<CallSlot>,
+    ) -> bool {
+        let gas = call.tx.gas().copied();
         let (to, data, value) = match call.tx {
             TypedTransaction::Legacy(tx) => (tx.to, tx.data, tx.value),
             TypedTransaction::Eip2930(tx) => (tx.tx.to, tx.tx.data, tx.tx.value),
             TypedTransaction::Eip1559(tx) => (tx.to, tx.data, tx.value),
+            TypedTransaction::Eip4844(tx) => (tx.to, tx.data, tx.value),
+            TypedTransaction::Eip7702(tx) => (tx.to, tx.data, tx.value),
             #[cfg(feature = "optimism")]
             TypedTransaction::DepositTransaction(tx) => (tx.tx.to, tx.tx.data, tx.tx.value),
         };
         if data.is_none() && !call.function.outputs.is_empty() {
-            return self
+            return false
         }
-        if let Some(NameOrAddress::Address(target)) = to {
-            let call = Call {
+        let Some(NameOrAddress::Address(target)) = to else { return false };
+        let call = Call {
                 target,
                 data: data.unwrap_or_default(),
                 value: value.unwrap_or_default(),
+                gas,
                 allow_failure,
-                function: call.function,
-            };
-            self.calls.push(call);
-        }
-        self
+            function: call.function,
+            slot,
+        };
+        self.calls.push(call);
+        true
     }
 
     /// Appends multiple `call`s to the list of calls of the Multicall instance.
@@ -608,6 +818,9 @@ impl<M: Middleware> Multicall<M> {
     ///
     /// If the Multicall version is 1, this will always be a vector of `Ok`.
     ///
+    /// The calls are split into chunks within the [`chunk_limits`](#structfield.chunk_limits),
+    /// which are sent concurrently. The handles of the calls are resolved with their results.
+    ///
     /// # Errors
     ///
     /// Returns a [`error::MulticallError`] if there are any errors in the RPC call.
@@ -630,17 +843,32 @@ impl<M: Middleware> Multicall<M> {
     /// # }
     /// ```
     pub async fn call_raw(&self) -> Result<Vec<StdResult<Token, Bytes>>, M> {
+        let chunks = self.chunk_limits.split(&self.calls);
+        let results = try_join_all(chunks.into_iter().map(|calls| self.call_chunk(calls))).await?;
+        let results: Vec<_> = results.into_iter().flatten().collect();
+
+        for (call, result) in self.calls.iter().zip(&results) {
+            if let Some(slot) = &call.slot {
+                *slot.lock().unwrap() = Some(result.clone());
+            }
+        }
+        Ok(results)
+    }
+
+    /// Makes the `calls` in a single aggregate call.
+    async fn call_chunk(&self, calls: &[Call]) -> Result<Vec<StdResult<Token, Bytes>>, M> {
         // Different call result types based on version
         match self.version {
             // Wrap the return data with `success: true` since version 1 reverts if any call failed
             MulticallVersion::Multicall => {
-                let call = self.as_aggregate();
+                let call = self.as_aggregate(calls);
                 let (_, bytes) = if let Some(state) = &self.state {
                     ContractCall::call_raw(&call).state(state).await?
                 } else {
                     ContractCall::call(&call).await?
                 };
-                self.parse_call_result(
+                Self::parse_call_result(
+                    calls,
                     bytes
                         .into_iter()
                         .map(|return_data| MulticallResult { success: true, return_data }),
@@ -649,16 +877,16 @@ impl<M: Middleware> Multicall<M> {
             // Same result type (`MulticallResult`)
             MulticallVersion::Multicall2 | MulticallVersion::Multicall3 => {
                 let call = if self.version.is_v2() {
-                    self.as_try_aggregate()
+                    self.as_try_aggregate(calls)
                 } else {
-                    self.as_aggregate_3()
+                    self.as_aggregate_3(calls)
                 };
                 let results = if let Some(state) = &self.state {
                     ContractCall::call_raw(&call).state(state).await?
                 } else {
                     ContractCall::call(&call).await?
                 };
-                self.parse_call_result(results.into_iter())
+                Self::parse_call_result(calls, results.into_iter())
             }
         }
     }
@@ -666,11 +894,11 @@ impl<M: Middleware> Multicall<M> {
     /// For each call and its `return_data`: if `success` is true, parses `return_data` with the
     /// call's function outputs, otherwise returns the bytes in `Err`.
     fn parse_call_result(
-        &self,
+        calls: &[Call],
         return_data: impl Iterator<Item = MulticallResult>,
     ) -> Result<Vec<StdResult<Token, Bytes>>, M> {
-        let mut results = Vec::with_capacity(self.calls.len());
-        for (call, MulticallResult { success, return_data }) in self.calls.iter().zip(return_data) {
+        let mut results = Vec::with_capacity(calls.len());
+        for (call, MulticallResult { success, return_data }) in calls.iter().zip(return_data) {
             let result = if !success || return_data.is_empty() {
                 // v2: In the function call to `tryAggregate`, the `allow_failure` check
                 // is done on a per-transaction basis, and we set this transaction-wide
@@ -721,9 +949,9 @@ impl<M: Middleware> Multicall<M> {
     /// ```
     pub async fn send(&self) -> Result<PendingTransaction<'_, M::Provider>, M> {
         let tx = match self.version {
-            MulticallVersion::Multicall => self.as_aggregate().tx,
-            MulticallVersion::Multicall2 => self.as_try_aggregate().tx,
-            MulticallVersion::Multicall3 => self.as_aggregate_3_value().tx,
+            MulticallVersion::Multicall => self.as_aggregate(&self.calls).tx,
+            MulticallVersion::Multicall2 => self.as_try_aggregate(&self.calls).tx,
+            MulticallVersion::Multicall3 => self.as_aggregate_3_value(&self.calls).tx,
         };
         let client: &M = self.contract.client_ref();
         client.send_transaction(tx, self.block.map(Into::into)).await.map_err(|e| {
@@ -733,12 +961,11 @@ impl<M: Middleware> Multicall<M> {
 
     /// v1
     #[inline]
-    fn as_aggregate(&self) -> ContractCall<M, (U256, Vec<Bytes>)> {
+    fn as_aggregate(&self, calls: &[Call]) -> ContractCall<M, (U256, Vec<Bytes>)> {
         // Map the calls vector into appropriate types for `aggregate` function
-        let calls: Vec<Multicall1Call> = self
-            .calls
-            .clone()
-            .into_iter()
+        let calls: Vec<Multicall1Call> = calls
+            .iter()
+            .cloned()
             .map(|call| Multicall1Call { target: call.target, call_data: call.data })
             .collect();
 
@@ -750,13 +977,12 @@ impl<M: Middleware> Multicall<M> {
 
     /// v2
     #[inline]
-    fn as_try_aggregate(&self) -> ContractCall<M, Vec<MulticallResult>> {
+    fn as_try_aggregate(&self, calls: &[Call]) -> ContractCall<M, Vec<MulticallResult>> {
         let mut allow_failure = false;
         // Map the calls vector into appropriate types for `try_aggregate` function
-        let calls: Vec<Multicall1Call> = self
-            .calls
-            .clone()
-            .into_iter()
+        let calls: Vec<Multicall1Call> = calls
+            .iter()
+            .cloned()
             .map(|call| {
                 // Allow entire call failure if at least one call is allowed to fail.
                 // To avoid iterating multiple times, equivalent of:
@@ -774,12 +1000,11 @@ impl<M: Middleware> Multicall<M> {
 
     /// v3
     #[inline]
-    fn as_aggregate_3(&self) -> ContractCall<M, Vec<MulticallResult>> {
+    fn as_aggregate_3(&self, calls: &[Call]) -> ContractCall<M, Vec<MulticallResult>> {
         // Map the calls vector into appropriate types for `aggregate_3` function
-        let calls: Vec<Multicall3Call> = self
-            .calls
-            .clone()
-            .into_iter()
+        let calls: Vec<Multicall3Call> = calls
+            .iter()
+            .cloned()
             .map(|call| Multicall3Call {
                 target: call.target,
                 call_data: call.data,
@@ -795,13 +1020,12 @@ impl<M: Middleware> Multicall<M> {
 
     /// v3 + values (only .send())
     #[inline]
-    fn as_aggregate_3_value(&self) -> ContractCall<M, Vec<MulticallResult>> {
+    fn as_aggregate_3_value(&self, calls: &[Call]) -> ContractCall<M, Vec<MulticallResult>> {
         // Map the calls vector into appropriate types for `aggregate_3_value` function
         let mut total_value = U256::zero();
-        let calls: Vec<Multicall3CallValue> = self
-            .calls
-            .clone()
-            .into_iter()
+        let value_calls: Vec<Multicall3CallValue> = calls
+            .iter()
+            .cloned()
             .map(|call| {
                 total_value += call.value;
                 Multicall3CallValue {
@@ -815,11 +1039,11 @@ impl<M: Middleware> Multicall<M> {
 
         if total_value.is_zero() {
             // No value is being sent
-            self.as_aggregate_3()
+            self.as_aggregate_3(calls)
         } else {
             // Construct the ContractCall for `aggregate_3_value` function to broadcast the
             // transaction
-            let contract_call = self.contract.aggregate_3_value(calls);
+            let contract_call = self.contract.aggregate_3_value(value_calls);
 
             self.set_call_flags(contract_call).value(total_value)
         }
@@ -849,3 +1073,122 @@ impl<'a, M: Middleware> RawCall<'a> for Multicall<M> {
         self.state(state.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Contract;
+    use ethers_core::abi::{self, parse_abi};
+    use ethers_providers::Provider;
+
+    fn call(data: usize, gas: Option<u64>) -> Call {
+        Call {
+            target: Address::zero(),
+            data: vec![0; data].into(),
+            value: U256::zero(),
+            gas: gas.map(Into::into),
+            allow_failure: false,
+            function: parse_abi(&["function f()"]).unwrap().function("f").unwrap().clone(),
+            slot: None,
+        }
+    }
+
+    #[test]
+    fn splits_calls_into_chunks() {
+        let calls: Vec<_> = (0..5).map(|i| call(10 * (i + 1), None)).collect();
+        let lens = |limits: ChunkLimits| {
+            limits.split(&calls).into_iter().map(<[Call]>::len).collect::<Vec<_>>()
+        };
+
+        assert_eq!(lens(ChunkLimits::default()), vec![5]);
+        assert_eq!(lens(ChunkLimits { max_calls: Some(2), ..Default::default() }), vec![2, 2, 1]);
+        // 10 + 20 + 30 | 40 | 50
+        assert_eq!(
+            lens(ChunkLimits { max_calldata_size: Some(60), ..Default::default() }),
+            vec![3, 1, 1]
+        );
+        // a call exceeding a limit on its own gets a chunk of its own
+        assert_eq!(
+            lens(ChunkLimits { max_calldata_size: Some(15), ..Default::default() }),
+            vec![1, 1, 1, 1, 1]
+        );
+
+        let calls = vec![call(0, Some(30_000_000)), call(0, None), call(0, Some(40_000_000))];
+        let limits = ChunkLimits { max_gas: Some(50_000_000), ..Default::default() };
+        let chunks = limits.split(&calls);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![2, 1]);
+
+        assert_eq!(ChunkLimits::default().split(&[]).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn resolves_handles_of_chunked_calls() {
+        let (provider, mock) = Provider::mocked();
+        let client = Arc::new(provider);
+        let abi = parse_abi(&[
+            "function balanceOf(address) view returns (uint256)",
+            "function symbol() view returns (string)",
+        ])
+        .unwrap();
+        let token = Contract::new(Address::repeat_byte(1), abi, client.clone());
+
+        let mut multicall = Multicall::new_with_chain_id(client, None, Some(1u64))
+            .unwrap()
+            .max_calls_per_chunk(2);
+        let balance = multicall
+            .add_call_handle(token.method::<_, U256>("balanceOf", Address::zero()).unwrap(), false)
+            .unwrap();
+        let symbol =
+            multicall.add_call_handle(token.method::<_, String>("symbol", ()).unwrap(), true).unwrap();
+        let failed = multicall
+            .add_call_handle(token.method::<_, U256>("balanceOf", Address::zero()).unwrap(), true)
+            .unwrap();
+        assert!(balance.get().is_none());
+
+        let result = |success: bool, data: Vec<u8>| {
+            Token::Tuple(vec![Token::Bool(success), Token::Bytes(data)])
+        };
+        let first = abi::encode(&[Token::Array(vec![
+            result(true, abi::encode(&[Token::Uint(100.into())])),
+            result(true, abi::encode(&[Token::String("WETH".to_string())])),
+        ])]);
+        let second = abi::encode(&[Token::Array(vec![result(false, vec![])])]);
+        // the chunks are requested in order, and the mock responds in reverse order
+        mock.push::<Bytes, Bytes>(second.into()).unwrap();
+        mock.push::<Bytes, Bytes>(first.into()).unwrap();
+
+        let results = multicall.call_raw().await.unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(balance.get().unwrap().unwrap(), U256::from(100));
+        assert_eq!(symbol.get().unwrap().unwrap(), "WETH");
+        assert!(failed.get().unwrap().unwrap_err().is_revert());
+    }
+
+    #[test]
+    fn rejects_handles_of_invalid_calls() {
+        let (provider, _) = Provider::mocked();
+        let client = Arc::new(provider);
+        let abi = parse_abi(&["function symbol() view returns (string)"]).unwrap();
+        let token = Contract::new(Address::repeat_byte(1), abi, client.clone());
+        let mut multicall = Multicall::new_with_chain_id(client, None, Some(1u64)).unwrap();
+
+        let mut ens = token.method::<_, String>("symbol", ()).unwrap();
+        ens.tx.set_to("token.eth");
+        assert!(matches!(
+            multicall.add_call_handle(ens, true),
+            Err(error::MulticallError::InvalidCall)
+        ));
+
+        let mut no_data = token.method::<_, String>("symbol", ()).unwrap().legacy();
+        if let TypedTransaction::Legacy(tx) = &mut no_data.tx {
+            tx.data = None;
+        }
+        assert!(matches!(
+            multicall.add_call_handle(no_data, true),
+            Err(error::MulticallError::InvalidCall)
+        ));
+
+        assert!(multicall.calls.is_empty());
+    }
+}
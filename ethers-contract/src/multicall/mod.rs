@@ -7,7 +7,9 @@ pub mod constants;
 
 if_providers! {
     mod middleware;
-    pub use middleware::{Call, Multicall, MulticallContract};
+    pub use middleware::{
+        Call, CallHandle, ChunkLimits, Multicall, MulticallContract, DEFAULT_CALL_GAS,
+    };
 
     pub mod error;
 }
@@ -1,7 +1,10 @@
 // Modified from <https://github.com/tomusdrw/rust-web3/blob/master/src/types/block.rs>
 
 #[cfg(not(feature = "celo"))]
-use crate::types::Withdrawal;
+use crate::types::{
+    transaction::eip4844::{calc_blob_gasprice, TARGET_BLOB_GAS_PER_BLOCK},
+    Withdrawal,
+};
 use crate::types::{Address, Bloom, Bytes, Transaction, TxHash, H256, U256, U64};
 use chrono::{DateTime, TimeZone, Utc};
 use serde::{
@@ -192,6 +195,24 @@ impl<TX> Block<TX> {
         }
     }
 
+    /// The price of a unit of blob gas in this block, as a function of its excess blob gas.
+    /// Returns `None` if the block predates EIP-4844.
+    /// Reference: <https://eips.ethereum.org/EIPS/eip-4844>
+    #[cfg(not(feature = "celo"))]
+    pub fn blob_base_fee(&self) -> Option<U256> {
+        self.excess_blob_gas.map(calc_blob_gasprice)
+    }
+
+    /// The next block's price of a unit of blob gas, it is a function of the parent block's
+    /// excess blob gas and blob gas usage.
+    /// Reference: <https://eips.ethereum.org/EIPS/eip-4844>
+    #[cfg(not(feature = "celo"))]
+    pub fn next_block_blob_base_fee(&self) -> Option<U256> {
+        let excess_blob_gas = self.excess_blob_gas? + self.blob_gas_used?;
+        let target = U256::from(TARGET_BLOB_GAS_PER_BLOCK);
+        Some(calc_blob_gasprice(excess_blob_gas.saturating_sub(target)))
+    }
+
     /// Parse [`Self::timestamp`] into a [`DateTime<Utc>`].
     ///
     /// # Errors
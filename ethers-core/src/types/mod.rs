@@ -15,6 +15,8 @@ pub mod transaction;
 pub use transaction::{
     eip1559::Eip1559TransactionRequest,
     eip2930::Eip2930TransactionRequest,
+    eip4844::Eip4844TransactionRequest,
+    eip7702::Eip7702TransactionRequest,
     request::TransactionRequest,
     response::{Transaction, TransactionReceipt},
 };
@@ -84,3 +86,6 @@ pub use opcode::Opcode;
 
 mod withdrawal;
 pub use withdrawal::Withdrawal;
+
+mod user_operation;
+pub use user_operation::*;
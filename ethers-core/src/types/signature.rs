@@ -199,7 +199,7 @@ impl open_fastrlp::Encodable for Signature {
 /// Recovery values of 2 and 3 are unlikely to occur in practice. In the vanishingly unlikely event
 /// that you encounter an EIP-155 signature with a recovery value of 2 or 3, you should normalize
 /// out of band.
-fn normalize_recovery_id(v: u64) -> u8 {
+pub(crate) fn normalize_recovery_id(v: u64) -> u8 {
     match v {
         // Case 0: raw/bare
         v @ 0..=26 => (v % 4) as u8,
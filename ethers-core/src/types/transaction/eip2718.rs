@@ -1,6 +1,8 @@
 use super::{
     eip1559::{Eip1559RequestError, Eip1559TransactionRequest},
     eip2930::{AccessList, Eip2930RequestError, Eip2930TransactionRequest},
+    eip4844::{Eip4844RequestError, Eip4844TransactionRequest},
+    eip7702::{Eip7702RequestError, Eip7702TransactionRequest},
     request::RequestError,
 };
 use crate::{
@@ -22,6 +24,8 @@ use super::optimism::{DepositTransaction, DepositTransactionError};
 /// 1. Legacy (pre-EIP2718) [`TransactionRequest`]
 /// 2. EIP2930 (state access lists) [`Eip2930TransactionRequest`]
 /// 3. EIP1559 [`Eip1559TransactionRequest`]
+/// 4. EIP4844 (blob transactions) [`Eip4844TransactionRequest`]
+/// 5. EIP7702 (set code transactions) [`Eip7702TransactionRequest`]
 ///
 /// To support Kovan and other non-London-compatbile networks, please enable
 /// the `legacy` crate feature. This will disable the `type` flag in the
@@ -40,6 +44,12 @@ pub enum TypedTransaction {
     // 0x02
     #[serde(rename = "0x02", alias = "0x2")]
     Eip1559(Eip1559TransactionRequest),
+    // 0x03
+    #[serde(rename = "0x03", alias = "0x3")]
+    Eip4844(Eip4844TransactionRequest),
+    // 0x04
+    #[serde(rename = "0x04", alias = "0x4")]
+    Eip7702(Eip7702TransactionRequest),
     // 0x7E
     #[cfg(feature = "optimism")]
     #[serde(rename = "0x7E")]
@@ -58,6 +68,12 @@ pub enum TypedTransactionError {
     /// When decoding a signed Eip2930 transaction
     #[error(transparent)]
     Eip2930Error(#[from] Eip2930RequestError),
+    /// When decoding a signed Eip4844 transaction
+    #[error(transparent)]
+    Eip4844Error(#[from] Eip4844RequestError),
+    /// When decoding a signed Eip7702 transaction
+    #[error(transparent)]
+    Eip7702Error(#[from] Eip7702RequestError),
     /// When decoding a signed Optimism Deposited transaction
     #[cfg(feature = "optimism")]
     #[error(transparent)]
@@ -95,6 +111,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.from.as_ref(),
             Eip2930(inner) => inner.tx.from.as_ref(),
             Eip1559(inner) => inner.from.as_ref(),
+            Eip4844(inner) => inner.from.as_ref(),
+            Eip7702(inner) => inner.from.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.from.as_ref(),
         }
@@ -105,6 +123,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.from = Some(from),
             Eip2930(inner) => inner.tx.from = Some(from),
             Eip1559(inner) => inner.from = Some(from),
+            Eip4844(inner) => inner.from = Some(from),
+            Eip7702(inner) => inner.from = Some(from),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.from = Some(from),
         };
@@ -116,6 +136,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.to.as_ref(),
             Eip2930(inner) => inner.tx.to.as_ref(),
             Eip1559(inner) => inner.to.as_ref(),
+            Eip4844(inner) => inner.to.as_ref(),
+            Eip7702(inner) => inner.to.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.to.as_ref(),
         }
@@ -131,6 +153,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.to = Some(to),
             Eip2930(inner) => inner.tx.to = Some(to),
             Eip1559(inner) => inner.to = Some(to),
+            Eip4844(inner) => inner.to = Some(to),
+            Eip7702(inner) => inner.to = Some(to),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.to = Some(to),
         };
@@ -142,6 +166,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.nonce.as_ref(),
             Eip2930(inner) => inner.tx.nonce.as_ref(),
             Eip1559(inner) => inner.nonce.as_ref(),
+            Eip4844(inner) => inner.nonce.as_ref(),
+            Eip7702(inner) => inner.nonce.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.nonce.as_ref(),
         }
@@ -153,6 +179,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.nonce = Some(nonce),
             Eip2930(inner) => inner.tx.nonce = Some(nonce),
             Eip1559(inner) => inner.nonce = Some(nonce),
+            Eip4844(inner) => inner.nonce = Some(nonce),
+            Eip7702(inner) => inner.nonce = Some(nonce),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.nonce = Some(nonce),
         };
@@ -164,6 +192,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.value.as_ref(),
             Eip2930(inner) => inner.tx.value.as_ref(),
             Eip1559(inner) => inner.value.as_ref(),
+            Eip4844(inner) => inner.value.as_ref(),
+            Eip7702(inner) => inner.value.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.value.as_ref(),
         }
@@ -175,6 +205,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.value = Some(value),
             Eip2930(inner) => inner.tx.value = Some(value),
             Eip1559(inner) => inner.value = Some(value),
+            Eip4844(inner) => inner.value = Some(value),
+            Eip7702(inner) => inner.value = Some(value),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.value = Some(value),
         };
@@ -186,6 +218,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.gas.as_ref(),
             Eip2930(inner) => inner.tx.gas.as_ref(),
             Eip1559(inner) => inner.gas.as_ref(),
+            Eip4844(inner) => inner.gas.as_ref(),
+            Eip7702(inner) => inner.gas.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.gas.as_ref(),
         }
@@ -196,6 +230,8 @@ impl TypedTransaction {
             Legacy(inner) => &mut inner.gas,
             Eip2930(inner) => &mut inner.tx.gas,
             Eip1559(inner) => &mut inner.gas,
+            Eip4844(inner) => &mut inner.gas,
+            Eip7702(inner) => &mut inner.gas,
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => &mut inner.tx.gas,
         }
@@ -207,6 +243,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.gas = Some(gas),
             Eip2930(inner) => inner.tx.gas = Some(gas),
             Eip1559(inner) => inner.gas = Some(gas),
+            Eip4844(inner) => inner.gas = Some(gas),
+            Eip7702(inner) => inner.gas = Some(gas),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.gas = Some(gas),
         };
@@ -225,6 +263,16 @@ impl TypedTransaction {
                     (max_fee, None) => max_fee,
                 }
             }
+            Eip4844(inner) => match (inner.max_fee_per_gas, inner.max_priority_fee_per_gas) {
+                (Some(max_fee), Some(_)) => Some(max_fee),
+                (None, prio_fee) => prio_fee,
+                (max_fee, None) => max_fee,
+            },
+            Eip7702(inner) => match (inner.max_fee_per_gas, inner.max_priority_fee_per_gas) {
+                (Some(max_fee), Some(_)) => Some(max_fee),
+                (None, prio_fee) => prio_fee,
+                (max_fee, None) => max_fee,
+            },
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.gas_price,
         }
@@ -239,6 +287,14 @@ impl TypedTransaction {
                 inner.max_fee_per_gas = Some(gas_price);
                 inner.max_priority_fee_per_gas = Some(gas_price);
             }
+            Eip4844(inner) => {
+                inner.max_fee_per_gas = Some(gas_price);
+                inner.max_priority_fee_per_gas = Some(gas_price);
+            }
+            Eip7702(inner) => {
+                inner.max_fee_per_gas = Some(gas_price);
+                inner.max_priority_fee_per_gas = Some(gas_price);
+            }
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.gas_price = Some(gas_price),
         };
@@ -250,6 +306,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.chain_id,
             Eip2930(inner) => inner.tx.chain_id,
             Eip1559(inner) => inner.chain_id,
+            Eip4844(inner) => inner.chain_id,
+            Eip7702(inner) => inner.chain_id,
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.chain_id,
         }
@@ -261,6 +319,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.chain_id = Some(chain_id),
             Eip2930(inner) => inner.tx.chain_id = Some(chain_id),
             Eip1559(inner) => inner.chain_id = Some(chain_id),
+            Eip4844(inner) => inner.chain_id = Some(chain_id),
+            Eip7702(inner) => inner.chain_id = Some(chain_id),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.chain_id = Some(chain_id),
         };
@@ -272,6 +332,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.data.as_ref(),
             Eip2930(inner) => inner.tx.data.as_ref(),
             Eip1559(inner) => inner.data.as_ref(),
+            Eip4844(inner) => inner.data.as_ref(),
+            Eip7702(inner) => inner.data.as_ref(),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.data.as_ref(),
         }
@@ -282,6 +344,8 @@ impl TypedTransaction {
             Legacy(_) => None,
             Eip2930(inner) => Some(&inner.access_list),
             Eip1559(inner) => Some(&inner.access_list),
+            Eip4844(inner) => Some(&inner.access_list),
+            Eip7702(inner) => Some(&inner.access_list),
             #[cfg(feature = "optimism")]
             DepositTransaction(_) => None,
         }
@@ -292,6 +356,8 @@ impl TypedTransaction {
             Legacy(_) => {}
             Eip2930(inner) => inner.access_list = access_list,
             Eip1559(inner) => inner.access_list = access_list,
+            Eip4844(inner) => inner.access_list = access_list,
+            Eip7702(inner) => inner.access_list = access_list,
             #[cfg(feature = "optimism")]
             DepositTransaction(_) => {}
         };
@@ -303,6 +369,8 @@ impl TypedTransaction {
             Legacy(inner) => inner.data = Some(data),
             Eip2930(inner) => inner.tx.data = Some(data),
             Eip1559(inner) => inner.data = Some(data),
+            Eip4844(inner) => inner.data = Some(data),
+            Eip7702(inner) => inner.data = Some(data),
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => inner.tx.data = Some(data),
         };
@@ -323,6 +391,14 @@ impl TypedTransaction {
                 encoded.extend_from_slice(&[0x2]);
                 encoded.extend_from_slice(inner.rlp_signed(signature).as_ref());
             }
+            Eip4844(inner) => {
+                encoded.extend_from_slice(&[0x3]);
+                encoded.extend_from_slice(inner.rlp_signed(signature).as_ref());
+            }
+            Eip7702(inner) => {
+                encoded.extend_from_slice(&[0x4]);
+                encoded.extend_from_slice(inner.rlp_signed(signature).as_ref());
+            }
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => {
                 encoded.extend_from_slice(&[0x7E]);
@@ -346,6 +422,14 @@ impl TypedTransaction {
                 encoded.extend_from_slice(&[0x2]);
                 encoded.extend_from_slice(inner.rlp().as_ref());
             }
+            Eip4844(inner) => {
+                encoded.extend_from_slice(&[0x3]);
+                encoded.extend_from_slice(inner.rlp().as_ref());
+            }
+            Eip7702(inner) => {
+                encoded.extend_from_slice(&[0x4]);
+                encoded.extend_from_slice(inner.rlp().as_ref());
+            }
             #[cfg(feature = "optimism")]
             DepositTransaction(inner) => {
                 encoded.extend_from_slice(&[0x7E]);
@@ -356,6 +440,23 @@ impl TypedTransaction {
         encoded.into()
     }
 
+    /// Produces the RLP encoding of the transaction with the provided signature, in the form
+    /// expected by `eth_sendRawTransaction`.
+    ///
+    /// For EIP-4844 transactions carrying a sidecar, this is the pooled encoding which includes the
+    /// blobs, commitments and proofs. For every other transaction this is the same as
+    /// [`Self::rlp_signed`].
+    pub fn rlp_signed_pooled(&self, signature: &Signature) -> Bytes {
+        match self {
+            Eip4844(inner) => {
+                let mut encoded = vec![0x3];
+                encoded.extend_from_slice(inner.rlp_signed_pooled(signature).as_ref());
+                encoded.into()
+            }
+            _ => self.rlp_signed(signature),
+        }
+    }
+
     /// Hashes the transaction's data. Does not double-RLP encode
     pub fn sighash(&self) -> H256 {
         let encoded = self.rlp();
@@ -402,6 +503,16 @@ impl TypedTransaction {
             let decoded_request = Eip1559TransactionRequest::decode_signed_rlp(&rest)?;
             return Ok((Self::Eip1559(decoded_request.0), decoded_request.1))
         }
+        if first == 0x03 {
+            // EIP-4844 (0x03)
+            let decoded_request = Eip4844TransactionRequest::decode_signed_rlp(&rest)?;
+            return Ok((Self::Eip4844(decoded_request.0), decoded_request.1))
+        }
+        if first == 0x04 {
+            // EIP-7702 (0x04)
+            let decoded_request = Eip7702TransactionRequest::decode_signed_rlp(&rest)?;
+            return Ok((Self::Eip7702(decoded_request.0), decoded_request.1))
+        }
         #[cfg(feature = "optimism")]
         if first == 0x7E {
             // Optimism Deposited (0x7E)
@@ -433,6 +544,14 @@ impl Decodable for TypedTransaction {
                 // EIP-1559 (0x02)
                 Ok(Self::Eip1559(Eip1559TransactionRequest::decode(&rest)?))
             }
+            Some(x) if x == U64::from(3) => {
+                // EIP-4844 (0x03)
+                Ok(Self::Eip4844(Eip4844TransactionRequest::decode(&rest)?))
+            }
+            Some(x) if x == U64::from(4) => {
+                // EIP-7702 (0x04)
+                Ok(Self::Eip7702(Eip7702TransactionRequest::decode(&rest)?))
+            }
             #[cfg(feature = "optimism")]
             Some(x) if x == U64::from(0x7E) => {
                 // Optimism Deposited (0x7E)
@@ -465,6 +584,18 @@ impl From<Eip1559TransactionRequest> for TypedTransaction {
     }
 }
 
+impl From<Eip4844TransactionRequest> for TypedTransaction {
+    fn from(src: Eip4844TransactionRequest) -> TypedTransaction {
+        TypedTransaction::Eip4844(src)
+    }
+}
+
+impl From<Eip7702TransactionRequest> for TypedTransaction {
+    fn from(src: Eip7702TransactionRequest) -> TypedTransaction {
+        TypedTransaction::Eip7702(src)
+    }
+}
+
 #[cfg(feature = "optimism")]
 impl From<DepositTransaction> for TypedTransaction {
     fn from(src: DepositTransaction) -> TypedTransaction {
@@ -485,6 +616,16 @@ impl From<&Transaction> for TypedTransaction {
                 let request: Eip1559TransactionRequest = tx.into();
                 request.into()
             }
+            // EIP-4844 (0x03)
+            Some(x) if x == U64::from(3) => {
+                let request: Eip4844TransactionRequest = tx.into();
+                request.into()
+            }
+            // EIP-7702 (0x04)
+            Some(x) if x == U64::from(4) => {
+                let request: Eip7702TransactionRequest = tx.into();
+                request.into()
+            }
             #[cfg(feature = "optimism")]
             // Optimism Deposited (0x7E)
             Some(x) if x == U64::from(0x7E) => {
@@ -519,6 +660,18 @@ impl TypedTransaction {
             _ => None,
         }
     }
+    pub fn as_eip4844_ref(&self) -> Option<&Eip4844TransactionRequest> {
+        match self {
+            Eip4844(tx) => Some(tx),
+            _ => None,
+        }
+    }
+    pub fn as_eip7702_ref(&self) -> Option<&Eip7702TransactionRequest> {
+        match self {
+            Eip7702(tx) => Some(tx),
+            _ => None,
+        }
+    }
     #[cfg(feature = "optimism")]
     pub fn as_optimism_deposited_ref(&self) -> Option<&DepositTransaction> {
         match self {
@@ -545,6 +698,18 @@ impl TypedTransaction {
             _ => None,
         }
     }
+    pub fn as_eip4844_mut(&mut self) -> Option<&mut Eip4844TransactionRequest> {
+        match self {
+            Eip4844(tx) => Some(tx),
+            _ => None,
+        }
+    }
+    pub fn as_eip7702_mut(&mut self) -> Option<&mut Eip7702TransactionRequest> {
+        match self {
+            Eip7702(tx) => Some(tx),
+            _ => None,
+        }
+    }
     #[cfg(feature = "optimism")]
     pub fn as_optimism_deposited_mut(&mut self) -> Option<&mut DepositTransaction> {
         match self {
@@ -558,6 +723,30 @@ impl TypedTransaction {
     fn into_eip1559(self) -> Eip1559TransactionRequest {
         match self {
             Eip1559(tx) => tx,
+            Eip4844(tx) => Eip1559TransactionRequest {
+                from: tx.from,
+                to: tx.to,
+                nonce: tx.nonce,
+                value: tx.value,
+                gas: tx.gas,
+                chain_id: tx.chain_id,
+                data: tx.data,
+                access_list: tx.access_list,
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                max_fee_per_gas: tx.max_fee_per_gas,
+            },
+            Eip7702(tx) => Eip1559TransactionRequest {
+                from: tx.from,
+                to: tx.to,
+                nonce: tx.nonce,
+                value: tx.value,
+                gas: tx.gas,
+                chain_id: tx.chain_id,
+                data: tx.data,
+                access_list: tx.access_list,
+                max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+                max_fee_per_gas: tx.max_fee_per_gas,
+            },
             _ => Eip1559TransactionRequest {
                 from: self.from().copied(),
                 to: self.to().cloned(),
@@ -584,7 +773,7 @@ impl TypedTransaction {
         match self {
             Legacy(tx) => tx,
             Eip2930(tx) => tx.tx,
-            Eip1559(_) => TransactionRequest {
+            Eip1559(_) | Eip4844(_) | Eip7702(_) => TransactionRequest {
                 from: self.from().copied(),
                 to: self.to().cloned(),
                 nonce: self.nonce().copied(),
@@ -622,7 +811,7 @@ impl TypedTransaction {
         match self {
             Eip2930(tx) => tx,
             Legacy(tx) => Eip2930TransactionRequest { tx, access_list },
-            Eip1559(_) => Eip2930TransactionRequest {
+            Eip1559(_) | Eip4844(_) | Eip7702(_) => Eip2930TransactionRequest {
                 tx: TransactionRequest {
                     from: self.from().copied(),
                     to: self.to().cloned(),
@@ -0,0 +1,595 @@
+use super::{
+    eip1559::Eip1559TransactionRequest, eip2718::TypedTransaction, eip2930::AccessList,
+    normalize_v, rlp_opt,
+};
+use crate::types::{
+    Address, Bytes, NameOrAddress, Signature, SignatureError, Transaction, H256, U256, U64,
+};
+use k256::sha2::{Digest, Sha256};
+use rlp::{Decodable, DecoderError, RlpStream};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// EIP-4844 transactions have 11 fields
+const NUM_TX_FIELDS: usize = 11;
+
+/// The pooled network form is `[tx_payload_body, blobs, commitments, proofs]`
+const NUM_POOLED_FIELDS: usize = 4;
+
+/// The version byte prepended to the hash of a KZG commitment
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// The amount of blob gas consumed by a single blob
+pub const DATA_GAS_PER_BLOB: u64 = 131_072;
+
+/// The size of a single blob in bytes
+pub const BYTES_PER_BLOB: usize = 131_072;
+
+/// The target amount of blob gas consumed per block
+pub const TARGET_BLOB_GAS_PER_BLOCK: u64 = 393_216;
+
+/// The minimum price of a unit of blob gas
+pub const MIN_BLOB_GASPRICE: u64 = 1;
+
+/// Controls the maximum rate of change of the blob gas price
+pub const BLOB_GASPRICE_UPDATE_FRACTION: u64 = 3_338_477;
+
+/// An error involving an EIP4844 transaction request.
+#[derive(Debug, Error)]
+pub enum Eip4844RequestError {
+    /// When decoding a transaction request from RLP
+    #[error(transparent)]
+    DecodingError(#[from] rlp::DecoderError),
+    /// When recovering the address from a signature
+    #[error(transparent)]
+    RecoveryError(#[from] SignatureError),
+}
+
+/// Computes the versioned hash of a KZG commitment, as referenced by
+/// [`Eip4844TransactionRequest::blob_versioned_hashes`].
+///
+/// Reference: <https://eips.ethereum.org/EIPS/eip-4844#helpers>
+pub fn kzg_to_versioned_hash(commitment: &[u8]) -> H256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash.into()
+}
+
+/// Computes the price of a unit of blob gas given the excess blob gas of a block.
+///
+/// Reference: <https://eips.ethereum.org/EIPS/eip-4844#gas-accounting>
+pub fn calc_blob_gasprice(excess_blob_gas: U256) -> U256 {
+    fake_exponential(
+        U256::from(MIN_BLOB_GASPRICE),
+        excess_blob_gas,
+        U256::from(BLOB_GASPRICE_UPDATE_FRACTION),
+    )
+}
+
+/// Approximates `factor * e ** (numerator / denominator)` using a Taylor expansion.
+fn fake_exponential(factor: U256, numerator: U256, denominator: U256) -> U256 {
+    let mut i = U256::one();
+    let mut output = U256::zero();
+    let mut numerator_accum = factor * denominator;
+    while !numerator_accum.is_zero() {
+        output += numerator_accum;
+        numerator_accum = numerator_accum * numerator / (denominator * i);
+        i += U256::one();
+    }
+    output / denominator
+}
+
+/// The blobs, KZG commitments and KZG proofs which accompany a blob transaction when it is
+/// gossiped or submitted to a node, but which are not part of the transaction's signed payload.
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct BlobTransactionSidecar {
+    /// The blob data, each entry being [`BYTES_PER_BLOB`] bytes long
+    pub blobs: Vec<Bytes>,
+    /// The KZG commitment of each blob
+    pub commitments: Vec<Bytes>,
+    /// The KZG proof of each blob against its commitment
+    pub proofs: Vec<Bytes>,
+}
+
+impl BlobTransactionSidecar {
+    /// Creates a new sidecar from its blobs, commitments and proofs
+    pub fn new(blobs: Vec<Bytes>, commitments: Vec<Bytes>, proofs: Vec<Bytes>) -> Self {
+        Self { blobs, commitments, proofs }
+    }
+
+    /// Returns the versioned hash of every commitment in the sidecar
+    pub fn versioned_hashes(&self) -> Vec<H256> {
+        self.commitments.iter().map(|c| kzg_to_versioned_hash(c.as_ref())).collect()
+    }
+
+    fn rlp_append(&self, rlp: &mut RlpStream) {
+        for list in [&self.blobs, &self.commitments, &self.proofs] {
+            rlp.begin_list(list.len());
+            for item in list {
+                rlp.append(&item.as_ref());
+            }
+        }
+    }
+
+    /// Decodes the sidecar lists of the pooled RLP encoding starting at the offset passed.
+    /// Increments the offset for each element parsed.
+    fn decode_rlp(rlp: &rlp::Rlp, offset: &mut usize) -> Result<Self, DecoderError> {
+        let mut lists = Vec::with_capacity(3);
+        for _ in 0..3 {
+            let list = rlp
+                .at(*offset)?
+                .iter()
+                .map(|item| item.data().map(|data| Bytes::from(data.to_vec())))
+                .collect::<Result<Vec<_>, _>>()?;
+            lists.push(list);
+            *offset += 1;
+        }
+        let proofs = lists.pop().unwrap_or_default();
+        let commitments = lists.pop().unwrap_or_default();
+        let blobs = lists.pop().unwrap_or_default();
+        Ok(Self { blobs, commitments, proofs })
+    }
+}
+
+/// Parameters for sending an EIP-4844 blob transaction.
+///
+/// The optional [`BlobTransactionSidecar`] is not part of the signed payload, it is only included
+/// in the pooled encoding produced by [`Eip4844TransactionRequest::rlp_signed_pooled`].
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Eip4844TransactionRequest {
+    /// Sender address or ENS name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+
+    /// Recipient address. Blob transactions cannot be used for contract creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<NameOrAddress>,
+
+    /// Supplied gas (None for sensible default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U256>,
+
+    /// Transferred value (None for no transfer)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+
+    /// The first 4 bytes of the hash of the invoked method signature and encoded parameters.
+    /// For details see Ethereum Contract ABI
+    #[serde(skip_serializing_if = "Option::is_none", alias = "input")]
+    pub data: Option<Bytes>,
+
+    /// Transaction nonce (None for next available nonce)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+
+    #[serde(rename = "accessList", default)]
+    pub access_list: AccessList,
+
+    #[serde(rename = "maxPriorityFeePerGas", default, skip_serializing_if = "Option::is_none")]
+    /// Represents the maximum tx fee that will go to the miner as part of the user's
+    /// fee payment.
+    pub max_priority_fee_per_gas: Option<U256>,
+
+    #[serde(rename = "maxFeePerGas", default, skip_serializing_if = "Option::is_none")]
+    /// Represents the maximum amount that a user is willing to pay for their tx (inclusive of
+    /// baseFeePerGas and maxPriorityFeePerGas).
+    pub max_fee_per_gas: Option<U256>,
+
+    #[serde(rename = "maxFeePerBlobGas", default, skip_serializing_if = "Option::is_none")]
+    /// Represents the maximum amount that a user is willing to pay per unit of blob gas, which is
+    /// priced independently from execution gas.
+    pub max_fee_per_blob_gas: Option<U256>,
+
+    #[serde(rename = "blobVersionedHashes", default)]
+    /// The versioned hashes of the blobs carried by this transaction
+    pub blob_versioned_hashes: Vec<H256>,
+
+    /// The blobs, commitments and proofs of the transaction
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub sidecar: Option<BlobTransactionSidecar>,
+
+    #[serde(skip_serializing)]
+    #[serde(default, rename = "chainId")]
+    /// Chain ID (None for mainnet)
+    pub chain_id: Option<U64>,
+}
+
+impl Eip4844TransactionRequest {
+    /// Creates an empty transaction request with all fields left empty
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Builder pattern helpers
+
+    /// Sets the `from` field in the transaction to the provided value
+    #[must_use]
+    pub fn from<T: Into<Address>>(mut self, from: T) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    /// Sets the `to` field in the transaction to the provided value
+    #[must_use]
+    pub fn to<T: Into<NameOrAddress>>(mut self, to: T) -> Self {
+        self.to = Some(to.into());
+        self
+    }
+
+    /// Sets the `gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn gas<T: Into<U256>>(mut self, gas: T) -> Self {
+        self.gas = Some(gas.into());
+        self
+    }
+
+    /// Sets the `max_priority_fee_per_gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn max_priority_fee_per_gas<T: Into<U256>>(mut self, max_priority_fee_per_gas: T) -> Self {
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the `max_fee_per_gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn max_fee_per_gas<T: Into<U256>>(mut self, max_fee_per_gas: T) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the `max_fee_per_blob_gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn max_fee_per_blob_gas<T: Into<U256>>(mut self, max_fee_per_blob_gas: T) -> Self {
+        self.max_fee_per_blob_gas = Some(max_fee_per_blob_gas.into());
+        self
+    }
+
+    /// Sets the `blob_versioned_hashes` field in the transaction to the provided value
+    #[must_use]
+    pub fn blob_versioned_hashes<T: Into<Vec<H256>>>(mut self, blob_versioned_hashes: T) -> Self {
+        self.blob_versioned_hashes = blob_versioned_hashes.into();
+        self
+    }
+
+    /// Sets the `sidecar` field in the transaction to the provided value, and sets the
+    /// `blob_versioned_hashes` to the versioned hashes of its commitments
+    #[must_use]
+    pub fn sidecar(mut self, sidecar: BlobTransactionSidecar) -> Self {
+        self.blob_versioned_hashes = sidecar.versioned_hashes();
+        self.sidecar = Some(sidecar);
+        self
+    }
+
+    /// Sets the `value` field in the transaction to the provided value
+    #[must_use]
+    pub fn value<T: Into<U256>>(mut self, value: T) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Sets the `data` field in the transaction to the provided value
+    #[must_use]
+    pub fn data<T: Into<Bytes>>(mut self, data: T) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the `access_list` field in the transaction to the provided value
+    #[must_use]
+    pub fn access_list<T: Into<AccessList>>(mut self, access_list: T) -> Self {
+        self.access_list = access_list.into();
+        self
+    }
+
+    /// Sets the `nonce` field in the transaction to the provided value
+    #[must_use]
+    pub fn nonce<T: Into<U256>>(mut self, nonce: T) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the `chain_id` field in the transaction to the provided value
+    #[must_use]
+    pub fn chain_id<T: Into<U64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    /// The total amount of blob gas consumed by the transaction's blobs
+    pub fn blob_gas(&self) -> U256 {
+        U256::from(DATA_GAS_PER_BLOB) * self.blob_versioned_hashes.len()
+    }
+
+    /// Gets the unsigned transaction's RLP encoding
+    pub fn rlp(&self) -> Bytes {
+        let mut rlp = RlpStream::new();
+        rlp.begin_list(NUM_TX_FIELDS);
+        self.rlp_base(&mut rlp);
+        rlp.out().freeze().into()
+    }
+
+    /// Produces the RLP encoding of the transaction with the provided signature.
+    ///
+    /// This is the canonical encoding of the transaction which is used for hashing and inclusion
+    /// in blocks, and it never contains the sidecar.
+    pub fn rlp_signed(&self, signature: &Signature) -> Bytes {
+        let mut rlp = RlpStream::new();
+        rlp.begin_unbounded_list();
+        self.rlp_base(&mut rlp);
+
+        // if the chain_id is none we assume mainnet and choose one
+        let chain_id = self.chain_id.unwrap_or_else(U64::one);
+
+        // append the signature
+        let v = normalize_v(signature.v, chain_id);
+        rlp.append(&v);
+        rlp.append(&signature.r);
+        rlp.append(&signature.s);
+        rlp.finalize_unbounded_list();
+        rlp.out().freeze().into()
+    }
+
+    /// Produces the pooled RLP encoding of the transaction with the provided signature, which
+    /// wraps the signed payload together with the blobs, commitments and proofs of the sidecar.
+    ///
+    /// This is the encoding expected by `eth_sendRawTransaction`. If the transaction has no
+    /// sidecar, this is the same as [`Self::rlp_signed`].
+    pub fn rlp_signed_pooled(&self, signature: &Signature) -> Bytes {
+        let sidecar = match self.sidecar {
+            Some(ref sidecar) => sidecar,
+            None => return self.rlp_signed(signature),
+        };
+
+        let mut rlp = RlpStream::new();
+        rlp.begin_list(NUM_POOLED_FIELDS);
+        rlp.append_raw(self.rlp_signed(signature).as_ref(), 1);
+        sidecar.rlp_append(&mut rlp);
+        rlp.out().freeze().into()
+    }
+
+    pub(crate) fn rlp_base(&self, rlp: &mut RlpStream) {
+        rlp_opt(rlp, &self.chain_id);
+        rlp_opt(rlp, &self.nonce);
+        rlp_opt(rlp, &self.max_priority_fee_per_gas);
+        rlp_opt(rlp, &self.max_fee_per_gas);
+        rlp_opt(rlp, &self.gas);
+        rlp_opt(rlp, &self.to.as_ref());
+        rlp_opt(rlp, &self.value);
+        rlp_opt(rlp, &self.data.as_ref().map(|d| d.as_ref()));
+        rlp.append(&self.access_list);
+        rlp_opt(rlp, &self.max_fee_per_blob_gas);
+        rlp.append_list(&self.blob_versioned_hashes);
+    }
+
+    /// Decodes fields of the request starting at the RLP offset passed. Increments the offset for
+    /// each element parsed.
+    #[inline]
+    pub fn decode_base_rlp(rlp: &rlp::Rlp, offset: &mut usize) -> Result<Self, DecoderError> {
+        // the first 9 fields are laid out exactly like an EIP-1559 transaction
+        let base = Eip1559TransactionRequest::decode_base_rlp(rlp, offset)?;
+        let mut tx = Self {
+            from: base.from,
+            to: base.to,
+            gas: base.gas,
+            value: base.value,
+            data: base.data,
+            nonce: base.nonce,
+            access_list: base.access_list,
+            max_priority_fee_per_gas: base.max_priority_fee_per_gas,
+            max_fee_per_gas: base.max_fee_per_gas,
+            chain_id: base.chain_id,
+            ..Default::default()
+        };
+        tx.max_fee_per_blob_gas = Some(rlp.val_at(*offset)?);
+        *offset += 1;
+        tx.blob_versioned_hashes = rlp.list_at(*offset)?;
+        *offset += 1;
+        Ok(tx)
+    }
+
+    /// Decodes the given RLP into a transaction, attempting to decode its signature as well.
+    ///
+    /// Both the canonical encoding and the pooled encoding (which includes the sidecar) are
+    /// accepted.
+    pub fn decode_signed_rlp(rlp: &rlp::Rlp) -> Result<(Self, Signature), Eip4844RequestError> {
+        // the pooled encoding starts with the payload body as a nested list
+        if rlp.at(0)?.is_list() {
+            let (mut txn, sig) = Self::decode_signed_rlp(&rlp.at(0)?)?;
+            txn.sidecar = Some(BlobTransactionSidecar::decode_rlp(rlp, &mut 1)?);
+            return Ok((txn, sig))
+        }
+
+        let mut offset = 0;
+        let mut txn = Self::decode_base_rlp(rlp, &mut offset)?;
+
+        let v = rlp.val_at(offset)?;
+        offset += 1;
+        let r = rlp.val_at(offset)?;
+        offset += 1;
+        let s = rlp.val_at(offset)?;
+
+        let sig = Signature { r, s, v };
+        txn.from = Some(sig.recover(TypedTransaction::Eip4844(txn.clone()).sighash())?);
+
+        Ok((txn, sig))
+    }
+}
+
+impl Decodable for Eip4844TransactionRequest {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Self::decode_base_rlp(rlp, &mut 0)
+    }
+}
+
+impl From<Eip4844TransactionRequest> for super::request::TransactionRequest {
+    fn from(tx: Eip4844TransactionRequest) -> Self {
+        Self {
+            from: tx.from,
+            to: tx.to,
+            gas: tx.gas,
+            gas_price: tx.max_fee_per_gas,
+            value: tx.value,
+            data: tx.data,
+            nonce: tx.nonce,
+            #[cfg(feature = "celo")]
+            fee_currency: None,
+            #[cfg(feature = "celo")]
+            gateway_fee_recipient: None,
+            #[cfg(feature = "celo")]
+            gateway_fee: None,
+            chain_id: tx.chain_id,
+        }
+    }
+}
+
+impl From<&Transaction> for Eip4844TransactionRequest {
+    fn from(tx: &Transaction) -> Eip4844TransactionRequest {
+        // the blob fields are not part of the `Transaction` response type, so they are read from
+        // the additional fields returned by the node
+        #[cfg(not(feature = "celo"))]
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = (
+            tx.other.get_deserialized("maxFeePerBlobGas").and_then(Result::ok),
+            tx.other.get_deserialized("blobVersionedHashes").and_then(Result::ok),
+        );
+        #[cfg(feature = "celo")]
+        let (max_fee_per_blob_gas, blob_versioned_hashes) = (None, None);
+
+        Eip4844TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to.map(NameOrAddress::Address),
+            gas: Some(tx.gas),
+            value: Some(tx.value),
+            data: Some(Bytes(tx.input.0.clone())),
+            nonce: Some(tx.nonce),
+            access_list: tx.access_list.clone().unwrap_or_default(),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_fee_per_blob_gas,
+            blob_versioned_hashes: blob_versioned_hashes.unwrap_or_default(),
+            sidecar: None,
+            chain_id: tx.chain_id.map(|x| U64::from(x.as_u64())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+    use std::str::FromStr;
+
+    fn sign(tx: &TypedTransaction, key: &SigningKey) -> Signature {
+        let (sig, recovery_id) = key.sign_prehash_recoverable(tx.sighash().as_ref()).unwrap();
+        let bytes = sig.to_bytes();
+        Signature {
+            r: U256::from_big_endian(&bytes[..32]),
+            s: U256::from_big_endian(&bytes[32..]),
+            v: recovery_id.to_byte() as u64,
+        }
+    }
+
+    fn test_request() -> Eip4844TransactionRequest {
+        Eip4844TransactionRequest::new()
+            .chain_id(1u64)
+            .nonce(3u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .max_fee_per_gas(30_000_000_000u64)
+            .max_fee_per_blob_gas(2_000_000_000u64)
+            .gas(21000u64)
+            .to(Address::from_str("0x0aa7420c43b8c1a7b165d216948870c8ecfe1ee1").unwrap())
+            .value(1u64)
+            .data(Bytes::from_static(b"batch"))
+    }
+
+    #[test]
+    fn test_kzg_to_versioned_hash() {
+        // the KZG commitment of an empty blob
+        let commitment = hex::decode("c00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000").unwrap();
+        let expected =
+            H256::from_str("0x010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014")
+                .unwrap();
+        assert_eq!(kzg_to_versioned_hash(&commitment), expected);
+    }
+
+    #[test]
+    fn test_calc_blob_gasprice() {
+        assert_eq!(calc_blob_gasprice(U256::zero()), U256::from(MIN_BLOB_GASPRICE));
+        assert_eq!(calc_blob_gasprice(U256::from(2_314_057u64)), U256::from(1u64));
+        assert_eq!(calc_blob_gasprice(U256::from(2_314_058u64)), U256::from(2u64));
+        assert_eq!(calc_blob_gasprice(U256::from(10u64 * 1024 * 1024)), U256::from(23u64));
+    }
+
+    #[test]
+    fn test_rlp_signed_roundtrip() {
+        let key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let sidecar = BlobTransactionSidecar::new(
+            vec![Bytes::from(vec![0u8; BYTES_PER_BLOB])],
+            vec![Bytes::from(vec![0xc0; 48])],
+            vec![Bytes::from(vec![0xc0; 48])],
+        );
+        let request = test_request().sidecar(sidecar.clone());
+        assert_eq!(request.blob_versioned_hashes, sidecar.versioned_hashes());
+        assert_eq!(request.blob_gas(), U256::from(DATA_GAS_PER_BLOB));
+
+        let tx = TypedTransaction::Eip4844(request.clone());
+        let signature = sign(&tx, &key);
+        let from = crate::utils::secret_key_to_address(&key);
+
+        // canonical encoding does not carry the sidecar
+        let encoded = tx.rlp_signed(&signature);
+        assert_eq!(encoded[0], 0x03);
+        let (decoded, decoded_sig) =
+            TypedTransaction::decode_signed(&rlp::Rlp::new(&encoded)).unwrap();
+        assert_eq!(decoded_sig, signature);
+        assert_eq!(decoded.from(), Some(&from));
+        let decoded = decoded.as_eip4844_ref().unwrap();
+        assert_eq!(decoded.sidecar, None);
+        assert_eq!(decoded.blob_versioned_hashes, request.blob_versioned_hashes);
+        assert_eq!(decoded.max_fee_per_blob_gas, request.max_fee_per_blob_gas);
+
+        // pooled encoding carries the sidecar and keeps the same transaction hash
+        let pooled = tx.rlp_signed_pooled(&signature);
+        assert_ne!(pooled, encoded);
+        let (decoded, _) = TypedTransaction::decode_signed(&rlp::Rlp::new(&pooled)).unwrap();
+        assert_eq!(decoded, TypedTransaction::Eip4844(request.from(from)));
+        assert_eq!(decoded.hash(&signature), tx.hash(&signature));
+    }
+
+    #[test]
+    fn test_unsigned_decode() {
+        let tx = TypedTransaction::Eip4844(
+            test_request().blob_versioned_hashes(vec![H256::repeat_byte(1), H256::repeat_byte(2)]),
+        );
+        let encoded = tx.rlp();
+        let decoded = TypedTransaction::decode(&rlp::Rlp::new(&encoded)).unwrap();
+        assert_eq!(decoded, tx);
+        assert_eq!(decoded.sighash(), tx.sighash());
+    }
+
+    #[test]
+    #[cfg_attr(feature = "legacy", ignore)]
+    fn test_serde_typed_tx() {
+        let json = r#"{
+            "type": "0x3",
+            "to": "0x0aa7420c43b8c1a7b165d216948870c8ecfe1ee1",
+            "gas": "0x5208",
+            "maxFeePerGas": "0x6fc23ac00",
+            "maxPriorityFeePerGas": "0x3b9aca00",
+            "maxFeePerBlobGas": "0x77359400",
+            "blobVersionedHashes": [
+                "0x010657f37554c781402a22917dee2f75def7ab966d7b770905398eba3c444014"
+            ],
+            "blobs": ["0x00"],
+            "commitments": ["0xc0"],
+            "proofs": ["0xc0"]
+        }"#;
+        let tx: TypedTransaction = serde_json::from_str(json).unwrap();
+        let inner = tx.as_eip4844_ref().unwrap();
+        assert_eq!(inner.blob_versioned_hashes.len(), 1);
+        assert_eq!(inner.sidecar.as_ref().unwrap().blobs, vec![Bytes::from(vec![0u8])]);
+
+        let serialized = serde_json::to_string(&tx).unwrap();
+        let de: TypedTransaction = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(tx, de);
+    }
+}
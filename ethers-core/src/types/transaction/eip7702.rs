@@ -0,0 +1,485 @@
+use super::{
+    eip1559::Eip1559TransactionRequest, eip2718::TypedTransaction, eip2930::AccessList,
+    normalize_v, rlp_opt,
+};
+use crate::{
+    types::{
+        signature::normalize_recovery_id, Address, Bytes, NameOrAddress, Signature, SignatureError,
+        Transaction, H256, U256, U64,
+    },
+    utils::keccak256,
+};
+use rlp::{Decodable, DecoderError, RlpDecodable, RlpEncodable, RlpStream};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// EIP-7702 transactions have 10 fields
+const NUM_TX_FIELDS: usize = 10;
+
+/// The magic byte prepended to the RLP encoding of an [`Authorization`] before hashing it
+pub const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// An error involving an EIP7702 transaction request.
+#[derive(Debug, Error)]
+pub enum Eip7702RequestError {
+    /// When decoding a transaction request from RLP
+    #[error(transparent)]
+    DecodingError(#[from] rlp::DecoderError),
+    /// When recovering the address from a signature
+    #[error(transparent)]
+    RecoveryError(#[from] SignatureError),
+}
+
+/// An unsigned EIP-7702 authorization, allowing an EOA to delegate its code to the contract at
+/// `address`.
+///
+/// A `chain_id` of zero makes the authorization valid on every chain.
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, RlpEncodable, RlpDecodable,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    /// The chain the authorization is valid on
+    pub chain_id: U256,
+    /// The address of the code to delegate to
+    pub address: Address,
+    /// The nonce of the authorizing account
+    pub nonce: U64,
+}
+
+impl Authorization {
+    /// Creates a new authorization
+    pub fn new<C: Into<U256>, N: Into<U64>>(chain_id: C, address: Address, nonce: N) -> Self {
+        Self { chain_id: chain_id.into(), address, nonce: nonce.into() }
+    }
+
+    /// The hash which must be signed by the authorizing account, i.e.
+    /// `keccak256(MAGIC || rlp([chain_id, address, nonce]))`
+    pub fn signature_hash(&self) -> H256 {
+        let mut encoded = vec![AUTHORIZATION_MAGIC];
+        encoded.extend_from_slice(rlp::encode(self).as_ref());
+        keccak256(encoded).into()
+    }
+
+    /// Attaches the provided signature to the authorization
+    pub fn into_signed(self, signature: Signature) -> SignedAuthorization {
+        SignedAuthorization {
+            chain_id: self.chain_id,
+            address: self.address,
+            nonce: self.nonce,
+            y_parity: normalize_recovery_id(signature.v).into(),
+            r: signature.r,
+            s: signature.s,
+        }
+    }
+}
+
+/// A signed EIP-7702 authorization tuple, as included in the `authorization_list` of an
+/// [`Eip7702TransactionRequest`].
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, RlpEncodable, RlpDecodable,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedAuthorization {
+    /// The chain the authorization is valid on
+    pub chain_id: U256,
+    /// The address of the code to delegate to
+    pub address: Address,
+    /// The nonce of the authorizing account
+    pub nonce: U64,
+    /// The parity of the signature's `y` coordinate
+    pub y_parity: U64,
+    /// The `r` value of the signature
+    pub r: U256,
+    /// The `s` value of the signature
+    pub s: U256,
+}
+
+impl SignedAuthorization {
+    /// Returns the unsigned authorization
+    pub fn authorization(&self) -> Authorization {
+        Authorization { chain_id: self.chain_id, address: self.address, nonce: self.nonce }
+    }
+
+    /// Returns the signature over the authorization
+    pub fn signature(&self) -> Signature {
+        Signature { r: self.r, s: self.s, v: self.y_parity.as_u64() }
+    }
+
+    /// Recovers the address of the account which signed the authorization
+    pub fn recover_authority(&self) -> Result<Address, SignatureError> {
+        self.signature().recover(self.authorization().signature_hash())
+    }
+}
+
+/// Parameters for sending an EIP-7702 set code transaction
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+pub struct Eip7702TransactionRequest {
+    /// Sender address or ENS name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+
+    /// Recipient address. Set code transactions cannot be used for contract creation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<NameOrAddress>,
+
+    /// Supplied gas (None for sensible default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U256>,
+
+    /// Transferred value (None for no transfer)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+
+    /// The first 4 bytes of the hash of the invoked method signature and encoded parameters.
+    /// For details see Ethereum Contract ABI
+    #[serde(skip_serializing_if = "Option::is_none", alias = "input")]
+    pub data: Option<Bytes>,
+
+    /// Transaction nonce (None for next available nonce)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+
+    #[serde(rename = "accessList", default)]
+    pub access_list: AccessList,
+
+    #[serde(rename = "maxPriorityFeePerGas", default, skip_serializing_if = "Option::is_none")]
+    /// Represents the maximum tx fee that will go to the miner as part of the user's
+    /// fee payment.
+    pub max_priority_fee_per_gas: Option<U256>,
+
+    #[serde(rename = "maxFeePerGas", default, skip_serializing_if = "Option::is_none")]
+    /// Represents the maximum amount that a user is willing to pay for their tx (inclusive of
+    /// baseFeePerGas and maxPriorityFeePerGas).
+    pub max_fee_per_gas: Option<U256>,
+
+    #[serde(rename = "authorizationList", default)]
+    /// The authorizations which set the code of their authorities
+    pub authorization_list: Vec<SignedAuthorization>,
+
+    #[serde(skip_serializing)]
+    #[serde(default, rename = "chainId")]
+    /// Chain ID (None for mainnet)
+    pub chain_id: Option<U64>,
+}
+
+impl Eip7702TransactionRequest {
+    /// Creates an empty transaction request with all fields left empty
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Builder pattern helpers
+
+    /// Sets the `from` field in the transaction to the provided value
+    #[must_use]
+    pub fn from<T: Into<Address>>(mut self, from: T) -> Self {
+        self.from = Some(from.into());
+        self
+    }
+
+    /// Sets the `to` field in the transaction to the provided value
+    #[must_use]
+    pub fn to<T: Into<NameOrAddress>>(mut self, to: T) -> Self {
+        self.to = Some(to.into());
+        self
+    }
+
+    /// Sets the `gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn gas<T: Into<U256>>(mut self, gas: T) -> Self {
+        self.gas = Some(gas.into());
+        self
+    }
+
+    /// Sets the `max_priority_fee_per_gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn max_priority_fee_per_gas<T: Into<U256>>(mut self, max_priority_fee_per_gas: T) -> Self {
+        self.max_priority_fee_per_gas = Some(max_priority_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the `max_fee_per_gas` field in the transaction to the provided value
+    #[must_use]
+    pub fn max_fee_per_gas<T: Into<U256>>(mut self, max_fee_per_gas: T) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas.into());
+        self
+    }
+
+    /// Sets the `value` field in the transaction to the provided value
+    #[must_use]
+    pub fn value<T: Into<U256>>(mut self, value: T) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+
+    /// Sets the `data` field in the transaction to the provided value
+    #[must_use]
+    pub fn data<T: Into<Bytes>>(mut self, data: T) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Sets the `access_list` field in the transaction to the provided value
+    #[must_use]
+    pub fn access_list<T: Into<AccessList>>(mut self, access_list: T) -> Self {
+        self.access_list = access_list.into();
+        self
+    }
+
+    /// Sets the `authorization_list` field in the transaction to the provided value
+    #[must_use]
+    pub fn authorization_list<T: Into<Vec<SignedAuthorization>>>(
+        mut self,
+        authorization_list: T,
+    ) -> Self {
+        self.authorization_list = authorization_list.into();
+        self
+    }
+
+    /// Sets the `nonce` field in the transaction to the provided value
+    #[must_use]
+    pub fn nonce<T: Into<U256>>(mut self, nonce: T) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Sets the `chain_id` field in the transaction to the provided value
+    #[must_use]
+    pub fn chain_id<T: Into<U64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = Some(chain_id.into());
+        self
+    }
+
+    /// Gets the unsigned transaction's RLP encoding
+    pub fn rlp(&self) -> Bytes {
+        let mut rlp = RlpStream::new();
+        rlp.begin_list(NUM_TX_FIELDS);
+        self.rlp_base(&mut rlp);
+        rlp.out().freeze().into()
+    }
+
+    /// Produces the RLP encoding of the transaction with the provided signature
+    pub fn rlp_signed(&self, signature: &Signature) -> Bytes {
+        let mut rlp = RlpStream::new();
+        rlp.begin_unbounded_list();
+        self.rlp_base(&mut rlp);
+
+        // if the chain_id is none we assume mainnet and choose one
+        let chain_id = self.chain_id.unwrap_or_else(U64::one);
+
+        // append the signature
+        let v = normalize_v(signature.v, chain_id);
+        rlp.append(&v);
+        rlp.append(&signature.r);
+        rlp.append(&signature.s);
+        rlp.finalize_unbounded_list();
+        rlp.out().freeze().into()
+    }
+
+    pub(crate) fn rlp_base(&self, rlp: &mut RlpStream) {
+        rlp_opt(rlp, &self.chain_id);
+        rlp_opt(rlp, &self.nonce);
+        rlp_opt(rlp, &self.max_priority_fee_per_gas);
+        rlp_opt(rlp, &self.max_fee_per_gas);
+        rlp_opt(rlp, &self.gas);
+        rlp_opt(rlp, &self.to.as_ref());
+        rlp_opt(rlp, &self.value);
+        rlp_opt(rlp, &self.data.as_ref().map(|d| d.as_ref()));
+        rlp.append(&self.access_list);
+        rlp.append_list(&self.authorization_list);
+    }
+
+    /// Decodes fields of the request starting at the RLP offset passed. Increments the offset for
+    /// each element parsed.
+    #[inline]
+    pub fn decode_base_rlp(rlp: &rlp::Rlp, offset: &mut usize) -> Result<Self, DecoderError> {
+        // the first 9 fields are laid out exactly like an EIP-1559 transaction
+        let base = Eip1559TransactionRequest::decode_base_rlp(rlp, offset)?;
+        let authorization_list = rlp.list_at(*offset)?;
+        *offset += 1;
+        Ok(Self {
+            from: base.from,
+            to: base.to,
+            gas: base.gas,
+            value: base.value,
+            data: base.data,
+            nonce: base.nonce,
+            access_list: base.access_list,
+            max_priority_fee_per_gas: base.max_priority_fee_per_gas,
+            max_fee_per_gas: base.max_fee_per_gas,
+            authorization_list,
+            chain_id: base.chain_id,
+        })
+    }
+
+    /// Decodes the given RLP into a transaction, attempting to decode its signature as well.
+    pub fn decode_signed_rlp(rlp: &rlp::Rlp) -> Result<(Self, Signature), Eip7702RequestError> {
+        let mut offset = 0;
+        let mut txn = Self::decode_base_rlp(rlp, &mut offset)?;
+
+        let v = rlp.val_at(offset)?;
+        offset += 1;
+        let r = rlp.val_at(offset)?;
+        offset += 1;
+        let s = rlp.val_at(offset)?;
+
+        let sig = Signature { r, s, v };
+        txn.from = Some(sig.recover(TypedTransaction::Eip7702(txn.clone()).sighash())?);
+
+        Ok((txn, sig))
+    }
+}
+
+impl Decodable for Eip7702TransactionRequest {
+    fn decode(rlp: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        Self::decode_base_rlp(rlp, &mut 0)
+    }
+}
+
+impl From<Eip7702TransactionRequest> for super::request::TransactionRequest {
+    fn from(tx: Eip7702TransactionRequest) -> Self {
+        Self {
+            from: tx.from,
+            to: tx.to,
+            gas: tx.gas,
+            gas_price: tx.max_fee_per_gas,
+            value: tx.value,
+            data: tx.data,
+            nonce: tx.nonce,
+            #[cfg(feature = "celo")]
+            fee_currency: None,
+            #[cfg(feature = "celo")]
+            gateway_fee_recipient: None,
+            #[cfg(feature = "celo")]
+            gateway_fee: None,
+            chain_id: tx.chain_id,
+        }
+    }
+}
+
+impl From<&Transaction> for Eip7702TransactionRequest {
+    fn from(tx: &Transaction) -> Eip7702TransactionRequest {
+        // the authorization list is not part of the `Transaction` response type, so it is read
+        // from the additional fields returned by the node
+        #[cfg(not(feature = "celo"))]
+        let authorization_list =
+            tx.other.get_deserialized("authorizationList").and_then(Result::ok);
+        #[cfg(feature = "celo")]
+        let authorization_list = None;
+
+        Eip7702TransactionRequest {
+            from: Some(tx.from),
+            to: tx.to.map(NameOrAddress::Address),
+            gas: Some(tx.gas),
+            value: Some(tx.value),
+            data: Some(Bytes(tx.input.0.clone())),
+            nonce: Some(tx.nonce),
+            access_list: tx.access_list.clone().unwrap_or_default(),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            authorization_list: authorization_list.unwrap_or_default(),
+            chain_id: tx.chain_id.map(|x| U64::from(x.as_u64())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::secret_key_to_address;
+    use k256::ecdsa::SigningKey;
+    use std::str::FromStr;
+
+    fn sign(hash: H256, key: &SigningKey) -> Signature {
+        let (sig, recovery_id) = key.sign_prehash_recoverable(hash.as_ref()).unwrap();
+        let bytes = sig.to_bytes();
+        Signature {
+            r: U256::from_big_endian(&bytes[..32]),
+            s: U256::from_big_endian(&bytes[32..]),
+            v: recovery_id.to_byte() as u64 + 27,
+        }
+    }
+
+    #[test]
+    fn test_authorization_signature_hash() {
+        let authorization = Authorization::new(
+            1u64,
+            Address::from_str("0x0000000000000000000000000000000000000001").unwrap(),
+            0u64,
+        );
+        // keccak256(0x05 || rlp([1, address, 0]))
+        let mut preimage = vec![0x05, 0xd7, 0x01, 0x94];
+        preimage.extend_from_slice(authorization.address.as_bytes());
+        preimage.push(0x80);
+        assert_eq!(authorization.signature_hash(), H256::from(keccak256(preimage)));
+    }
+
+    #[test]
+    fn test_signed_authorization_recover() {
+        let key = SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let authorization = Authorization::new(
+            1u64,
+            Address::from_str("0x0aa7420c43b8c1a7b165d216948870c8ecfe1ee1").unwrap(),
+            7u64,
+        );
+        let signature = sign(authorization.signature_hash(), &key);
+        let signed = authorization.clone().into_signed(signature);
+
+        assert!(signed.y_parity <= U64::one());
+        assert_eq!(signed.authorization(), authorization);
+        assert_eq!(signed.recover_authority().unwrap(), secret_key_to_address(&key));
+
+        let encoded = rlp::encode(&signed);
+        let decoded: SignedAuthorization = rlp::decode(&encoded).unwrap();
+        assert_eq!(decoded, signed);
+
+        let json = serde_json::to_value(&signed).unwrap();
+        assert!(json.get("yParity").is_some());
+        assert_eq!(serde_json::from_value::<SignedAuthorization>(json).unwrap(), signed);
+    }
+
+    #[test]
+    fn test_rlp_signed_roundtrip() {
+        let authority = SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let sender = SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let delegate = Address::from_str("0x0aa7420c43b8c1a7b165d216948870c8ecfe1ee1").unwrap();
+
+        let authorization = Authorization::new(1u64, delegate, 0u64);
+        let signed =
+            authorization.clone().into_signed(sign(authorization.signature_hash(), &authority));
+
+        let request = Eip7702TransactionRequest::new()
+            .chain_id(1u64)
+            .nonce(4u64)
+            .max_priority_fee_per_gas(1_000_000_000u64)
+            .max_fee_per_gas(30_000_000_000u64)
+            .gas(100_000u64)
+            .to(secret_key_to_address(&authority))
+            .value(0u64)
+            .data(Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]))
+            .authorization_list(vec![signed]);
+        let tx = TypedTransaction::Eip7702(request.clone());
+
+        // unsigned roundtrip
+        let decoded = TypedTransaction::decode(&rlp::Rlp::new(tx.rlp().as_ref())).unwrap();
+        assert_eq!(decoded, tx);
+
+        // signed roundtrip
+        let mut signature = sign(tx.sighash(), &sender);
+        signature.v -= 27;
+        let encoded = tx.rlp_signed(&signature);
+        assert_eq!(encoded[0], 0x04);
+        let (decoded, decoded_sig) =
+            TypedTransaction::decode_signed(&rlp::Rlp::new(&encoded)).unwrap();
+        assert_eq!(decoded_sig, signature);
+        assert_eq!(
+            decoded,
+            TypedTransaction::Eip7702(request.from(secret_key_to_address(&sender)))
+        );
+        assert_eq!(
+            decoded.as_eip7702_ref().unwrap().authorization_list[0].recover_authority().unwrap(),
+            secret_key_to_address(&authority)
+        );
+    }
+}
@@ -4,6 +4,8 @@ pub mod response;
 pub mod eip1559;
 pub mod eip2718;
 pub mod eip2930;
+pub mod eip4844;
+pub mod eip7702;
 
 #[cfg(feature = "optimism")]
 pub mod optimism;
@@ -0,0 +1,434 @@
+//! ERC-4337 account abstraction types
+use crate::{
+    abi::{encode, Token},
+    types::{Address, Bytes, Log, TransactionReceipt, H160, H256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+
+/// The address of the v0.6 EntryPoint contract, deployed at the same address on most chains.
+pub const ENTRY_POINT_V06: Address = H160([
+    0x5f, 0xf1, 0x37, 0xd4, 0xb0, 0xfd, 0xcd, 0x49, 0xdc, 0xa3, 0x0c, 0x7c, 0xf5, 0x7e, 0x57, 0x8a,
+    0x02, 0x6d, 0x27, 0x89,
+]);
+
+/// The address of the v0.7 EntryPoint contract, deployed at the same address on most chains.
+pub const ENTRY_POINT_V07: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x71, 0x72, 0x7d, 0xe2, 0x2e, 0x5e, 0x9d, 0x8b, 0xaf, 0x0e, 0xda, 0xc6,
+    0xf3, 0x7d, 0xa0, 0x32,
+]);
+
+/// Hashes the packed user operation with the EntryPoint and the chain id
+fn hash_with_entry_point(packed: Vec<Token>, entry_point: Address, chain_id: U256) -> H256 {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(encode(&packed)).to_vec()),
+        Token::Address(entry_point),
+        Token::Uint(chain_id),
+    ]))
+    .into()
+}
+
+/// Packs two 128 bit values into 32 bytes, `high` first. Larger values are truncated.
+fn pack_u128s(high: U256, low: U256) -> H256 {
+    let mut packed = [0u8; 32];
+    packed[..16].copy_from_slice(&<[u8; 32]>::from(high)[16..]);
+    packed[16..].copy_from_slice(&<[u8; 32]>::from(low)[16..]);
+    packed.into()
+}
+
+/// A user operation of the v0.6 EntryPoint.
+///
+/// Ref: <https://github.com/eth-infinitism/account-abstraction/blob/v0.6.0/contracts/interfaces/UserOperation.sol>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    /// The account making the operation
+    pub sender: Address,
+    /// The anti-replay nonce of the account, see `EntryPoint.getNonce`
+    pub nonce: U256,
+    /// The factory address and calldata deploying the account, empty if it is deployed
+    pub init_code: Bytes,
+    /// The calldata of the call to the account
+    pub call_data: Bytes,
+    /// The gas limit of the call to the account
+    pub call_gas_limit: U256,
+    /// The gas limit of the verification of the operation
+    pub verification_gas_limit: U256,
+    /// The gas paid to the bundler for the overhead of the operation
+    pub pre_verification_gas: U256,
+    /// The max fee per gas, as in EIP-1559
+    pub max_fee_per_gas: U256,
+    /// The max priority fee per gas, as in EIP-1559
+    pub max_priority_fee_per_gas: U256,
+    /// The paymaster address and data, empty if the account pays for itself
+    pub paymaster_and_data: Bytes,
+    /// The signature verified by the account
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Returns the hash of the operation, signed by the owner of the account
+    pub fn hash(&self, entry_point: Address, chain_id: impl Into<U256>) -> H256 {
+        let packed = vec![
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(keccak256(&self.init_code).to_vec()),
+            Token::FixedBytes(keccak256(&self.call_data).to_vec()),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            Token::FixedBytes(keccak256(&self.paymaster_and_data).to_vec()),
+        ];
+        hash_with_entry_point(packed, entry_point, chain_id.into())
+    }
+}
+
+/// A user operation of the v0.7 EntryPoint, as sent to bundlers.
+///
+/// The EntryPoint executes the [`PackedUserOperation`], see [`UserOperationV07::pack`].
+///
+/// Ref: <https://github.com/eth-infinitism/account-abstraction/blob/v0.7.0/erc/ERCS/erc-4337.md>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationV07 {
+    /// The account making the operation
+    pub sender: Address,
+    /// The anti-replay nonce of the account, see `EntryPoint.getNonce`
+    pub nonce: U256,
+    /// The factory deploying the account, `None` if it is deployed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    /// The calldata of the factory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    /// The calldata of the call to the account
+    pub call_data: Bytes,
+    /// The gas limit of the call to the account
+    pub call_gas_limit: U256,
+    /// The gas limit of the verification of the operation
+    pub verification_gas_limit: U256,
+    /// The gas paid to the bundler for the overhead of the operation
+    pub pre_verification_gas: U256,
+    /// The max fee per gas, as in EIP-1559
+    pub max_fee_per_gas: U256,
+    /// The max priority fee per gas, as in EIP-1559
+    pub max_priority_fee_per_gas: U256,
+    /// The paymaster, `None` if the account pays for itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    /// The gas limit of the verification by the paymaster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    /// The gas limit of the post-operation call of the paymaster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    /// The data of the paymaster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    /// The signature verified by the account
+    pub signature: Bytes,
+}
+
+impl UserOperationV07 {
+    /// Returns the operation in the packed format executed by the EntryPoint
+    pub fn pack(&self) -> PackedUserOperation {
+        let init_code = match self.factory {
+            Some(factory) => {
+                let mut init_code = factory.as_bytes().to_vec();
+                init_code.extend_from_slice(self.factory_data.as_deref().unwrap_or_default());
+                init_code.into()
+            }
+            None => Bytes::default(),
+        };
+        let paymaster_and_data = match self.paymaster {
+            Some(paymaster) => {
+                let mut paymaster_and_data = paymaster.as_bytes().to_vec();
+                paymaster_and_data.extend_from_slice(
+                    pack_u128s(
+                        self.paymaster_verification_gas_limit.unwrap_or_default(),
+                        self.paymaster_post_op_gas_limit.unwrap_or_default(),
+                    )
+                    .as_bytes(),
+                );
+                paymaster_and_data
+                    .extend_from_slice(self.paymaster_data.as_deref().unwrap_or_default());
+                paymaster_and_data.into()
+            }
+            None => Bytes::default(),
+        };
+
+        PackedUserOperation {
+            sender: self.sender,
+            nonce: self.nonce,
+            init_code,
+            call_data: self.call_data.clone(),
+            account_gas_limits: pack_u128s(self.verification_gas_limit, self.call_gas_limit),
+            pre_verification_gas: self.pre_verification_gas,
+            gas_fees: pack_u128s(self.max_priority_fee_per_gas, self.max_fee_per_gas),
+            paymaster_and_data,
+            signature: self.signature.clone(),
+        }
+    }
+
+    /// Returns the hash of the operation, signed by the owner of the account
+    pub fn hash(&self, entry_point: Address, chain_id: impl Into<U256>) -> H256 {
+        self.pack().hash(entry_point, chain_id)
+    }
+}
+
+/// A user operation in the packed format of the v0.7 EntryPoint.
+///
+/// Ref: <https://github.com/eth-infinitism/account-abstraction/blob/v0.7.0/contracts/interfaces/PackedUserOperation.sol>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedUserOperation {
+    /// The account making the operation
+    pub sender: Address,
+    /// The anti-replay nonce of the account
+    pub nonce: U256,
+    /// The factory address and calldata deploying the account, empty if it is deployed
+    pub init_code: Bytes,
+    /// The calldata of the call to the account
+    pub call_data: Bytes,
+    /// The verification gas limit and the call gas limit, 16 bytes each
+    pub account_gas_limits: H256,
+    /// The gas paid to the bundler for the overhead of the operation
+    pub pre_verification_gas: U256,
+    /// The max priority fee per gas and the max fee per gas, 16 bytes each
+    pub gas_fees: H256,
+    /// The paymaster address, its gas limits and its data, empty if the account pays for itself
+    pub paymaster_and_data: Bytes,
+    /// The signature verified by the account
+    pub signature: Bytes,
+}
+
+impl PackedUserOperation {
+    /// Returns the hash of the operation, signed by the owner of the account
+    pub fn hash(&self, entry_point: Address, chain_id: impl Into<U256>) -> H256 {
+        let packed = vec![
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(keccak256(&self.init_code).to_vec()),
+            Token::FixedBytes(keccak256(&self.call_data).to_vec()),
+            Token::FixedBytes(self.account_gas_limits.as_bytes().to_vec()),
+            Token::Uint(self.pre_verification_gas),
+            Token::FixedBytes(self.gas_fees.as_bytes().to_vec()),
+            Token::FixedBytes(keccak256(&self.paymaster_and_data).to_vec()),
+        ];
+        hash_with_entry_point(packed, entry_point, chain_id.into())
+    }
+}
+
+/// A user operation of any EntryPoint version, as sent to bundlers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UserOperationRequest {
+    /// A user operation of the v0.6 EntryPoint
+    V06(UserOperation),
+    /// A user operation of the v0.7 EntryPoint
+    V07(UserOperationV07),
+}
+
+impl UserOperationRequest {
+    /// The account making the operation
+    pub fn sender(&self) -> Address {
+        match self {
+            Self::V06(op) => op.sender,
+            Self::V07(op) => op.sender,
+        }
+    }
+
+    /// Returns the hash of the operation, signed by the owner of the account
+    pub fn hash(&self, entry_point: Address, chain_id: impl Into<U256>) -> H256 {
+        match self {
+            Self::V06(op) => op.hash(entry_point, chain_id),
+            Self::V07(op) => op.hash(entry_point, chain_id),
+        }
+    }
+
+    /// Sets the signature verified by the account
+    pub fn set_signature(&mut self, signature: impl Into<Bytes>) -> &mut Self {
+        match self {
+            Self::V06(op) => op.signature = signature.into(),
+            Self::V07(op) => op.signature = signature.into(),
+        }
+        self
+    }
+
+    /// Sets the max fee and the max priority fee per gas
+    pub fn set_fees(&mut self, max_fee_per_gas: U256, max_priority_fee_per_gas: U256) -> &mut Self {
+        match self {
+            Self::V06(op) => {
+                op.max_fee_per_gas = max_fee_per_gas;
+                op.max_priority_fee_per_gas = max_priority_fee_per_gas;
+            }
+            Self::V07(op) => {
+                op.max_fee_per_gas = max_fee_per_gas;
+                op.max_priority_fee_per_gas = max_priority_fee_per_gas;
+            }
+        }
+        self
+    }
+
+    /// Sets the gas limits to the estimate of a bundler
+    pub fn set_gas(&mut self, estimate: &UserOperationGasEstimate) -> &mut Self {
+        match self {
+            Self::V06(op) => {
+                op.pre_verification_gas = estimate.pre_verification_gas;
+                op.verification_gas_limit = estimate.verification_gas_limit;
+                op.call_gas_limit = estimate.call_gas_limit;
+            }
+            Self::V07(op) => {
+                op.pre_verification_gas = estimate.pre_verification_gas;
+                op.verification_gas_limit = estimate.verification_gas_limit;
+                op.call_gas_limit = estimate.call_gas_limit;
+                if op.paymaster.is_some() {
+                    if let Some(gas) = estimate.paymaster_verification_gas_limit {
+                        op.paymaster_verification_gas_limit = Some(gas);
+                    }
+                    if let Some(gas) = estimate.paymaster_post_op_gas_limit {
+                        op.paymaster_post_op_gas_limit = Some(gas);
+                    }
+                }
+            }
+        }
+        self
+    }
+}
+
+impl From<UserOperation> for UserOperationRequest {
+    fn from(op: UserOperation) -> Self {
+        Self::V06(op)
+    }
+}
+
+impl From<UserOperationV07> for UserOperationRequest {
+    fn from(op: UserOperationV07) -> Self {
+        Self::V07(op)
+    }
+}
+
+/// The gas limits of a user operation estimated by `eth_estimateUserOperationGas`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasEstimate {
+    /// The gas paid to the bundler for the overhead of the operation
+    pub pre_verification_gas: U256,
+    /// The gas limit of the verification of the operation
+    pub verification_gas_limit: U256,
+    /// The gas limit of the call to the account
+    pub call_gas_limit: U256,
+    /// The gas limit of the verification by the paymaster, v0.7 only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    /// The gas limit of the post-operation call of the paymaster, v0.7 only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+}
+
+/// The receipt of an included user operation, as returned by `eth_getUserOperationReceipt`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    /// The hash of the operation
+    pub user_op_hash: H256,
+    /// The EntryPoint which executed the operation
+    pub entry_point: Address,
+    /// The account which made the operation
+    pub sender: Address,
+    /// The nonce of the operation
+    pub nonce: U256,
+    /// The paymaster which paid for the operation, if any
+    #[serde(default)]
+    pub paymaster: Option<Address>,
+    /// The fee paid for the operation
+    pub actual_gas_cost: U256,
+    /// The gas used by the operation
+    pub actual_gas_used: U256,
+    /// Whether the call to the account succeeded
+    pub success: bool,
+    /// The revert reason of a failed call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The logs emitted by the operation
+    pub logs: Vec<Log>,
+    /// The receipt of the transaction which included the operation
+    pub receipt: TransactionReceipt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_v07_operation() {
+        let op = UserOperationV07 {
+            sender: Address::repeat_byte(1),
+            nonce: 2.into(),
+            factory: Some(Address::repeat_byte(3)),
+            factory_data: Some(vec![0xaa].into()),
+            call_gas_limit: 4.into(),
+            verification_gas_limit: 5.into(),
+            max_fee_per_gas: 6.into(),
+            max_priority_fee_per_gas: 7.into(),
+            paymaster: Some(Address::repeat_byte(8)),
+            paymaster_verification_gas_limit: Some(9.into()),
+            paymaster_post_op_gas_limit: Some(10.into()),
+            paymaster_data: Some(vec![0xbb].into()),
+            ..Default::default()
+        };
+        let packed = op.pack();
+
+        assert_eq!(&packed.init_code[..20], Address::repeat_byte(3).as_bytes());
+        assert_eq!(&packed.init_code[20..], &[0xaa]);
+        assert_eq!(packed.account_gas_limits.to_low_u64_be(), 4);
+        assert_eq!(U256::from_big_endian(&packed.account_gas_limits[..16]), 5.into());
+        assert_eq!(packed.gas_fees.to_low_u64_be(), 6);
+        assert_eq!(U256::from_big_endian(&packed.gas_fees[..16]), 7.into());
+        assert_eq!(packed.paymaster_and_data.len(), 20 + 32 + 1);
+        assert_eq!(U256::from_big_endian(&packed.paymaster_and_data[20..36]), 9.into());
+        assert_eq!(U256::from_big_endian(&packed.paymaster_and_data[36..52]), 10.into());
+
+        assert_eq!(op.hash(ENTRY_POINT_V07, 1), packed.hash(ENTRY_POINT_V07, 1));
+        assert_ne!(op.hash(ENTRY_POINT_V07, 1), op.hash(ENTRY_POINT_V07, 10));
+    }
+
+    #[test]
+    fn hashes_v06_operation() {
+        let op = UserOperation { sender: Address::repeat_byte(1), ..Default::default() };
+        let inner = keccak256(encode(&[
+            Token::Address(op.sender),
+            Token::Uint(0.into()),
+            Token::FixedBytes(keccak256([]).to_vec()),
+            Token::FixedBytes(keccak256([]).to_vec()),
+            Token::Uint(0.into()),
+            Token::Uint(0.into()),
+            Token::Uint(0.into()),
+            Token::Uint(0.into()),
+            Token::Uint(0.into()),
+            Token::FixedBytes(keccak256([]).to_vec()),
+        ]));
+        let expected = keccak256(encode(&[
+            Token::FixedBytes(inner.to_vec()),
+            Token::Address(ENTRY_POINT_V06),
+            Token::Uint(1.into()),
+        ]));
+        assert_eq!(op.hash(ENTRY_POINT_V06, 1), H256(expected));
+    }
+
+    #[test]
+    fn serializes_requests() {
+        let op: UserOperationRequest = UserOperationV07::default().into();
+        let json = serde_json::to_value(&op).unwrap();
+        assert!(json.get("factory").is_none());
+        assert_eq!(json["callGasLimit"], "0x0");
+        assert_eq!(serde_json::from_value::<UserOperationRequest>(json).unwrap(), op);
+
+        let op: UserOperationRequest = UserOperation::default().into();
+        let json = serde_json::to_value(&op).unwrap();
+        assert_eq!(json["initCode"], "0x");
+        assert_eq!(serde_json::from_value::<UserOperationRequest>(json).unwrap(), op);
+    }
+}
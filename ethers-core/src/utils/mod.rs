@@ -96,6 +96,10 @@ pub const EIP1559_FEE_ESTIMATION_PRIORITY_FEE_TRIGGER: u64 = 100_000_000_000;
 /// The threshold max change/difference (in %) at which we will ignore the fee history values
 /// under it.
 pub const EIP1559_FEE_ESTIMATION_THRESHOLD_MAX_CHANGE: i64 = 200;
+/// The multiplier applied to the next block's blob base fee when estimating the max fee per blob
+/// gas. The blob base fee rises by up to 12.5% per block, so this keeps a transaction includable
+/// for a few full blocks.
+pub const EIP4844_BLOB_FEE_ESTIMATION_MULTIPLIER: u64 = 2;
 
 /// This enum holds the numeric types that a possible to be returned by `parse_units` and
 /// that are taken by `format_units`.
@@ -1,4 +1,5 @@
 use crate::{
+    cache::{CacheMiddleware, CacheStore},
     gas_oracle::{GasOracle, GasOracleMiddleware},
     NonceManagerMiddleware, SignerMiddleware,
 };
@@ -80,6 +81,14 @@ pub trait MiddlewareBuilder: Middleware + Sized + 'static {
     {
         GasOracleMiddleware::new(self, gas_oracle)
     }
+
+    /// Wraps `self` inside a [`CacheMiddleware`].
+    fn cache<S>(self, store: S) -> CacheMiddleware<Self, S>
+    where
+        S: CacheStore,
+    {
+        CacheMiddleware::new(self, store)
+    }
 }
 
 impl<M> MiddlewareBuilder for M where M: Middleware + Sized + 'static {}
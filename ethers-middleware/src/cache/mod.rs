@@ -0,0 +1,486 @@
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::DiskStore;
+pub use store::{CacheStore, CacheStoreError, MemoryStore};
+
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Block, BlockId, BlockNumber, Bytes, Filter,
+    FilterBlockOption, Log, NameOrAddress, Transaction, TransactionReceipt, TxHash, H256, U64,
+};
+use ethers_providers::{Middleware, MiddlewareError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+/// The default number of blocks after which a block is considered final
+pub const DEFAULT_CONFIRMATIONS: u64 = 12;
+
+/// Middleware used for caching the results of RPC requests which can no longer change, so that
+/// repeated requests for historical data are answered without reaching the node.
+///
+/// The following requests are cached:
+/// - `get_block` and `get_block_with_txs` by block hash
+/// - `get_transaction` and `get_transaction_receipt`, once the transaction's block is final
+/// - `get_code`, `get_storage_at` and `call` at a block hash or at a final block number
+/// - `get_logs` at a block hash or over a range of final block numbers
+///
+/// A block is considered final once it is at least `confirmations` blocks deep (default: 12).
+/// Requests made against the latest or pending state are always forwarded.
+///
+/// The cached responses are held in a pluggable [`CacheStore`], e.g. an in-memory
+/// [`MemoryStore`] or an on-disk [`DiskStore`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Middleware, Provider, Http};
+/// use ethers_middleware::cache::{CacheMiddleware, MemoryStore};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let provider = CacheMiddleware::new(provider, MemoryStore::new(10_000)).confirmations(64);
+///
+/// // the second request is answered from the cache
+/// let receipt = provider.get_transaction_receipt(ethers_core::types::H256::zero()).await?;
+/// let receipt = provider.get_transaction_receipt(ethers_core::types::H256::zero()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CacheMiddleware<M, S> {
+    inner: M,
+    store: S,
+    confirmations: u64,
+    /// The highest block number returned by the inner middleware so far
+    latest_block: AtomicU64,
+}
+
+impl<M, S> CacheMiddleware<M, S>
+where
+    M: Middleware,
+    S: CacheStore,
+{
+    /// Creates a new cache middleware holding its responses in `store`
+    pub fn new(inner: M, store: S) -> Self {
+        Self {
+            inner,
+            store,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            latest_block: Default::default(),
+        }
+    }
+
+    /// Sets the number of blocks after which a block is considered final (default: 12)
+    #[must_use]
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Returns the store holding the cached responses
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    fn get_cached<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, CacheMiddlewareError<M>> {
+        match self.store.get(key)? {
+            Some(value) => Ok(Some(serde_json::from_str(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert<T: Serialize>(&self, key: &str, value: &T) -> Result<(), CacheMiddlewareError<M>> {
+        Ok(self.store.insert(key, serde_json::to_string(value)?)?)
+    }
+
+    /// Returns `true` if the block `number` is at least `confirmations` blocks deep
+    async fn is_final(&self, number: U64) -> Result<bool, CacheMiddlewareError<M>> {
+        let required = number.as_u64().saturating_add(self.confirmations);
+        if required <= self.latest_block.load(Ordering::SeqCst) {
+            return Ok(true)
+        }
+
+        let latest = self.inner.get_block_number().await.map_err(CacheMiddlewareError::from_err)?;
+        self.latest_block.fetch_max(latest.as_u64(), Ordering::SeqCst);
+        Ok(required <= latest.as_u64())
+    }
+
+    /// Returns `true` if the state at `block` can no longer change
+    async fn is_final_block(
+        &self,
+        block: Option<BlockId>,
+    ) -> Result<bool, CacheMiddlewareError<M>> {
+        match block {
+            Some(BlockId::Hash(_)) => Ok(true),
+            Some(BlockId::Number(BlockNumber::Number(number))) => self.is_final(number).await,
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns the cache key of the request, or `None` if its result may still change
+    async fn state_key<T: Serialize>(
+        &self,
+        method: &str,
+        params: T,
+        block: Option<BlockId>,
+    ) -> Result<Option<String>, CacheMiddlewareError<M>> {
+        if !self.is_final_block(block).await? {
+            return Ok(None)
+        }
+        Ok(Some(format!("{method}:{}", serde_json::to_string(&(params, block))?)))
+    }
+}
+
+#[derive(Error, Debug)]
+/// Thrown when an error happens at the Cache Middleware
+pub enum CacheMiddlewareError<M: Middleware> {
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+
+    /// Thrown when the store fails to read or write a response
+    #[error(transparent)]
+    StoreError(#[from] CacheStoreError),
+
+    /// Thrown when a response cannot be (de)serialized
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+impl<M: Middleware> MiddlewareError for CacheMiddlewareError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        CacheMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            CacheMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            CacheMiddlewareError::SerdeJson(e) => Some(e),
+            _ => self.as_inner()?.as_serde_error(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for CacheMiddleware<M, S>
+where
+    M: Middleware,
+    S: CacheStore,
+{
+    type Error = CacheMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn get_block_number(&self) -> Result<U64, Self::Error> {
+        let latest = self.inner.get_block_number().await.map_err(CacheMiddlewareError::from_err)?;
+        self.latest_block.fetch_max(latest.as_u64(), Ordering::SeqCst);
+        Ok(latest)
+    }
+
+    async fn get_block<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<TxHash>>, Self::Error> {
+        let id = block_hash_or_number.into();
+        let BlockId::Hash(hash) = id else {
+            return self.inner.get_block(id).await.map_err(MiddlewareError::from_err)
+        };
+
+        let key = format!("eth_getBlockByHash:{hash:?}:false");
+        if let Some(block) = self.get_cached(&key)? {
+            return Ok(Some(block))
+        }
+        let block = self.inner.get_block(id).await.map_err(CacheMiddlewareError::from_err)?;
+        if let Some(ref block) = block {
+            self.insert(&key, block)?;
+        }
+        Ok(block)
+    }
+
+    async fn get_block_with_txs<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<Transaction>>, Self::Error> {
+        let id = block_hash_or_number.into();
+        let BlockId::Hash(hash) = id else {
+            return self.inner.get_block_with_txs(id).await.map_err(MiddlewareError::from_err)
+        };
+
+        let key = format!("eth_getBlockByHash:{hash:?}:true");
+        if let Some(block) = self.get_cached(&key)? {
+            return Ok(Some(block))
+        }
+        let block =
+            self.inner.get_block_with_txs(id).await.map_err(CacheMiddlewareError::from_err)?;
+        if let Some(ref block) = block {
+            self.insert(&key, block)?;
+        }
+        Ok(block)
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = format!("eth_getTransactionByHash:{hash:?}");
+        if let Some(tx) = self.get_cached(&key)? {
+            return Ok(Some(tx))
+        }
+
+        let tx = self.inner.get_transaction(hash).await.map_err(CacheMiddlewareError::from_err)?;
+        if let Some(ref tx) = tx {
+            if let Some(number) = tx.block_number {
+                if self.is_final(number).await? {
+                    self.insert(&key, tx)?;
+                }
+            }
+        }
+        Ok(tx)
+    }
+
+    async fn get_transaction_receipt<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<TransactionReceipt>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = format!("eth_getTransactionReceipt:{hash:?}");
+        if let Some(receipt) = self.get_cached(&key)? {
+            return Ok(Some(receipt))
+        }
+
+        let receipt = self
+            .inner
+            .get_transaction_receipt(hash)
+            .await
+            .map_err(CacheMiddlewareError::from_err)?;
+        if let Some(ref receipt) = receipt {
+            if let Some(number) = receipt.block_number {
+                if self.is_final(number).await? {
+                    self.insert(&key, receipt)?;
+                }
+            }
+        }
+        Ok(receipt)
+    }
+
+    async fn get_code<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        at: T,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let at = at.into();
+        // ENS names may point to a different address later on
+        let key = match at {
+            NameOrAddress::Address(address) => {
+                self.state_key("eth_getCode", address, block).await?
+            }
+            NameOrAddress::Name(_) => None,
+        };
+        let Some(key) = key else {
+            return self.inner.get_code(at, block).await.map_err(MiddlewareError::from_err)
+        };
+
+        if let Some(code) = self.get_cached(&key)? {
+            return Ok(code)
+        }
+        let code = self.inner.get_code(at, block).await.map_err(CacheMiddlewareError::from_err)?;
+        self.insert(&key, &code)?;
+        Ok(code)
+    }
+
+    async fn get_storage_at<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        from: T,
+        location: H256,
+        block: Option<BlockId>,
+    ) -> Result<H256, Self::Error> {
+        let from = from.into();
+        let key = match from {
+            NameOrAddress::Address(address) => {
+                self.state_key("eth_getStorageAt", (address, location), block).await?
+            }
+            NameOrAddress::Name(_) => None,
+        };
+        let Some(key) = key else {
+            return self
+                .inner
+                .get_storage_at(from, location, block)
+                .await
+                .map_err(MiddlewareError::from_err)
+        };
+
+        if let Some(value) = self.get_cached(&key)? {
+            return Ok(value)
+        }
+        let value = self
+            .inner
+            .get_storage_at(from, location, block)
+            .await
+            .map_err(CacheMiddlewareError::from_err)?;
+        self.insert(&key, &value)?;
+        Ok(value)
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let key = match tx.to() {
+            Some(NameOrAddress::Name(_)) => None,
+            _ => self.state_key("eth_call", tx, block).await?,
+        };
+        let Some(key) = key else {
+            return self.inner.call(tx, block).await.map_err(MiddlewareError::from_err)
+        };
+
+        if let Some(res) = self.get_cached(&key)? {
+            return Ok(res)
+        }
+        let res = self.inner.call(tx, block).await.map_err(CacheMiddlewareError::from_err)?;
+        self.insert(&key, &res)?;
+        Ok(res)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        let cacheable = match filter.block_option {
+            FilterBlockOption::AtBlockHash(_) => true,
+            FilterBlockOption::Range {
+                from_block: Some(BlockNumber::Number(_)),
+                to_block: Some(BlockNumber::Number(to)),
+            } => self.is_final(to).await?,
+            _ => false,
+        };
+        if !cacheable {
+            return self.inner.get_logs(filter).await.map_err(MiddlewareError::from_err)
+        }
+
+        let key = format!("eth_getLogs:{}", serde_json::to_string(filter)?);
+        if let Some(logs) = self.get_cached(&key)? {
+            return Ok(logs)
+        }
+        let logs = self.inner.get_logs(filter).await.map_err(CacheMiddlewareError::from_err)?;
+        self.insert(&key, &logs)?;
+        Ok(logs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, TransactionRequest};
+    use ethers_providers::{MockProvider, Provider};
+
+    fn cached_provider(
+        confirmations: u64,
+    ) -> (CacheMiddleware<Provider<MockProvider>, MemoryStore>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        (CacheMiddleware::new(provider, MemoryStore::new(100)).confirmations(confirmations), mock)
+    }
+
+    #[tokio::test]
+    async fn caches_blocks_by_hash() {
+        let (provider, mock) = cached_provider(2);
+        let hash = H256::repeat_byte(1);
+        let block = Block::<TxHash> { hash: Some(hash), ..Default::default() };
+
+        mock.push(block.clone()).unwrap();
+        assert_eq!(provider.get_block(hash).await.unwrap(), Some(block.clone()));
+        // answered from the cache, the mock has no response left
+        assert_eq!(provider.get_block(hash).await.unwrap(), Some(block));
+        assert_eq!(provider.store().len(), 1);
+
+        // blocks by number are not cached
+        mock.push(Option::<Block<TxHash>>::None).unwrap();
+        assert_eq!(provider.get_block(1u64).await.unwrap(), None);
+        assert_eq!(provider.store().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn caches_receipts_once_final() {
+        let (provider, mock) = cached_provider(2);
+        let hash = H256::repeat_byte(1);
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            block_number: Some(10u64.into()),
+            ..Default::default()
+        };
+
+        // not final yet at block 11
+        mock.push(U64::from(11)).unwrap();
+        mock.push(receipt.clone()).unwrap();
+        provider.get_transaction_receipt(hash).await.unwrap();
+        assert!(provider.store().is_empty());
+
+        // final at block 12
+        mock.push(U64::from(12)).unwrap();
+        mock.push(receipt.clone()).unwrap();
+        provider.get_transaction_receipt(hash).await.unwrap();
+        assert_eq!(provider.store().len(), 1);
+
+        assert_eq!(provider.get_transaction_receipt(hash).await.unwrap(), Some(receipt));
+    }
+
+    #[tokio::test]
+    async fn caches_calls_at_final_blocks() {
+        let (provider, mock) = cached_provider(0);
+        let tx: TypedTransaction = TransactionRequest::new().to(Address::zero()).into();
+        let res = Bytes::from(vec![1, 2, 3]);
+
+        // calls against the latest state are forwarded
+        mock.push::<Bytes, _>(res.clone()).unwrap();
+        provider.call(&tx, None).await.unwrap();
+        assert!(provider.store().is_empty());
+
+        mock.push::<Bytes, _>(res.clone()).unwrap();
+        mock.push(U64::from(5)).unwrap();
+        assert_eq!(provider.call(&tx, Some(5u64.into())).await.unwrap(), res);
+        assert_eq!(provider.call(&tx, Some(5u64.into())).await.unwrap(), res);
+
+        // the latest block number is remembered for older blocks
+        mock.push(H256::repeat_byte(2)).unwrap();
+        let value = provider
+            .get_storage_at(Address::zero(), H256::zero(), Some(4u64.into()))
+            .await
+            .unwrap();
+        assert_eq!(value, H256::repeat_byte(2));
+        assert_eq!(provider.store().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn caches_logs_over_final_ranges() {
+        let (provider, mock) = cached_provider(0);
+        let logs = vec![Log { block_number: Some(3u64.into()), ..Default::default() }];
+
+        let open = Filter::new().from_block(1u64);
+        mock.push::<Vec<Log>, _>(logs.clone()).unwrap();
+        provider.get_logs(&open).await.unwrap();
+        assert!(provider.store().is_empty());
+
+        let closed = Filter::new().from_block(1u64).to_block(3u64);
+        mock.push::<Vec<Log>, _>(logs.clone()).unwrap();
+        mock.push(U64::from(3)).unwrap();
+        assert_eq!(provider.get_logs(&closed).await.unwrap(), logs);
+        assert_eq!(provider.get_logs(&closed).await.unwrap(), logs);
+        assert_eq!(provider.store().len(), 1);
+    }
+}
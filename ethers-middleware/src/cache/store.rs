@@ -0,0 +1,196 @@
+use auto_impl::auto_impl;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    sync::Mutex,
+};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::file_store;
+#[cfg(not(target_arch = "wasm32"))]
+use ethers_core::{types::H256, utils::keccak256};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::PathBuf};
+
+/// Error thrown by a [`CacheStore`]
+#[derive(Debug, Error)]
+pub enum CacheStoreError {
+    /// An I/O error of an on-disk store
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// An error of a custom store
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// A key-value store holding the responses cached by the
+/// [`CacheMiddleware`](super::CacheMiddleware).
+///
+/// Values are the JSON-serialized responses, keyed by the RPC method and its parameters.
+#[auto_impl(&, Box, Arc)]
+pub trait CacheStore: Debug + Send + Sync {
+    /// Returns the value stored under `key`, if any
+    fn get(&self, key: &str) -> Result<Option<String>, CacheStoreError>;
+
+    /// Stores `value` under `key`, replacing any previous value
+    fn insert(&self, key: &str, value: String) -> Result<(), CacheStoreError>;
+}
+
+/// An in-memory [`CacheStore`] which evicts the least recently used entry once it holds
+/// `capacity` entries.
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    /// The values and the tick they were last used at
+    entries: HashMap<String, (String, u64)>,
+    /// The keys ordered by the tick they were last used at
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn touch(&mut self, key: &str) -> Option<&mut String> {
+        self.tick += 1;
+        let (value, used) = self.entries.get_mut(key)?;
+        let key = self.recency.remove(used).expect("recency is in sync with entries");
+        *used = self.tick;
+        self.recency.insert(self.tick, key);
+        Some(value)
+    }
+}
+
+impl MemoryStore {
+    /// Creates a new store holding at most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), inner: Default::default() }
+    }
+
+    /// Returns the number of cached entries
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Returns `true` if the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Result<Option<String>, CacheStoreError> {
+        Ok(self.inner.lock().unwrap().touch(key).cloned())
+    }
+
+    fn insert(&self, key: &str, value: String) -> Result<(), CacheStoreError> {
+        let mut lru = self.inner.lock().unwrap();
+        if let Some(entry) = lru.touch(key) {
+            *entry = value;
+            return Ok(())
+        }
+
+        if lru.entries.len() >= self.capacity {
+            if let Some(oldest) = lru.recency.keys().next().copied() {
+                let evicted = lru.recency.remove(&oldest).expect("key exists");
+                lru.entries.remove(&evicted);
+            }
+        }
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.recency.insert(tick, key.to_string());
+        lru.entries.insert(key.to_string(), (value, tick));
+        Ok(())
+    }
+}
+
+/// An on-disk [`CacheStore`], storing every entry in its own file inside a directory.
+///
+/// Entries are never evicted, so the store can be shared across runs of the same program. The
+/// entries of every chain are kept in their own subdirectory, as the same request, e.g. for a
+/// block number, has different responses on different chains.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DiskStore {
+    /// Creates a new store for the chain with the id `chain_id` in `dir`, creating the directory
+    /// if it does not exist
+    pub fn new(dir: impl Into<PathBuf>, chain_id: u64) -> Result<Self, CacheStoreError> {
+        let dir = dir.into().join(chain_id.to_string());
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// The directory the entries of the chain are stored in
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:x}.json", H256::from(keccak256(key))))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CacheStore for DiskStore {
+    fn get(&self, key: &str) -> Result<Option<String>, CacheStoreError> {
+        Ok(file_store::read(&self.path(key))?)
+    }
+
+    fn insert(&self, key: &str, value: String) -> Result<(), CacheStoreError> {
+        Ok(file_store::write(&self.path(key), value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_store_evicts_least_recently_used() {
+        let store = MemoryStore::new(2);
+        store.insert("a", "1".to_string()).unwrap();
+        store.insert("b", "2".to_string()).unwrap();
+
+        // using `a` makes `b` the least recently used entry
+        assert_eq!(store.get("a").unwrap(), Some("1".to_string()));
+        store.insert("c", "3".to_string()).unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get("b").unwrap(), None);
+        assert_eq!(store.get("a").unwrap(), Some("1".to_string()));
+        assert_eq!(store.get("c").unwrap(), Some("3".to_string()));
+
+        // overwriting does not evict
+        store.insert("a", "4".to_string()).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get("a").unwrap(), Some("4".to_string()));
+    }
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    fn disk_store_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DiskStore::new(dir.path().join("cache"), 1).unwrap();
+
+        assert_eq!(store.get("eth_getBlockByHash:0x01").unwrap(), None);
+        store.insert("eth_getBlockByHash:0x01", "{}".to_string()).unwrap();
+
+        let store = DiskStore::new(dir.path().join("cache"), 1).unwrap();
+        assert_eq!(store.get("eth_getBlockByHash:0x01").unwrap(), Some("{}".to_string()));
+        assert_eq!(fs::read_dir(store.dir()).unwrap().count(), 1);
+
+        // other chains don't share the entries
+        let store = DiskStore::new(dir.path().join("cache"), 5).unwrap();
+        assert_eq!(store.get("eth_getBlockByHash:0x01").unwrap(), None);
+    }
+}
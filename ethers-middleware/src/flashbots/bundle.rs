@@ -0,0 +1,299 @@
+use ethers_core::{
+    types::{
+        serde_helpers::deserialize_stringified_numeric, Address, BlockNumber, Bytes, TxHash, H256,
+        U256, U64,
+    },
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+
+/// A bundle of signed transactions, included atomically and in order in a target block.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::Bytes;
+/// use ethers_middleware::flashbots::BundleRequest;
+///
+/// # let (approve, swap): (Bytes, Bytes) = Default::default();
+/// let bundle = BundleRequest::new()
+///     .push_transaction(approve)
+///     .push_transaction(swap)
+///     .set_block(17_000_000u64)
+///     .set_max_timestamp(1_700_000_000);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[must_use]
+pub struct BundleRequest {
+    transactions: Vec<Bytes>,
+    reverting_tx_hashes: Vec<TxHash>,
+    block: Option<U64>,
+    min_timestamp: Option<u64>,
+    max_timestamp: Option<u64>,
+    replacement_uuid: Option<String>,
+    simulation_block: Option<BlockNumber>,
+    simulation_timestamp: Option<u64>,
+}
+
+impl BundleRequest {
+    /// Creates an empty bundle
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a signed transaction to the bundle
+    pub fn push_transaction(mut self, tx: impl Into<Bytes>) -> Self {
+        self.transactions.push(tx.into());
+        self
+    }
+
+    /// Appends a signed transaction to the bundle, which is allowed to revert
+    pub fn push_revertible_transaction(mut self, tx: impl Into<Bytes>) -> Self {
+        let tx = tx.into();
+        self.reverting_tx_hashes.push(keccak256(&tx).into());
+        self.transactions.push(tx);
+        self
+    }
+
+    /// The signed transactions of the bundle
+    pub fn transactions(&self) -> &[Bytes] {
+        &self.transactions
+    }
+
+    /// The hashes of the transactions of the bundle
+    pub fn transaction_hashes(&self) -> Vec<TxHash> {
+        self.transactions.iter().map(|tx| keccak256(tx).into()).collect()
+    }
+
+    /// The block the bundle targets
+    pub fn block(&self) -> Option<U64> {
+        self.block
+    }
+
+    /// Sets the block the bundle targets
+    pub fn set_block(mut self, block: impl Into<U64>) -> Self {
+        self.block = Some(block.into());
+        self
+    }
+
+    /// Sets the minimum timestamp of the block including the bundle
+    pub fn set_min_timestamp(mut self, timestamp: u64) -> Self {
+        self.min_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the maximum timestamp of the block including the bundle
+    pub fn set_max_timestamp(mut self, timestamp: u64) -> Self {
+        self.max_timestamp = Some(timestamp);
+        self
+    }
+
+    /// Sets the id which allows to replace or cancel the bundle
+    pub fn set_replacement_uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.replacement_uuid = Some(uuid.into());
+        self
+    }
+
+    /// Sets the block whose state the bundle is simulated on, defaults to the latest block
+    pub fn set_simulation_block(mut self, block: impl Into<BlockNumber>) -> Self {
+        self.simulation_block = Some(block.into());
+        self
+    }
+
+    /// Sets the timestamp of the simulated block
+    pub fn set_simulation_timestamp(mut self, timestamp: u64) -> Self {
+        self.simulation_timestamp = Some(timestamp);
+        self
+    }
+
+    /// The parameters of `eth_sendBundle`
+    pub(crate) fn send_params(&self) -> SendBundleParams<'_> {
+        SendBundleParams {
+            txs: &self.transactions,
+            block_number: self.block,
+            min_timestamp: self.min_timestamp,
+            max_timestamp: self.max_timestamp,
+            reverting_tx_hashes: &self.reverting_tx_hashes,
+            replacement_uuid: self.replacement_uuid.as_deref(),
+        }
+    }
+
+    /// The parameters of `eth_callBundle`
+    pub(crate) fn call_params(&self) -> CallBundleParams<'_> {
+        CallBundleParams {
+            txs: &self.transactions,
+            block_number: self.block,
+            state_block_number: self.simulation_block.unwrap_or(BlockNumber::Latest),
+            timestamp: self.simulation_timestamp,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendBundleParams<'a> {
+    txs: &'a [Bytes],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_number: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_timestamp: Option<u64>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    reverting_tx_hashes: &'a [TxHash],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replacement_uuid: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CallBundleParams<'a> {
+    txs: &'a [Bytes],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_number: Option<U64>,
+    state_block_number: BlockNumber,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+}
+
+/// The response of `eth_sendBundle`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendBundleResponse {
+    pub(crate) bundle_hash: H256,
+}
+
+/// The result of simulating a bundle with `eth_callBundle`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBundle {
+    /// The hash of the bundle
+    pub bundle_hash: H256,
+    /// The effective gas price of the bundle, paid to the coinbase per gas
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub bundle_gas_price: U256,
+    /// The change of the balance of the coinbase
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub coinbase_diff: U256,
+    /// The ether sent directly to the coinbase
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub eth_sent_to_coinbase: U256,
+    /// The gas fees paid by the bundle
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_fees: U256,
+    /// The results of the transactions, in order
+    pub results: Vec<SimulatedTransaction>,
+    /// The block whose state the bundle was simulated on
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub state_block_number: U256,
+    /// The gas used by the bundle
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub total_gas_used: U256,
+}
+
+impl SimulatedBundle {
+    /// Returns the first transaction which reverted or failed, if any
+    pub fn first_failure(&self) -> Option<&SimulatedTransaction> {
+        self.results.iter().find(|tx| tx.error.is_some() || tx.revert.is_some())
+    }
+}
+
+/// The result of a transaction of a simulated bundle
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTransaction {
+    /// The hash of the transaction
+    pub tx_hash: TxHash,
+    /// The sender of the transaction
+    pub from_address: Address,
+    /// The recipient of the transaction, `None` for deployments
+    #[serde(default)]
+    pub to_address: Option<Address>,
+    /// The gas used by the transaction
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_used: U256,
+    /// The effective gas price of the transaction
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_price: U256,
+    /// The gas fees paid by the transaction
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_fees: U256,
+    /// The change of the balance of the coinbase
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub coinbase_diff: U256,
+    /// The ether sent directly to the coinbase
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub eth_sent_to_coinbase: U256,
+    /// The return data of the transaction
+    #[serde(default)]
+    pub value: Option<Bytes>,
+    /// The error of a failed transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The revert reason of a reverted transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_bundle_params() {
+        let bundle = BundleRequest::new()
+            .push_transaction(vec![1u8])
+            .push_revertible_transaction(vec![2u8])
+            .set_block(16u64)
+            .set_max_timestamp(100);
+
+        assert_eq!(
+            serde_json::to_value(bundle.send_params()).unwrap(),
+            serde_json::json!({
+                "txs": ["0x01", "0x02"],
+                "blockNumber": "0x10",
+                "maxTimestamp": 100,
+                "revertingTxHashes": [H256::from(keccak256([2u8]))],
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(bundle.call_params()).unwrap(),
+            serde_json::json!({
+                "txs": ["0x01", "0x02"],
+                "blockNumber": "0x10",
+                "stateBlockNumber": "latest",
+            })
+        );
+        assert_eq!(bundle.transaction_hashes()[0], H256::from(keccak256([1u8])));
+    }
+
+    #[test]
+    fn deserializes_simulated_bundle() {
+        let bundle: SimulatedBundle = serde_json::from_value(serde_json::json!({
+            "bundleGasPrice": "476190476193",
+            "bundleHash": "0x73b1e258c7a42fd0230b2fd05529c5d4b6fcb66c227783f8bece8aeacdd1db2e",
+            "coinbaseDiff": "20000000000126000",
+            "ethSentToCoinbase": "20000000000000000",
+            "gasFees": "126000",
+            "results": [{
+                "coinbaseDiff": "10000000000063000",
+                "ethSentToCoinbase": "10000000000000000",
+                "fromAddress": "0x02A727155aeF8609c9f7F2179b2a1f560B39F5A0",
+                "gasFees": "63000",
+                "gasPrice": "476190476193",
+                "gasUsed": 21000,
+                "toAddress": "0x73625f59CAdc5009Cb458B751b3E7b6b48C06f2C",
+                "txHash": "0x669b4704a7d993a946cdd6e2f95233f308ce0c4649d2e04944e8299efcaa098a",
+                "value": "0x",
+                "revert": "insufficient balance"
+            }],
+            "stateBlockNumber": 5221585,
+            "totalGasUsed": 42000
+        }))
+        .unwrap();
+
+        assert_eq!(bundle.total_gas_used, 42_000.into());
+        assert_eq!(bundle.results[0].gas_used, 21_000.into());
+        assert_eq!(bundle.first_failure().unwrap().revert.as_deref(), Some("insufficient balance"));
+    }
+}
@@ -0,0 +1,230 @@
+use super::{bundle::SendBundleResponse, BundleRequest, Relay, RelayError, SimulatedBundle};
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, Bytes, TxHash, H256, U64,
+};
+use ethers_providers::{Middleware, MiddlewareError, PendingTransaction};
+use ethers_signers::Signer;
+use serde::Serialize;
+use thiserror::Error;
+use url::Url;
+
+/// Middleware sending transactions privately to a MEV relay, such as the Flashbots relay.
+///
+/// Transactions signed below this middleware, e.g. by a [`SignerMiddleware`] wrapping it, are
+/// sent with `eth_sendPrivateTransaction` instead of the public mempool. Bundles are built with
+/// [`BundleRequest`], simulated with [`FlashbotsMiddleware::simulate_bundle`] and sent with
+/// [`FlashbotsMiddleware::send_bundle`]. All other requests go to the inner middleware.
+///
+/// The requests to the relay are signed by a separate searcher key, which identifies the
+/// searcher to the relay and does not need to hold any funds.
+///
+/// Unsigned transactions are refused instead of being sent with `eth_sendTransaction`, which
+/// would publish them to the public mempool of the node.
+///
+/// [`SignerMiddleware`]: crate::SignerMiddleware
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Bytes;
+/// use ethers_middleware::{flashbots::BundleRequest, FlashbotsMiddleware};
+/// use ethers_providers::{Http, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo(tx: Bytes) -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let searcher = LocalWallet::new(&mut rand::thread_rng());
+/// let client = FlashbotsMiddleware::new(provider, "https://relay.flashbots.net".parse()?, searcher);
+///
+/// let bundle = BundleRequest::new().push_transaction(tx);
+/// let simulation = client.simulate_bundle(&bundle).await?;
+/// if simulation.first_failure().is_none() {
+///     let bundle_hash = client.send_bundle(&bundle).await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FlashbotsMiddleware<M, S> {
+    inner: M,
+    relay: Relay<S>,
+}
+
+/// The parameters of `eth_sendPrivateTransaction`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrivateTransactionParams<'a> {
+    tx: &'a Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_block_number: Option<U64>,
+}
+
+impl<M, S> FlashbotsMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    /// Creates a new middleware sending bundles and private transactions to the relay at
+    /// `relay_url`, signing its requests with `searcher`.
+    pub fn new(inner: M, relay_url: Url, searcher: S) -> Self {
+        Self { inner, relay: Relay::new(relay_url, searcher) }
+    }
+
+    /// Returns the client of the relay.
+    pub fn relay(&self) -> &Relay<S> {
+        &self.relay
+    }
+
+    /// Sends a bundle to the relay, returning the hash of the bundle.
+    ///
+    /// Bundles without a target block target the block after the current one.
+    pub async fn send_bundle(
+        &self,
+        bundle: &BundleRequest,
+    ) -> Result<H256, FlashbotsMiddlewareError<M, S>> {
+        let bundle = self.with_target_block(bundle).await?;
+        let response: SendBundleResponse =
+            self.relay.request("eth_sendBundle", [bundle.send_params()]).await?;
+        Ok(response.bundle_hash)
+    }
+
+    /// Simulates a bundle with `eth_callBundle` on the state of its simulation block.
+    pub async fn simulate_bundle(
+        &self,
+        bundle: &BundleRequest,
+    ) -> Result<SimulatedBundle, FlashbotsMiddlewareError<M, S>> {
+        let bundle = self.with_target_block(bundle).await?;
+        Ok(self.relay.request("eth_callBundle", [bundle.call_params()]).await?)
+    }
+
+    /// Cancels the bundles sent with the replacement id `uuid`.
+    pub async fn cancel_bundle(
+        &self,
+        uuid: impl Into<String>,
+    ) -> Result<(), FlashbotsMiddlewareError<M, S>> {
+        let params = serde_json::json!([{ "replacementUuid": uuid.into() }]);
+        let _: serde_json::Value = self.relay.request("eth_cancelBundle", params).await?;
+        Ok(())
+    }
+
+    /// Sends a signed transaction privately to the relay, which tries to include it until
+    /// `max_block`, returning the hash of the transaction.
+    pub async fn send_private_transaction(
+        &self,
+        tx: Bytes,
+        max_block: Option<U64>,
+    ) -> Result<TxHash, FlashbotsMiddlewareError<M, S>> {
+        let params = PrivateTransactionParams { tx: &tx, max_block_number: max_block };
+        Ok(self.relay.request("eth_sendPrivateTransaction", [params]).await?)
+    }
+
+    /// Returns the bundle targeting the block after the current one, if it does not target a
+    /// block yet.
+    async fn with_target_block(
+        &self,
+        bundle: &BundleRequest,
+    ) -> Result<BundleRequest, FlashbotsMiddlewareError<M, S>> {
+        if bundle.block().is_some() {
+            return Ok(bundle.clone())
+        }
+        let block = self
+            .inner
+            .get_block_number()
+            .await
+            .map_err(FlashbotsMiddlewareError::MiddlewareError)?;
+        Ok(bundle.clone().set_block(block + 1))
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the client sends requests to the relay
+pub enum FlashbotsMiddlewareError<M: Middleware, S: Signer> {
+    /// Thrown when a request to the relay fails
+    #[error(transparent)]
+    RelayError(#[from] RelayError<S>),
+
+    /// Thrown when an unsigned transaction is sent, which the node would publish to the public
+    /// mempool
+    #[error("unsigned transactions can not be sent privately, sign them below this middleware")]
+    UnsignedTransaction,
+
+    /// Thrown when an internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware, S: Signer> MiddlewareError for FlashbotsMiddlewareError<M, S> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        FlashbotsMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            FlashbotsMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for FlashbotsMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    type Error = FlashbotsMiddlewareError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Refuses unsigned transactions, which the node would send to the public mempool.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        _tx: T,
+        _block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        Err(FlashbotsMiddlewareError::UnsignedTransaction)
+    }
+
+    /// Sends the signed transaction privately to the relay instead of the public mempool.
+    async fn send_raw_transaction<'a>(
+        &'a self,
+        tx: Bytes,
+    ) -> Result<PendingTransaction<'a, Self::Provider>, Self::Error> {
+        let hash = self.send_private_transaction(tx, None).await?;
+        Ok(PendingTransaction::new(hash, self.provider()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{TransactionRequest, H160};
+    use ethers_providers::Provider;
+    use ethers_signers::LocalWallet;
+
+    #[tokio::test]
+    async fn refuses_unsigned_transactions() {
+        let (provider, mock) = Provider::mocked();
+        let searcher = LocalWallet::new(&mut rand::thread_rng());
+        let client = FlashbotsMiddleware::new(
+            provider,
+            Url::parse("https://relay.flashbots.net").unwrap(),
+            searcher,
+        );
+
+        let tx = TransactionRequest::new().to(H160::zero()).value(1u64);
+        let err = client.send_transaction(tx, None).await.unwrap_err();
+        assert!(matches!(err, FlashbotsMiddlewareError::UnsignedTransaction));
+        // nothing reached the node
+        assert!(mock.assert_request("eth_sendTransaction", ()).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Private transaction and bundle submission to MEV relays, such as the
+//! [Flashbots relay](https://docs.flashbots.net/flashbots-auction/advanced/rpc-endpoint).
+
+mod bundle;
+pub use bundle::{BundleRequest, SimulatedBundle, SimulatedTransaction};
+
+mod relay;
+pub use relay::{Relay, RelayError};
+
+mod middleware;
+pub use middleware::{FlashbotsMiddleware, FlashbotsMiddlewareError};
@@ -0,0 +1,135 @@
+use ethers_core::{
+    types::H256,
+    utils::{hex, keccak256},
+};
+use ethers_providers::JsonRpcError;
+use ethers_signers::Signer;
+use reqwest::{Client, Error as ReqwestError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+use url::Url;
+
+/// The header authenticating the searcher to the relay
+const FLASHBOTS_SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+/// A JSON-RPC client of a MEV relay, such as the Flashbots relay.
+///
+/// Every request is signed by the searcher, whose address identifies the sender of bundles to
+/// the relay and builds its reputation. The searcher key should not hold any funds.
+#[derive(Debug)]
+pub struct Relay<S> {
+    id: AtomicU64,
+    client: Client,
+    url: Url,
+    signer: S,
+}
+
+/// Error thrown when sending requests to a relay.
+#[derive(Debug, Error)]
+pub enum RelayError<S: Signer> {
+    /// The request could not be sent
+    #[error(transparent)]
+    RequestError(#[from] ReqwestError),
+
+    /// The relay responded with an error
+    #[error(transparent)]
+    JsonRpcError(#[from] JsonRpcError),
+
+    /// The response could not be deserialized
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    /// The searcher could not sign the request
+    #[error("{0}")]
+    SignerError(S::Error),
+}
+
+#[derive(Deserialize)]
+struct RelayResponse<R> {
+    result: Option<R>,
+    error: Option<JsonRpcError>,
+}
+
+impl<S: Signer> Relay<S> {
+    /// Creates a client of the relay at `url`, signing requests with `signer`
+    pub fn new(url: Url, signer: S) -> Self {
+        Self { id: AtomicU64::new(1), client: Client::new(), url, signer }
+    }
+
+    /// The URL of the relay
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// The searcher signing the requests
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Sends a signed JSON-RPC request to the relay
+    pub async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, RelayError<S>> {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let body = serde_json::to_string(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+        let signature = self.signature_header(&body).await?;
+
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(FLASHBOTS_SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await?
+            .bytes()
+            .await?;
+
+        let response: RelayResponse<R> = serde_json::from_slice(&response)?;
+        match (response.result, response.error) {
+            (_, Some(err)) => Err(err.into()),
+            (Some(result), None) => Ok(result),
+            // the methods without a result respond with `null`
+            (None, None) => Ok(serde_json::from_value(serde_json::Value::Null)?),
+        }
+    }
+
+    /// Returns the value of the signature header for the request `body`: the address of the
+    /// searcher and its signature of the hex encoded hash of the body
+    pub(crate) async fn signature_header(&self, body: &str) -> Result<String, RelayError<S>> {
+        let hash = format!("{:?}", H256::from(keccak256(body)));
+        let signature = self.signer.sign_message(hash).await.map_err(RelayError::SignerError)?;
+        Ok(format!("{:?}:0x{}", self.signer.address(), hex::encode(signature.to_vec())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, Signature};
+    use ethers_signers::LocalWallet;
+
+    #[tokio::test]
+    async fn signs_requests() {
+        let searcher = LocalWallet::new(&mut rand::thread_rng());
+        let relay =
+            Relay::new(Url::parse("https://relay.flashbots.net").unwrap(), searcher.clone());
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"eth_sendBundle","params":[]}"#;
+
+        let header = relay.signature_header(body).await.unwrap();
+        let (address, signature) = header.split_once(':').unwrap();
+        assert_eq!(address.parse::<Address>().unwrap(), searcher.address());
+
+        let signature: Signature = signature.parse().unwrap();
+        let hash = format!("{:?}", H256::from(keccak256(body)));
+        assert_eq!(signature.recover(hash).unwrap(), searcher.address());
+    }
+}
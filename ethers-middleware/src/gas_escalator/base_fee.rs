@@ -0,0 +1,78 @@
+use super::GasEscalator;
+use ethers_core::types::U256;
+
+/// Gas price escalation relative to the current base fee.
+///
+/// The priority fee of EIP-1559 transactions is escalated by the `inner` escalator. The max fee
+/// is escalated the same way, but raised to at least `base_fee_multiplier` times the current base
+/// fee plus the priority fee, so that the transaction stays includable while the base fee rises.
+/// There is an optional upper limit for the max fee.
+///
+/// Gas prices of legacy transactions are escalated by the `inner` escalator.
+#[derive(Clone, Debug)]
+pub struct BaseFeeGasPrice<E> {
+    inner: E,
+    base_fee_multiplier: u64,
+    max_fee: Option<U256>,
+}
+
+impl<E> BaseFeeGasPrice<E> {
+    /// Constructor
+    ///
+    /// Note: Providing `None` to `max_fee` requires giving it a type-hint, so you'll need
+    /// to call this like `BaseFeeGasPrice::new(escalator, 2, None::<u64>)`.
+    pub fn new<T: Into<U256>>(inner: E, base_fee_multiplier: u64, max_fee: Option<T>) -> Self {
+        BaseFeeGasPrice { inner, base_fee_multiplier, max_fee: max_fee.map(Into::into) }
+    }
+}
+
+impl<E: GasEscalator> GasEscalator for BaseFeeGasPrice<E> {
+    fn get_gas_price(&self, initial_price: U256, time_elapsed: u64) -> U256 {
+        self.inner.get_gas_price(initial_price, time_elapsed)
+    }
+
+    fn get_eip1559_fees(
+        &self,
+        (max_fee_per_gas, max_priority_fee_per_gas): (U256, U256),
+        time_elapsed: u64,
+        base_fee: U256,
+    ) -> (U256, U256) {
+        let max_priority_fee_per_gas =
+            self.inner.get_gas_price(max_priority_fee_per_gas, time_elapsed);
+        let mut max_fee_per_gas = std::cmp::max(
+            self.inner.get_gas_price(max_fee_per_gas, time_elapsed),
+            base_fee * self.base_fee_multiplier + max_priority_fee_per_gas,
+        );
+        if let Some(max_fee) = self.max_fee {
+            max_fee_per_gas = std::cmp::min(max_fee_per_gas, max_fee);
+        }
+        (max_fee_per_gas, max_priority_fee_per_gas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas_escalator::GeometricGasPrice;
+
+    #[test]
+    fn max_fee_follows_base_fee() {
+        let escalator =
+            BaseFeeGasPrice::new(GeometricGasPrice::new(1.125, 10u64, None::<u64>), 2, None::<u64>);
+        let fees = (U256::from(300), U256::from(100));
+
+        assert_eq!(escalator.get_eip1559_fees(fees, 0, 100.into()), (300.into(), 100.into()));
+        assert_eq!(escalator.get_eip1559_fees(fees, 10, 100.into()), (338.into(), 113.into()));
+        // the base fee rose, the max fee must cover it
+        assert_eq!(escalator.get_eip1559_fees(fees, 10, 400.into()), (913.into(), 113.into()));
+    }
+
+    #[test]
+    fn max_fee_should_obey_max_value() {
+        let escalator =
+            BaseFeeGasPrice::new(GeometricGasPrice::new(1.125, 10u64, None::<u64>), 2, Some(500));
+        let fees = (U256::from(300), U256::from(100));
+
+        assert_eq!(escalator.get_eip1559_fees(fees, 10, 400.into()), (500.into(), 113.into()));
+    }
+}
@@ -4,6 +4,9 @@ pub use geometric::GeometricGasPrice;
 mod linear;
 pub use linear::LinearGasPrice;
 
+mod base_fee;
+pub use base_fee::BaseFeeGasPrice;
+
 use async_trait::async_trait;
 
 use futures_channel::oneshot;
@@ -14,14 +17,20 @@ use thiserror::Error;
 use tracing_futures::Instrument;
 
 use ethers_core::types::{
-    transaction::eip2718::TypedTransaction, BlockId, TransactionRequest, TxHash, U256,
+    transaction::eip2718::TypedTransaction, BlockId, BlockNumber, TxHash, U256,
+};
+use ethers_providers::{
+    interval, Middleware, MiddlewareError, PendingTransaction, RpcErrorKind, StreamExt,
 };
-use ethers_providers::{interval, Middleware, MiddlewareError, PendingTransaction, StreamExt};
 
 #[cfg(not(target_arch = "wasm32"))]
 use tokio::spawn;
 
-type ToEscalate = Arc<Mutex<Vec<(TxHash, TransactionRequest, Instant, Option<BlockId>)>>>;
+type ToEscalate = Arc<Mutex<Vec<(TxHash, TypedTransaction, Instant, Option<BlockId>)>>>;
+
+/// The minimum percentage by which nodes require the fees of a replacement transaction to be
+/// higher than the fees of the transaction it replaces
+pub const REPLACEMENT_BUMP_PERCENTAGE: u64 = 10;
 
 #[cfg(target_arch = "wasm32")]
 type WatcherFuture<'a> = Pin<Box<dyn futures_util::stream::Stream<Item = ()> + 'a>>;
@@ -34,6 +43,74 @@ pub trait GasEscalator: Send + Sync + std::fmt::Debug {
     /// Given the initial gas price and the time elapsed since the transaction's
     /// first broadcast, it returns the new gas price
     fn get_gas_price(&self, initial_price: U256, time_elapsed: u64) -> U256;
+
+    /// Given the initial `(max_fee_per_gas, max_priority_fee_per_gas)` of an
+    /// EIP-1559 transaction, the time elapsed since the transaction's first
+    /// broadcast and the base fee of the latest block, it returns the new fees.
+    ///
+    /// By default, both fees are escalated independently with
+    /// [`GasEscalator::get_gas_price`].
+    fn get_eip1559_fees(
+        &self,
+        initial_fees: (U256, U256),
+        time_elapsed: u64,
+        base_fee: U256,
+    ) -> (U256, U256) {
+        let _ = base_fee;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = initial_fees;
+        (
+            self.get_gas_price(max_fee_per_gas, time_elapsed),
+            self.get_gas_price(max_priority_fee_per_gas, time_elapsed),
+        )
+    }
+}
+
+/// Returns the lowest fee a node accepts to replace a transaction paying `fee`
+fn min_replacement_fee(fee: U256) -> U256 {
+    fee + (fee * REPLACEMENT_BUMP_PERCENTAGE + 99) / 100
+}
+
+/// Escalates the fees of `tx`, returns `false` if the fees were not raised enough to replace
+/// the transaction, in which case `tx` is left unchanged.
+fn escalate_fees<E: GasEscalator>(
+    escalator: &E,
+    tx: &mut TypedTransaction,
+    time_elapsed: u64,
+    base_fee: U256,
+) -> bool {
+    match tx {
+        TypedTransaction::Eip1559(inner) => {
+            let max_fee_per_gas = inner.max_fee_per_gas.unwrap_or_default();
+            let max_priority_fee_per_gas = inner.max_priority_fee_per_gas.unwrap_or_default();
+            let (new_max_fee_per_gas, new_max_priority_fee_per_gas) = escalator.get_eip1559_fees(
+                (max_fee_per_gas, max_priority_fee_per_gas),
+                time_elapsed,
+                base_fee,
+            );
+            // the tip can never exceed the max fee
+            let new_max_priority_fee_per_gas =
+                std::cmp::min(new_max_priority_fee_per_gas, new_max_fee_per_gas);
+            // nodes only replace a transaction if both fees are bumped
+            if new_max_fee_per_gas <= max_fee_per_gas ||
+                new_max_fee_per_gas < min_replacement_fee(max_fee_per_gas) ||
+                new_max_priority_fee_per_gas < min_replacement_fee(max_priority_fee_per_gas)
+            {
+                return false
+            }
+            inner.max_fee_per_gas = Some(new_max_fee_per_gas);
+            inner.max_priority_fee_per_gas = Some(new_max_priority_fee_per_gas);
+            true
+        }
+        _ => {
+            let gas_price = tx.gas_price().unwrap_or_default();
+            let new_gas_price = escalator.get_gas_price(gas_price, time_elapsed);
+            if new_gas_price <= gas_price || new_gas_price < min_replacement_fee(gas_price) {
+                return false
+            }
+            tx.set_gas_price(new_gas_price);
+            true
+        }
+    }
 }
 
 /// Error thrown when the GasEscalator interacts with the blockchain
@@ -43,7 +120,7 @@ pub enum GasEscalatorError<M: Middleware> {
     /// Thrown when an internal middleware errors
     MiddlewareError(M::Error),
 
-    #[error("Gas escalation is only supported for EIP2930 or Legacy transactions")]
+    #[error("Gas escalation is only supported for EIP1559, EIP2930 or Legacy transactions")]
     UnsupportedTxType,
 }
 
@@ -84,6 +161,12 @@ pub(crate) struct GasEscalatorMiddlewareInternal<M> {
 /// A Gas escalator allows bumping transactions' gas price to avoid getting them
 /// stuck in the memory pool.
 ///
+/// Legacy and EIP-2930 transactions are escalated by bumping their gas price,
+/// EIP-1559 transactions by bumping both their max fee and max priority fee,
+/// see [`GasEscalator::get_eip1559_fees`]. A replacement is only sent if the
+/// fees are raised by at least [`REPLACEMENT_BUMP_PERCENTAGE`], as nodes reject
+/// it otherwise.
+///
 /// GasEscalator runs a background task which monitors the blockchain for tx
 /// confirmation, and bumps fees over time if txns do not occur. This task
 /// periodically loops over a stored history of sent transactions, and checks
@@ -173,7 +256,18 @@ where
         tx: T,
         block: Option<BlockId>,
     ) -> Result<PendingTransaction<'_, M::Provider>, GasEscalatorError<M>> {
-        let tx = tx.into();
+        let mut tx = tx.into();
+        if !matches!(
+            tx,
+            TypedTransaction::Legacy(_) |
+                TypedTransaction::Eip2930(_) |
+                TypedTransaction::Eip1559(_)
+        ) {
+            return Err(GasEscalatorError::UnsupportedTxType)
+        }
+
+        // the fees and nonce must be known to replace the transaction
+        self.inner.fill_transaction(&mut tx, block).await.map_err(MiddlewareError::from_err)?;
 
         let pending_tx = self
             .inner
@@ -181,12 +275,6 @@ where
             .await
             .map_err(MiddlewareError::from_err)?;
 
-        let tx = match tx {
-            TypedTransaction::Legacy(inner) => inner,
-            TypedTransaction::Eip2930(inner) => inner.tx,
-            _ => return Err(GasEscalatorError::UnsupportedTxType),
-        };
-
         // insert the tx in the pending txs
         let mut lock = self.txs.lock().await;
         lock.push((*pending_tx, tx, Instant::now(), block));
@@ -287,6 +375,23 @@ impl<M, E> EscalationTask<M, E> {
                     // Lock scope ends
                 };
 
+                // the base fee is only needed to escalate EIP-1559 transactions
+                let base_fee = if txs.iter().any(|(_, tx, _, _)| tx.as_eip1559_ref().is_some()) {
+                    match self.inner.get_block(BlockNumber::Latest).await {
+                        Ok(block) => {
+                            block.and_then(|block| block.base_fee_per_gas).unwrap_or_default()
+                        }
+                        Err(err) => {
+                            // put the transactions back and retry on the next tick
+                            tracing::error!(err = %err, "failed to fetch the base fee, skipping");
+                            self.txs.lock().await.extend(txs);
+                            continue
+                        }
+                    }
+                } else {
+                    U256::zero()
+                };
+
                 let len = txs.len();
                 // Pop all transactions and re-insert those that have not been included yet
                 for _ in 0..len {
@@ -294,28 +399,32 @@ impl<M, E> EscalationTask<M, E> {
                     let (tx_hash, mut replacement_tx, time, priority) =
                         txs.pop().expect("should have element in vector");
 
-                    let receipt = self
-                        .inner
-                        .get_transaction_receipt(tx_hash)
-                        .await
-                        .map_err(MiddlewareError::from_err)?;
+                    let receipt = match self.inner.get_transaction_receipt(tx_hash).await {
+                        Ok(receipt) => receipt,
+                        Err(err) => {
+                            // keep the transaction and check it again on the next tick
+                            tracing::error!(tx_hash = ?tx_hash, err = %err, "failed to fetch receipt");
+                            txs.insert(0, (tx_hash, replacement_tx, time, priority));
+                            continue
+                        }
+                    };
 
                     tracing::trace!(tx_hash = ?tx_hash, "checking if exists");
 
                     if receipt.is_none() {
-                        let old_gas_price = replacement_tx.gas_price.expect("gas price must be set");
-                        // Get the new gas price based on how much time passed since the
+                        let old_tx = replacement_tx.clone();
+                        // Get the new fees based on how much time passed since the
                         // tx was last broadcast
-                        let new_gas_price = self
-                            .escalator
-                            .get_gas_price(old_gas_price, now.duration_since(time).as_secs());
-
-                        let new_txhash = if new_gas_price == old_gas_price {
+                        let escalated = escalate_fees(
+                            &self.escalator,
+                            &mut replacement_tx,
+                            now.duration_since(time).as_secs(),
+                            base_fee,
+                        );
+
+                        let new_txhash = if !escalated {
                              tx_hash
                         } else {
-                            // bump the gas price
-                            replacement_tx.gas_price = Some(new_gas_price);
-
                             // the tx hash will be different so we need to update it
                             match self.inner.send_transaction(replacement_tx.clone(), priority).await {
                                 Ok(new_tx_hash) => {
@@ -323,14 +432,14 @@ impl<M, E> EscalationTask<M, E> {
                                     tracing::trace!(
                                         old_tx_hash = ?tx_hash,
                                         new_tx_hash = ?new_tx_hash,
-                                        old_gas_price = ?old_gas_price,
-                                        new_gas_price = ?new_gas_price,
+                                        old_tx = ?old_tx,
+                                        new_tx = ?replacement_tx,
                                         "escalated"
                                     );
                                     new_tx_hash
                                 }
                                 Err(err) => {
-                                    if err.to_string().contains("nonce too low") {
+                                    if err.error_kind() == Some(RpcErrorKind::NonceTooLow) {
                                         // ignore "nonce too low" errors because they
                                         // may happen if we try to broadcast a higher
                                         // gas price tx when one of the previous ones
@@ -358,3 +467,70 @@ impl<M, E> EscalationTask<M, E> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn escalates_eip1559_fees() {
+        let escalator = GeometricGasPrice::new(1.125, 10u64, None::<u64>);
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(200)
+            .max_priority_fee_per_gas(100)
+            .into();
+
+        assert!(!escalate_fees(&escalator, &mut tx, 0, U256::zero()));
+        assert!(escalate_fees(&escalator, &mut tx, 10, U256::zero()));
+        let tx = tx.as_eip1559_ref().unwrap();
+        assert_eq!(tx.max_fee_per_gas, Some(225.into()));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(113.into()));
+    }
+
+    #[test]
+    fn respects_minimum_replacement_bump() {
+        // a 5% bump is rejected by nodes
+        let escalator = GeometricGasPrice::new(1.05, 10u64, None::<u64>);
+        let mut tx: TypedTransaction = TransactionRequest::new().gas_price(1000).into();
+        assert!(!escalate_fees(&escalator, &mut tx, 10, U256::zero()));
+        assert_eq!(tx.gas_price(), Some(1000.into()));
+        assert!(escalate_fees(&escalator, &mut tx, 20, U256::zero()));
+        assert_eq!(tx.gas_price(), Some(1103.into()));
+
+        // the max fee is capped, so the tip can't be replaced
+        let escalator = BaseFeeGasPrice::new(
+            GeometricGasPrice::new(1.125, 10u64, None::<u64>),
+            2,
+            Some(205u64),
+        );
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(200)
+            .max_priority_fee_per_gas(100)
+            .into();
+        assert!(!escalate_fees(&escalator, &mut tx, 10, 50.into()));
+    }
+
+    #[tokio::test]
+    async fn keeps_transactions_if_the_base_fee_is_unavailable() {
+        // the mock has no responses, so fetching the latest block fails
+        let (provider, _mock) = ethers_providers::Provider::mocked();
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(200)
+            .max_priority_fee_per_gas(100)
+            .into();
+        let txs: ToEscalate = Default::default();
+        txs.lock().await.push((TxHash::zero(), tx, Instant::now(), None));
+
+        let (shutdown, rx) = oneshot::channel();
+        let escalator = GeometricGasPrice::new(1.125, 10u64, None::<u64>);
+        let task =
+            EscalationTask::new(provider, escalator, Frequency::Duration(1), txs.clone(), rx);
+        let handle = spawn(task.escalate());
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        shutdown.send(()).unwrap();
+        handle.await.unwrap().unwrap();
+        assert_eq!(txs.lock().await.len(), 1);
+    }
+}
@@ -0,0 +1,265 @@
+use super::{GasCategory, GasOracle, GasOracleError, Result};
+use async_trait::async_trait;
+use ethers_core::types::{BlockNumber, Chain, FeeHistory, U256};
+use ethers_providers::Middleware;
+
+/// The default number of blocks the priority fees are sampled from
+pub const DEFAULT_FEE_HISTORY_BLOCKS: u64 = 20;
+
+/// The precision of the gas used ratios when predicting the base fee
+const RATIO_PRECISION: f64 = 1_000_000.0;
+
+/// The parameters of the EIP-1559 base fee adjustment of a chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BaseFeeParams {
+    /// The ratio of the block gas limit to the gas target
+    pub elasticity_multiplier: u64,
+    /// Bounds the change of the base fee from one block to the next to
+    /// `1 / max_change_denominator`
+    pub max_change_denominator: u64,
+}
+
+impl BaseFeeParams {
+    /// The parameters of Ethereum
+    pub const ETHEREUM: Self = Self { elasticity_multiplier: 2, max_change_denominator: 8 };
+
+    /// The parameters of OP-stack chains before the Canyon upgrade
+    pub const OPTIMISM: Self = Self { elasticity_multiplier: 6, max_change_denominator: 50 };
+
+    /// The parameters of OP-stack chains since the Canyon upgrade
+    pub const OPTIMISM_CANYON: Self =
+        Self { elasticity_multiplier: 6, max_change_denominator: 250 };
+
+    /// Arbitrum prices gas by its throughput over time instead of by the usage of single blocks,
+    /// so the base fee of the next block is predicted to stay the same
+    pub const ARBITRUM: Self = Self { elasticity_multiplier: 1, max_change_denominator: u64::MAX };
+
+    /// Returns the parameters of `chain`, defaulting to those of Ethereum
+    pub fn for_chain(chain: Chain) -> Self {
+        match chain {
+            Chain::Optimism |
+            Chain::OptimismGoerli |
+            Chain::OptimismSepolia |
+            Chain::Base |
+            Chain::BaseGoerli |
+            Chain::BaseSepolia => Self::OPTIMISM_CANYON,
+            Chain::Arbitrum |
+            Chain::ArbitrumGoerli |
+            Chain::ArbitrumSepolia |
+            Chain::ArbitrumNova |
+            Chain::ArbitrumTestnet => Self::ARBITRUM,
+            _ => Self::ETHEREUM,
+        }
+    }
+
+    /// Predicts the base fee of the next block from the base fee and the ratio of gas used to the
+    /// gas limit of its parent
+    pub fn next_base_fee(&self, base_fee: U256, gas_used_ratio: f64) -> U256 {
+        let elasticity = self.elasticity_multiplier.max(1) as f64;
+        let denominator = U256::from(self.max_change_denominator.max(1));
+        // the gas used relative to the gas target, minus 1
+        let delta = gas_used_ratio * elasticity - 1.0;
+        let change = U256::from((delta.abs() * RATIO_PRECISION).round() as u64);
+        let change = base_fee * change / U256::from(RATIO_PRECISION as u64) / denominator;
+
+        if delta > 0.0 {
+            base_fee + change.max(U256::one())
+        } else {
+            base_fee.saturating_sub(change)
+        }
+    }
+}
+
+impl Default for BaseFeeParams {
+    fn default() -> Self {
+        Self::ETHEREUM
+    }
+}
+
+/// Gas oracle estimating fees from the `eth_feeHistory` of a [`Middleware`], without relying on
+/// third-party APIs.
+///
+/// The priority fee is the median, over the last `block_count` blocks, of the priority fees paid
+/// at the percentile of the [`GasCategory`]:
+/// - `SafeLow`: 10th percentile
+/// - `Standard`: 25th percentile
+/// - `Fast`: 50th percentile
+/// - `Fastest`: 75th percentile
+///
+/// The base fee of the next block is predicted from the latest block per the EIP-1559 formula,
+/// using the [`BaseFeeParams`] of the chain. The max fee allows for twice the predicted base fee.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Chain;
+/// use ethers_middleware::gas_oracle::{FeeHistoryOracle, GasCategory, GasOracle};
+/// use ethers_providers::{Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let oracle = FeeHistoryOracle::new(provider).chain(Chain::Optimism).category(GasCategory::Fast);
+/// let (max_fee, priority_fee) = oracle.estimate_eip1559_fees().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct FeeHistoryOracle<M: Middleware> {
+    provider: M,
+    gas_category: GasCategory,
+    block_count: u64,
+    params: BaseFeeParams,
+}
+
+impl<M: Middleware> FeeHistoryOracle<M> {
+    /// Creates a new oracle for an Ethereum-like chain
+    pub fn new(provider: M) -> Self {
+        Self {
+            provider,
+            gas_category: GasCategory::Standard,
+            block_count: DEFAULT_FEE_HISTORY_BLOCKS,
+            params: BaseFeeParams::ETHEREUM,
+        }
+    }
+
+    /// Sets the gas price category to be used when fetching the gas price.
+    pub fn category(mut self, gas_category: GasCategory) -> Self {
+        self.gas_category = gas_category;
+        self
+    }
+
+    /// Sets the number of blocks the priority fees are sampled from.
+    pub fn block_count(mut self, block_count: u64) -> Self {
+        self.block_count = block_count.max(1);
+        self
+    }
+
+    /// Uses the base fee parameters of `chain`, see [`BaseFeeParams::for_chain`].
+    pub fn chain(self, chain: Chain) -> Self {
+        self.base_fee_params(BaseFeeParams::for_chain(chain))
+    }
+
+    /// Sets the parameters used to predict the base fee of the next block.
+    pub fn base_fee_params(mut self, params: BaseFeeParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Returns the predicted base fee of the next block and the priority fee
+    async fn estimate(&self) -> Result<(U256, U256)>
+    where
+        M::Error: 'static,
+    {
+        let percentile = match self.gas_category {
+            GasCategory::SafeLow => 10.0,
+            GasCategory::Standard => 25.0,
+            GasCategory::Fast => 50.0,
+            GasCategory::Fastest => 75.0,
+        };
+        let history = self
+            .provider
+            .fee_history(self.block_count, BlockNumber::Latest, &[percentile])
+            .await
+            .map_err(|err| GasOracleError::ProviderError(Box::new(err)))?;
+
+        Ok((self.next_base_fee(&history)?, priority_fee(&history)))
+    }
+
+    fn next_base_fee(&self, history: &FeeHistory) -> Result<U256> {
+        // the base fees include the one of the block after the latest, so the base fee of the
+        // latest block is the second to last
+        let (base_fee, gas_used_ratio) = match history.gas_used_ratio.last() {
+            Some(ratio) => {
+                let base_fee = history
+                    .base_fee_per_gas
+                    .get(history.gas_used_ratio.len() - 1)
+                    .ok_or(GasOracleError::InvalidResponse)?;
+                (*base_fee, *ratio)
+            }
+            None => return Err(GasOracleError::InvalidResponse),
+        };
+        Ok(self.params.next_base_fee(base_fee, gas_used_ratio))
+    }
+}
+
+/// Returns the median of the sampled priority fees, ignoring empty blocks
+fn priority_fee(history: &FeeHistory) -> U256 {
+    let mut rewards = history
+        .reward
+        .iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .filter(|reward| !reward.is_zero())
+        .collect::<Vec<_>>();
+    if rewards.is_empty() {
+        return U256::zero()
+    }
+    rewards.sort_unstable();
+    rewards[rewards.len() / 2]
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> GasOracle for FeeHistoryOracle<M>
+where
+    M::Error: 'static,
+{
+    async fn fetch(&self) -> Result<U256> {
+        let (base_fee, priority_fee) = self.estimate().await?;
+        Ok(base_fee + priority_fee)
+    }
+
+    async fn estimate_eip1559_fees(&self) -> Result<(U256, U256)> {
+        let (base_fee, priority_fee) = self.estimate().await?;
+        Ok((base_fee * 2 + priority_fee, priority_fee))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::Provider;
+
+    #[test]
+    fn predicts_base_fee() {
+        let params = BaseFeeParams::ETHEREUM;
+        let base_fee = U256::from(1_000_000_000u64);
+        assert_eq!(params.next_base_fee(base_fee, 0.5), base_fee);
+        assert_eq!(params.next_base_fee(base_fee, 1.0), 1_125_000_000u64.into());
+        assert_eq!(params.next_base_fee(base_fee, 0.0), 875_000_000u64.into());
+        assert_eq!(params.next_base_fee(base_fee, 0.75), 1_062_500_000u64.into());
+
+        // OP-stack chains target a sixth of the gas limit
+        let params = BaseFeeParams::OPTIMISM_CANYON;
+        assert_eq!(params.next_base_fee(base_fee, 1.0 / 6.0), base_fee);
+        assert_eq!(params.next_base_fee(base_fee, 1.0), 1_020_000_000u64.into());
+
+        assert_eq!(BaseFeeParams::ARBITRUM.next_base_fee(base_fee, 0.0), base_fee);
+    }
+
+    #[tokio::test]
+    async fn estimates_fees_from_history() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(FeeHistory {
+            base_fee_per_gas: vec![100.into(), 110.into(), 120.into(), 135.into()],
+            gas_used_ratio: vec![0.9, 0.9, 1.0],
+            oldest_block: 10.into(),
+            reward: vec![vec![5.into()], vec![0.into()], vec![3.into()]],
+        })
+        .unwrap();
+
+        let oracle = FeeHistoryOracle::new(provider).block_count(3);
+        let (max_fee, priority_fee) = oracle.estimate_eip1559_fees().await.unwrap();
+        // the empty block is ignored
+        assert_eq!(priority_fee, 5.into());
+        // 120 * 1.125
+        assert_eq!(max_fee, U256::from(135 * 2 + 5));
+
+        mock.assert_request(
+            "eth_feeHistory",
+            [serde_json::json!("0x3"), "latest".into(), serde_json::json!([25.0])],
+        )
+        .unwrap();
+    }
+}
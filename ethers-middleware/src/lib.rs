@@ -33,14 +33,45 @@ pub use signer::SignerMiddleware;
 /// [`PolicyMiddleware`] before sending them.
 pub mod policy;
 pub use policy::{
-    AllowEverything, Policy, PolicyMiddleware, PolicyMiddlewareError, RejectEverything,
+    AllowEverything, Policy, PolicyError, PolicyMiddleware, PolicyMiddlewareError, RejectEverything,
 };
 
+/// The [SimulationMiddleware] simulates transactions before sending them, refusing those which
+/// would revert.
+pub mod simulation;
+pub use simulation::SimulationMiddleware;
+
+/// The [UserOperationMiddleware] sends transactions as ERC-4337 user operations of a smart
+/// account.
+pub mod user_operation;
+pub use user_operation::UserOperationMiddleware;
+
+/// The [MulticallMiddleware] folds concurrent calls into Multicall3 aggregates.
+pub mod multicall;
+pub use multicall::MulticallMiddleware;
+
+/// The [FlashbotsMiddleware] sends transactions and bundles privately to a MEV relay.
+pub mod flashbots;
+pub use flashbots::FlashbotsMiddleware;
+
 /// The [TimeLag] middleware provides safety against reorgs by querying state N blocks before the
 /// chain tip.
 pub mod timelag;
 pub use timelag::TimeLag;
 
+/// The [CacheMiddleware] memoizes the results of RPC requests which can no longer change, such as
+/// blocks by hash or receipts of final transactions.
+pub mod cache;
+pub use cache::CacheMiddleware;
+
+/// The [TxManager] tracks sent transactions until they are final, persisting them so that the
+/// tracking survives a restart.
+pub mod tx_manager;
+pub use tx_manager::TxManager;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file_store;
+
 /// [MiddlewareBuilder] provides a way to compose many [`Middleware`]s in a concise way.
 pub mod builder;
 pub use builder::MiddlewareBuilder;
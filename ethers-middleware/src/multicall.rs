@@ -0,0 +1,414 @@
+use async_trait::async_trait;
+use ethers_contract::{
+    multicall_contract::{Aggregate3Call, Aggregate3Return, Call3},
+    MULTICALL_ADDRESS,
+};
+use ethers_core::{
+    abi::{AbiDecode, AbiEncode},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, NameOrAddress,
+        TransactionRequest,
+    },
+};
+use ethers_providers::{
+    interval, JsonRpcError, Middleware, MiddlewareError, RpcErrorKind, StreamExt,
+};
+use futures_channel::oneshot;
+use futures_util::future::{self, join_all, Either};
+use instant::Duration;
+use std::{mem, sync::Mutex};
+use thiserror::Error;
+use tracing::trace;
+
+/// The default time calls are collected for before they are aggregated
+pub const DEFAULT_AGGREGATION_WINDOW: Duration = Duration::from_millis(5);
+
+/// The default maximum number of calls in an aggregate
+pub const DEFAULT_MAX_AGGREGATE_SIZE: usize = 100;
+
+/// The result of an aggregated call: its return data, its revert data, or `None` if the call
+/// was not aggregated and has to be sent on its own
+type CallResult = Option<Result<Bytes, Bytes>>;
+
+/// Middleware folding concurrent `eth_call`s into Multicall3 `aggregate3` calls.
+///
+/// The calls made within a short window are collected and every group of calls targeting the
+/// same block is sent as a single `aggregate3` call, whose results are unpacked back to their
+/// callers. An aggregate is sent once the window after its first call has passed, or as soon as
+/// it holds `max_aggregate_size` calls. Existing [`ContractCall::call`] users get the batching
+/// without any changes.
+///
+/// Aggregated calls are executed by the Multicall3 contract, which becomes their `msg.sender`.
+/// Calls with a `from`, a `value` or a `gas` limit are therefore sent as they are, as well as
+/// calls whose aggregate failed as a whole. Reverted calls fail with an error response holding
+/// their revert data, just as if they were sent on their own.
+///
+/// A [`SignerMiddleware`] sets the `from` of every call to the signer's address, so this
+/// middleware has to be placed above it, where the aggregates are sent from the signer's address.
+/// Below it, no call would be aggregated.
+///
+/// [`ContractCall::call`]: ethers_contract::ContractCall::call
+/// [`SignerMiddleware`]: crate::SignerMiddleware
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::MulticallMiddleware;
+/// use ethers_providers::{Http, Provider};
+/// use std::{convert::TryFrom, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = MulticallMiddleware::new(provider).window(Duration::from_millis(10));
+/// // with a signer: MulticallMiddleware::new(SignerMiddleware::new(provider, wallet))
+///
+/// // contract calls made concurrently through `client` share a single `eth_call`
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MulticallMiddleware<M> {
+    inner: M,
+    address: Address,
+    queue: Mutex<Queue>,
+    window: Duration,
+    max_aggregate_size: usize,
+}
+
+#[derive(Debug)]
+struct PendingCall {
+    target: Address,
+    data: Bytes,
+    block: Option<BlockId>,
+    sender: oneshot::Sender<CallResult>,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    /// Incremented whenever the pending calls are taken to be sent
+    generation: u64,
+    pending: Vec<PendingCall>,
+}
+
+impl Queue {
+    fn take(&mut self) -> Vec<PendingCall> {
+        self.generation += 1;
+        mem::take(&mut self.pending)
+    }
+}
+
+impl<M: Middleware> MulticallMiddleware<M> {
+    /// Creates a new middleware aggregating the calls with the Multicall3 contract at
+    /// [`MULTICALL_ADDRESS`].
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            address: MULTICALL_ADDRESS,
+            queue: Default::default(),
+            window: DEFAULT_AGGREGATION_WINDOW,
+            max_aggregate_size: DEFAULT_MAX_AGGREGATE_SIZE,
+        }
+    }
+
+    /// Sets the address of the Multicall3 contract, for chains where it is not deployed at
+    /// [`MULTICALL_ADDRESS`].
+    #[must_use]
+    pub fn address(mut self, address: Address) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Sets how long calls are collected for before they are aggregated.
+    #[must_use]
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the maximum number of calls in an aggregate.
+    #[must_use]
+    pub fn max_aggregate_size(mut self, max_aggregate_size: usize) -> Self {
+        self.max_aggregate_size = max_aggregate_size.max(1);
+        self
+    }
+
+    /// Returns the target and calldata of `tx` if it can be aggregated.
+    fn aggregatable(tx: &TypedTransaction) -> Option<(Address, Bytes)> {
+        if tx.from().is_some() || tx.gas().is_some() || tx.value().map_or(false, |v| !v.is_zero()) {
+            return None
+        }
+        match tx.to() {
+            Some(NameOrAddress::Address(to)) => Some((*to, tx.data().cloned().unwrap_or_default())),
+            _ => None,
+        }
+    }
+
+    /// Queues the call and waits until its aggregate was sent.
+    async fn aggregate(&self, target: Address, data: Bytes, block: Option<BlockId>) -> CallResult {
+        let (sender, mut receiver) = oneshot::channel();
+        let (generation, full) = {
+            let mut queue = self.queue.lock().unwrap();
+            queue.pending.push(PendingCall { target, data, block, sender });
+            let generation = queue.generation;
+            let full = (queue.pending.len() >= self.max_aggregate_size).then(|| queue.take());
+            (generation, full)
+        };
+
+        if let Some(calls) = full {
+            self.flush(calls).await;
+        } else {
+            // every call waits for the window, so that the aggregate is sent even if the call
+            // that opened it is dropped
+            let mut window = interval(self.window);
+            receiver = match future::select(receiver, window.next()).await {
+                Either::Left((res, _)) => return res.ok().flatten(),
+                Either::Right((_, receiver)) => receiver,
+            };
+            let calls = {
+                let mut queue = self.queue.lock().unwrap();
+                (queue.generation == generation).then(|| queue.take())
+            };
+            if let Some(calls) = calls {
+                self.flush(calls).await;
+            }
+        }
+
+        receiver.await.ok().flatten()
+    }
+
+    /// Sends an aggregate for every block targeted by the `calls`.
+    async fn flush(&self, mut calls: Vec<PendingCall>) {
+        let mut groups: Vec<Vec<PendingCall>> = Vec::new();
+        while let Some(block) = calls.first().map(|call| call.block) {
+            let (group, rest) = calls.into_iter().partition(|call| call.block == block);
+            groups.push(group);
+            calls = rest;
+        }
+        join_all(groups.into_iter().map(|group| self.send_aggregate(group))).await;
+    }
+
+    /// Sends the `calls`, which target the same block, in a single `aggregate3` call.
+    async fn send_aggregate(&self, calls: Vec<PendingCall>) {
+        if calls.len() < 2 {
+            // no need to aggregate a single call, the caller sends it on its own
+            return
+        }
+        trace!(calls = calls.len(), "sending aggregate");
+
+        let block = calls[0].block;
+        let (calls, senders): (Vec<_>, Vec<_>) = calls
+            .into_iter()
+            .map(|call| {
+                let call3 =
+                    Call3 { target: call.target, allow_failure: true, call_data: call.data };
+                (call3, call.sender)
+            })
+            .unzip();
+        let tx: TypedTransaction = TransactionRequest::new()
+            .to(self.address)
+            .data(Aggregate3Call { calls }.encode())
+            .into();
+
+        let results = match self.inner.call(&tx, block).await {
+            Ok(data) => Aggregate3Return::decode(data).map(|ret| ret.return_data).ok(),
+            Err(err) => {
+                trace!(err = %err, "aggregate failed");
+                None
+            }
+        };
+
+        match results {
+            Some(results) if results.len() == senders.len() => {
+                for (sender, result) in senders.into_iter().zip(results) {
+                    let result = if result.success {
+                        Ok(result.return_data)
+                    } else {
+                        Err(result.return_data)
+                    };
+                    let _ = sender.send(Some(result));
+                }
+            }
+            // the callers send their calls on their own
+            _ => drop(senders),
+        }
+    }
+}
+
+/// Error thrown when the client makes calls
+#[derive(Error, Debug)]
+pub enum MulticallMiddlewareError<M: Middleware> {
+    /// Thrown when an internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+
+    /// Thrown when an aggregated call reverted, with an error response holding the revert data
+    #[error(transparent)]
+    Reverted(JsonRpcError),
+}
+
+impl<M: Middleware> MiddlewareError for MulticallMiddlewareError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        MulticallMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            MulticallMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            MulticallMiddlewareError::MiddlewareError(e) => e.as_error_response(),
+            MulticallMiddlewareError::Reverted(e) => Some(e),
+        }
+    }
+
+    fn error_kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            MulticallMiddlewareError::MiddlewareError(e) => e.error_kind(),
+            MulticallMiddlewareError::Reverted(_) => Some(RpcErrorKind::ExecutionReverted),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for MulticallMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = MulticallMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Aggregates the call with the concurrent calls targeting the same block.
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        if let Some((target, data)) = Self::aggregatable(tx) {
+            match self.aggregate(target, data, block).await {
+                Some(Ok(data)) => return Ok(data),
+                Some(Err(data)) => {
+                    return Err(MulticallMiddlewareError::Reverted(JsonRpcError {
+                        code: 3,
+                        message: "execution reverted".to_string(),
+                        data: Some(serde_json::to_value(data).expect("bytes serialize")),
+                    }))
+                }
+                None => {}
+            }
+        }
+        self.inner.call(tx, block).await.map_err(MulticallMiddlewareError::MiddlewareError)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::SignerMiddleware;
+    use ethers_contract::multicall_contract::Result as MulticallResult;
+    use ethers_core::types::{BlockNumber, H160};
+    use ethers_providers::Provider;
+    use ethers_signers::{LocalWallet, Signer};
+
+    #[tokio::test]
+    async fn aggregates_concurrent_calls() {
+        let (provider, mock) = Provider::mocked();
+        let client = MulticallMiddleware::new(provider);
+
+        let call = |to: u8, data: u8| -> TypedTransaction {
+            TransactionRequest::new().to(H160([to; 20])).data(vec![data]).into()
+        };
+        let (a, b, c) = (call(1, 0xa), call(2, 0xb), call(3, 0xc));
+        let latest = Some(BlockNumber::Latest.into());
+
+        let results = Aggregate3Return {
+            return_data: vec![
+                MulticallResult { success: true, return_data: vec![1].into() },
+                MulticallResult { success: false, return_data: vec![2].into() },
+            ],
+        };
+        // `c` targets another block and is sent on its own
+        mock.push::<Bytes, Bytes>(vec![3].into()).unwrap();
+        mock.push::<Bytes, Bytes>(results.encode().into()).unwrap();
+
+        let (a, b, c) = futures_util::join!(
+            client.call(&a, latest),
+            client.call(&b, latest),
+            client.call(&c, Some(BlockNumber::Number(1.into()).into())),
+        );
+        assert_eq!(a.unwrap(), Bytes::from(vec![1]));
+        let err = b.unwrap_err();
+        assert_eq!(err.as_error_response().unwrap().as_revert_data(), Some(vec![2].into()));
+        assert_eq!(c.unwrap(), Bytes::from(vec![3]));
+
+        let aggregate: TypedTransaction = TransactionRequest::new()
+            .to(MULTICALL_ADDRESS)
+            .data(
+                Aggregate3Call {
+                    calls: vec![
+                        Call3 {
+                            target: H160([1; 20]),
+                            allow_failure: true,
+                            call_data: vec![0xa].into(),
+                        },
+                        Call3 {
+                            target: H160([2; 20]),
+                            allow_failure: true,
+                            call_data: vec![0xb].into(),
+                        },
+                    ],
+                }
+                .encode(),
+            )
+            .into();
+        mock.assert_request("eth_call", (aggregate, "latest")).unwrap();
+    }
+
+    #[tokio::test]
+    async fn aggregates_calls_above_signer() {
+        let (provider, mock) = Provider::mocked();
+        let wallet: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let from = wallet.address();
+        let client = MulticallMiddleware::new(SignerMiddleware::new(provider, wallet));
+
+        let call = |to: u8| -> TypedTransaction {
+            TransactionRequest::new().to(H160([to; 20])).data(vec![to]).into()
+        };
+        let results = Aggregate3Return {
+            return_data: vec![
+                MulticallResult { success: true, return_data: vec![1].into() },
+                MulticallResult { success: true, return_data: vec![2].into() },
+            ],
+        };
+        mock.push::<Bytes, Bytes>(results.encode().into()).unwrap();
+
+        let (a, b) = (call(1), call(2));
+        let (a, b) = futures_util::join!(client.call(&a, None), client.call(&b, None));
+        assert_eq!(a.unwrap(), Bytes::from(vec![1]));
+        assert_eq!(b.unwrap(), Bytes::from(vec![2]));
+
+        let calls = [1, 2].map(|to| Call3 {
+            target: H160([to; 20]),
+            allow_failure: true,
+            call_data: vec![to].into(),
+        });
+        let aggregate: TypedTransaction = TransactionRequest::new()
+            .from(from)
+            .to(MULTICALL_ADDRESS)
+            .data(Aggregate3Call { calls: calls.to_vec() }.encode())
+            .into();
+        mock.assert_request("eth_call", (aggregate, "latest")).unwrap();
+    }
+}
@@ -1,20 +1,91 @@
 use async_trait::async_trait;
 use ethers_core::types::{transaction::eip2718::TypedTransaction, *};
-use ethers_providers::{Middleware, MiddlewareError, PendingTransaction};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use ethers_providers::{Middleware, MiddlewareError, PendingTransaction, RpcErrorKind};
+use futures_util::try_join;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Mutex,
+};
 use thiserror::Error;
 
 #[derive(Debug)]
 /// Middleware used for calculating nonces locally, useful for signing multiple
 /// consecutive transactions without waiting for them to hit the mempool
+///
+/// Nonces are tracked separately for every sender. Transactions without a
+/// `from` address use the nonces of the `address` the manager was created
+/// with.
+///
+/// If sending a transaction fails, the manager recovers without leaving a gap:
+/// - if the node rejected the transaction, its nonce is handed out again
+/// - if the node reports that the nonce was already used or is too high, the nonces are resynced
+///   from the `pending` block and the transaction is sent once more with a fresh nonce. If that
+///   nonce conflicts as well, the nonces are resynced again and the error is returned
+/// - if it is unknown whether the transaction was broadcast, e.g. on a connection error, the nonces
+///   are resynced from the `pending` block
 pub struct NonceManagerMiddleware<M> {
     inner: M,
     init_guard: futures_locks::Mutex<()>,
-    initialized: AtomicBool,
-    nonce: AtomicU64,
+    accounts: Mutex<HashMap<Address, AccountNonces>>,
     address: Address,
 }
 
+/// The nonces of a single sender
+#[derive(Debug, Default)]
+struct AccountNonces {
+    /// Whether the nonces were initialized from the node
+    initialized: bool,
+    /// The lowest nonce that was never handed out
+    next: u64,
+    /// Nonces that were handed out and are not yet known to be mined
+    in_flight: BTreeSet<u64>,
+    /// Nonces of failed transactions, which are handed out again before `next`
+    released: BTreeSet<u64>,
+}
+
+impl AccountNonces {
+    fn take(&mut self) -> u64 {
+        let nonce = match self.released.iter().next().copied() {
+            Some(nonce) => {
+                self.released.remove(&nonce);
+                nonce
+            }
+            None => {
+                self.next += 1;
+                self.next - 1
+            }
+        };
+        self.in_flight.insert(nonce);
+        nonce
+    }
+
+    fn release(&mut self, nonce: u64) {
+        // the nonce may have been dropped by a resync in the meantime
+        if !self.in_flight.remove(&nonce) {
+            return
+        }
+        self.released.insert(nonce);
+        // roll back instead of keeping released nonces at the end
+        while self.next > 0 && self.released.remove(&(self.next - 1)) {
+            self.next -= 1;
+        }
+    }
+
+    /// Forgets a handed out nonce which was used by another transaction
+    fn forget(&mut self, nonce: u64) {
+        self.in_flight.remove(&nonce);
+    }
+
+    fn sync(&mut self, mined: u64, pending: u64) {
+        // nonces above `pending` may be held by transactions which did not reach the node yet
+        self.in_flight.retain(|nonce| *nonce >= mined);
+        self.next =
+            self.in_flight.iter().next_back().map_or(pending, |nonce| pending.max(nonce + 1));
+        self.released.clear();
+        self.initialized = true;
+    }
+}
+
 impl<M> NonceManagerMiddleware<M>
 where
     M: Middleware,
@@ -22,65 +93,123 @@ where
     /// Instantiates the nonce manager with a 0 nonce. The `address` should be the
     /// address which you'll be sending transactions from
     pub fn new(inner: M, address: Address) -> Self {
-        Self {
-            inner,
-            init_guard: Default::default(),
-            initialized: Default::default(),
-            nonce: Default::default(),
-            address,
-        }
+        Self { inner, init_guard: Default::default(), accounts: Default::default(), address }
     }
 
     /// Returns the next nonce to be used
     pub fn next(&self) -> U256 {
-        let nonce = self.nonce.fetch_add(1, Ordering::SeqCst);
-        nonce.into()
+        self.accounts.lock().unwrap().entry(self.address).or_default().take().into()
+    }
+
+    /// Returns the nonces of `from` which were handed out and are not yet
+    /// known to be mined, in ascending order.
+    ///
+    /// Mined nonces are only removed when the nonces are resynced, see
+    /// [`Self::resync`].
+    pub fn in_flight(&self, from: Address) -> Vec<U256> {
+        self.accounts
+            .lock()
+            .unwrap()
+            .get(&from)
+            .map(|account| account.in_flight.iter().map(|nonce| U256::from(*nonce)).collect())
+            .unwrap_or_default()
     }
 
     pub async fn initialize_nonce(
         &self,
         block: Option<BlockId>,
     ) -> Result<U256, NonceManagerError<M>> {
-        if self.initialized.load(Ordering::SeqCst) {
-            // return current nonce
-            return Ok(self.nonce.load(Ordering::SeqCst).into())
+        self.initialize(self.address, block).await
+    }
+
+    /// Initializes the nonces of `from` from its transaction count at `block`,
+    /// or the `pending` block if `None`. Returns the next nonce to be used.
+    async fn initialize(
+        &self,
+        from: Address,
+        block: Option<BlockId>,
+    ) -> Result<U256, NonceManagerError<M>> {
+        if let Some(account) = self.accounts.lock().unwrap().get(&from) {
+            if account.initialized {
+                // return current nonce
+                return Ok(account.next.into())
+            }
         }
 
         let _guard = self.init_guard.lock().await;
 
         // do this again in case multiple tasks enter this codepath
-        if self.initialized.load(Ordering::SeqCst) {
-            // return current nonce
-            return Ok(self.nonce.load(Ordering::SeqCst).into())
+        if let Some(account) = self.accounts.lock().unwrap().get(&from) {
+            if account.initialized {
+                // return current nonce
+                return Ok(account.next.into())
+            }
         }
 
         // initialize the nonce the first time the manager is called
         let nonce = self
             .inner
-            .get_transaction_count(self.address, block)
+            .get_transaction_count(from, block.or_else(|| Some(BlockNumber::Pending.into())))
             .await
             .map_err(MiddlewareError::from_err)?;
-        self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
-        self.initialized.store(true, Ordering::SeqCst);
+        self.accounts.lock().unwrap().insert(
+            from,
+            AccountNonces { initialized: true, next: nonce.as_u64(), ..Default::default() },
+        );
         Ok(nonce)
     } // guard dropped here
 
-    async fn get_transaction_count_with_manager(
+    /// Resyncs the nonces of `from` with the node, returning the next nonce to
+    /// be used.
+    ///
+    /// The next nonce is set to the transaction count at the `pending` block,
+    /// which fills the gaps left by dropped transactions, unless higher nonces
+    /// are still in flight. Nonces that were mined since are no longer
+    /// reported as in flight.
+    pub async fn resync(&self, from: Address) -> Result<U256, NonceManagerError<M>> {
+        let (mined, pending) = try_join!(
+            self.inner.get_transaction_count(from, Some(BlockNumber::Latest.into())),
+            self.inner.get_transaction_count(from, Some(BlockNumber::Pending.into())),
+        )
+        .map_err(MiddlewareError::from_err)?;
+        tracing::debug!(?from, ?mined, ?pending, "resynced nonces");
+
+        self.accounts
+            .lock()
+            .unwrap()
+            .entry(from)
+            .or_default()
+            .sync(mined.as_u64(), pending.as_u64());
+        Ok(pending)
+    }
+
+    /// Returns the next nonce of `from`, initializing its nonces first if
+    /// needed
+    async fn take(
         &self,
+        from: Address,
         block: Option<BlockId>,
     ) -> Result<U256, NonceManagerError<M>> {
-        // initialize the nonce the first time the manager is called
-        if !self.initialized.load(Ordering::SeqCst) {
-            let nonce = self
-                .inner
-                .get_transaction_count(self.address, block)
-                .await
-                .map_err(MiddlewareError::from_err)?;
-            self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
-            self.initialized.store(true, Ordering::SeqCst);
+        self.initialize(from, block).await?;
+        Ok(self.accounts.lock().unwrap().entry(from).or_default().take().into())
+    }
+
+    /// Hands out the nonce of a transaction that was not broadcast again
+    fn release(&self, from: Address, nonce: U256) {
+        if let Some(account) = self.accounts.lock().unwrap().get_mut(&from) {
+            account.release(nonce.as_u64());
         }
+    }
 
-        Ok(self.next())
+    /// Forgets the nonce of a transaction that conflicted with another transaction
+    fn forget(&self, from: Address, nonce: U256) {
+        if let Some(account) = self.accounts.lock().unwrap().get_mut(&from) {
+            account.forget(nonce.as_u64());
+        }
+    }
+
+    fn sender(&self, tx: &TypedTransaction) -> Address {
+        tx.from().copied().unwrap_or(self.address)
     }
 }
 
@@ -126,7 +255,14 @@ where
         block: Option<BlockId>,
     ) -> Result<(), Self::Error> {
         if tx.nonce().is_none() {
-            tx.set_nonce(self.get_transaction_count_with_manager(block).await?);
+            let from = self.sender(tx);
+            let nonce = self.take(from, block).await?;
+            tx.set_nonce(nonce);
+            if let Err(err) = self.inner().fill_transaction(tx, block).await {
+                self.release(from, nonce);
+                return Err(MiddlewareError::from_err(err))
+            }
+            return Ok(())
         }
 
         Ok(self.inner().fill_transaction(tx, block).await.map_err(MiddlewareError::from_err)?)
@@ -142,25 +278,187 @@ where
     ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
         let mut tx = tx.into();
 
-        if tx.nonce().is_none() {
-            tx.set_nonce(self.get_transaction_count_with_manager(block).await?);
+        if tx.nonce().is_some() {
+            // the nonce is managed by the caller
+            return self.inner.send_transaction(tx, block).await.map_err(MiddlewareError::from_err)
         }
 
-        match self.inner.send_transaction(tx.clone(), block).await {
-            Ok(tx_hash) => Ok(tx_hash),
-            Err(err) => {
-                let nonce = self.get_transaction_count(self.address, block).await?;
-                if nonce != self.nonce.load(Ordering::SeqCst).into() {
-                    // try re-submitting the transaction with the correct nonce if there
-                    // was a nonce mismatch
-                    self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
-                    tx.set_nonce(nonce);
-                    self.inner.send_transaction(tx, block).await.map_err(MiddlewareError::from_err)
-                } else {
-                    // propagate the error otherwise
-                    Err(MiddlewareError::from_err(err))
+        let from = self.sender(&tx);
+        let mut resynced = false;
+        loop {
+            let nonce = self.take(from, block).await?;
+            tx.set_nonce(nonce);
+
+            let err = match self.inner.send_transaction(tx.clone(), block).await {
+                Ok(tx_hash) => return Ok(tx_hash),
+                Err(err) => err,
+            };
+
+            match err.error_kind() {
+                Some(
+                    RpcErrorKind::NonceTooLow |
+                    RpcErrorKind::NonceTooHigh |
+                    RpcErrorKind::ReplacementUnderpriced,
+                ) => {
+                    // the nonce was used by another transaction or a previous transaction was
+                    // dropped, so it must not be handed out again
+                    self.forget(from, nonce);
+                    if resynced {
+                        let _ = self.resync(from).await;
+                        return Err(MiddlewareError::from_err(err))
+                    }
+                    // try re-submitting the transaction with the correct nonce
+                    self.resync(from).await?;
+                    resynced = true;
+                }
+                // the transaction was broadcast before
+                Some(RpcErrorKind::AlreadyKnown) => return Err(MiddlewareError::from_err(err)),
+                Some(_) => {
+                    // the node rejected the transaction, so the nonce is still unused
+                    self.release(from, nonce);
+                    return Err(MiddlewareError::from_err(err))
+                }
+                None => {
+                    // the transaction may or may not have been broadcast, ask the node
+                    let _ = self.resync(from).await;
+                    return Err(MiddlewareError::from_err(err))
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+
+    fn json_rpc_error(message: &str) -> MockResponse {
+        MockResponse::Error(JsonRpcError { code: -32000, message: message.to_string(), data: None })
+    }
+
+    fn tx(from: Address) -> TransactionRequest {
+        TransactionRequest::new().from(from).to(Address::zero()).gas(21000).gas_price(1)
+    }
+
+    fn nonce_manager() -> (NonceManagerMiddleware<Provider<MockProvider>>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        (NonceManagerMiddleware::new(provider, Address::zero()), mock)
+    }
+
+    fn assert_sent(mock: &MockProvider, tx: TransactionRequest, nonce: u64) {
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx.nonce(nonce))])
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn tracks_nonces_per_sender() {
+        let (manager, mock) = nonce_manager();
+        let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        // the mock pops from the back
+        mock.push(H256::repeat_byte(3)).unwrap();
+        mock.push(H256::repeat_byte(2)).unwrap();
+        mock.push(U256::from(7)).unwrap();
+        mock.push(H256::repeat_byte(1)).unwrap();
+        mock.push(U256::from(3)).unwrap();
+
+        manager.send_transaction(tx(alice), None).await.unwrap();
+        manager.send_transaction(tx(bob), None).await.unwrap();
+        manager.send_transaction(tx(alice), None).await.unwrap();
+
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 3);
+        mock.assert_request("eth_getTransactionCount", (bob, "pending")).unwrap();
+        assert_sent(&mock, tx(bob), 7);
+        assert_sent(&mock, tx(alice), 4);
+        assert_eq!(manager.in_flight(alice), vec![3.into(), 4.into()]);
+    }
+
+    #[tokio::test]
+    async fn reuses_nonce_of_rejected_transaction() {
+        let (manager, mock) = nonce_manager();
+        let alice = Address::repeat_byte(1);
+        mock.push(H256::repeat_byte(1)).unwrap();
+        mock.push_response(json_rpc_error("insufficient funds for gas * price + value"));
+        mock.push(U256::from(3)).unwrap();
+
+        manager.send_transaction(tx(alice), None).await.unwrap_err();
+        assert!(manager.in_flight(alice).is_empty());
+        manager.send_transaction(tx(alice), None).await.unwrap();
+
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 3);
+        assert_sent(&mock, tx(alice), 3);
+    }
+
+    #[tokio::test]
+    async fn resyncs_on_nonce_errors() {
+        let (manager, mock) = nonce_manager();
+        let alice = Address::repeat_byte(1);
+        mock.push(H256::repeat_byte(1)).unwrap();
+        mock.push(U256::from(5)).unwrap();
+        mock.push(U256::from(4)).unwrap();
+        mock.push_response(json_rpc_error("nonce too low"));
+        mock.push(U256::from(3)).unwrap();
+
+        manager.send_transaction(tx(alice), None).await.unwrap();
+
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 3);
+        mock.assert_request("eth_getTransactionCount", (alice, "latest")).unwrap();
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 5);
+        assert_eq!(manager.in_flight(alice), vec![5.into()]);
+    }
+
+    #[tokio::test]
+    async fn keeps_conflicting_nonces_after_resync() {
+        let (manager, mock) = nonce_manager();
+        let alice = Address::repeat_byte(1);
+        mock.push(U256::from(6)).unwrap();
+        mock.push(U256::from(6)).unwrap();
+        mock.push_response(json_rpc_error("replacement transaction underpriced"));
+        mock.push(U256::from(5)).unwrap();
+        mock.push(U256::from(4)).unwrap();
+        mock.push_response(json_rpc_error("nonce too low"));
+        mock.push(U256::from(3)).unwrap();
+
+        manager.send_transaction(tx(alice), None).await.unwrap_err();
+
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 3);
+        mock.assert_request("eth_getTransactionCount", (alice, "latest")).unwrap();
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        assert_sent(&mock, tx(alice), 5);
+        mock.assert_request("eth_getTransactionCount", (alice, "latest")).unwrap();
+        mock.assert_request("eth_getTransactionCount", (alice, "pending")).unwrap();
+        // the conflicting nonce is not released, the nonces are resynced instead
+        assert_eq!(manager.in_flight(alice), Vec::<U256>::new());
+        assert_eq!(manager.accounts.lock().unwrap()[&alice].next, 6);
+    }
+
+    #[tokio::test]
+    async fn initializes_nonces_after_next() {
+        let (manager, mock) = nonce_manager();
+        mock.push(H256::repeat_byte(1)).unwrap();
+        mock.push(U256::from(3)).unwrap();
+
+        manager.next();
+        manager.send_transaction(tx(Address::zero()), None).await.unwrap();
+
+        mock.assert_request("eth_getTransactionCount", (Address::zero(), "pending")).unwrap();
+        assert_sent(&mock, tx(Address::zero()), 3);
+    }
+
+    #[test]
+    fn keeps_nonces_above_pending_on_sync() {
+        let mut account = AccountNonces { initialized: true, next: 5, ..Default::default() };
+        assert_eq!(account.take(), 5);
+        assert_eq!(account.take(), 6);
+
+        // the transaction with nonce 6 did not reach the node yet
+        account.sync(4, 6);
+        assert_eq!(account.in_flight, BTreeSet::from([5, 6]));
+        assert_eq!(account.take(), 7);
+    }
+}
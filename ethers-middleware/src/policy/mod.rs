@@ -0,0 +1,394 @@
+mod rules;
+pub use rules::{AllowList, AllowedSelectors, DenyList, MaxFee, MaxValue};
+
+mod spend_limit;
+pub use spend_limit::SpendLimit;
+
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, NameOrAddress, Selector, U256,
+    },
+    utils::hex,
+};
+use ethers_providers::{Middleware, MiddlewareError, PendingTransaction};
+
+use async_trait::async_trait;
+use std::{fmt::Debug, sync::Mutex};
+use thiserror::Error;
+
+/// Basic trait to ensure that transactions about to be sent follow certain rules.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Policy: Sync + Send + Debug {
+    type Error: Sync + Send + Debug;
+
+    /// Evaluates the transactions.
+    ///
+    /// Returns Ok with the `tx` or an Err otherwise.
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error>;
+
+    /// Called by the [`PolicyMiddleware`] once a transaction accepted by the policy has been sent.
+    fn on_sent(&self, _tx: &TypedTransaction) {}
+
+    /// Called by the [`PolicyMiddleware`] when a transaction accepted by the policy is not sent,
+    /// because another policy rejected it or sending it failed.
+    ///
+    /// Policies reserving resources for the transactions they accept, like [`SpendLimit`],
+    /// release them here.
+    fn on_not_sent(&self, _tx: &TypedTransaction) {}
+
+    /// Returns a policy which requires both this policy and `other` to accept a transaction.
+    ///
+    /// The transaction returned by this policy is evaluated by `other`.
+    fn and<P: Policy>(self, other: P) -> And<Self, P>
+    where
+        Self: Sized,
+    {
+        And { first: self, second: other }
+    }
+
+    /// Returns a policy which requires this policy or `other` to accept a transaction.
+    ///
+    /// `other` is only evaluated if this policy rejects the transaction.
+    fn or<P: Policy>(self, other: P) -> Or<Self, P>
+    where
+        Self: Sized,
+    {
+        Or { first: self, second: other, accepted: Default::default() }
+    }
+}
+
+/// Error thrown by the policies of this module when rejecting a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PolicyError {
+    /// The destination of the transaction is not allowed. `None` for contract deployments.
+    #[error("destination {0:?} is not allowed")]
+    DestinationNotAllowed(Option<NameOrAddress>),
+    /// The transaction calls a function which is not allowed
+    #[error("function selector 0x{} is not allowed", hex::encode(.0))]
+    SelectorNotAllowed(Selector),
+    /// The value of the transaction exceeds the maximum
+    #[error("value {value} exceeds the maximum of {max}")]
+    ValueTooHigh { value: U256, max: U256 },
+    /// The maximum fee of the transaction exceeds the maximum
+    #[error("fee {fee} exceeds the maximum of {max}")]
+    FeeTooHigh { fee: U256, max: U256 },
+    /// The gas limit or the gas price of the transaction are not set
+    #[error("the gas limit and the gas price of the transaction must be set")]
+    UnknownFee,
+    /// The calldata of a token transfer could not be decoded
+    #[error("invalid calldata for token {0:?}")]
+    InvalidCalldata(Address),
+    /// The transaction spends more of an asset than the remaining limit. `None` for ether.
+    #[error("spending {amount} of {token:?} exceeds the remaining limit of {remaining}")]
+    SpendLimitExceeded { token: Option<Address>, amount: U256, remaining: U256 },
+}
+
+/// A policy that does not restrict anything.
+#[derive(Debug, Clone, Copy)]
+pub struct AllowEverything;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for AllowEverything {
+    type Error = ();
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        Ok(tx)
+    }
+}
+
+/// A policy that rejects all transactions.
+#[derive(Debug, Clone, Copy)]
+pub struct RejectEverything;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for RejectEverything {
+    type Error = ();
+
+    async fn ensure_can_send(&self, _: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        Err(())
+    }
+}
+
+/// A policy which requires both of its policies to accept a transaction, see [`Policy::and`].
+#[derive(Debug, Clone, Copy)]
+pub struct And<A, B> {
+    first: A,
+    second: B,
+}
+
+/// Error thrown by the [`And`] policy, holding the error of the policy which rejected the
+/// transaction.
+#[derive(Debug, Error)]
+pub enum AndError<A: Debug, B: Debug> {
+    /// The first policy rejected the transaction
+    #[error("{0:?}")]
+    First(A),
+    /// The second policy rejected the transaction
+    #[error("{0:?}")]
+    Second(B),
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<A: Policy, B: Policy> Policy for And<A, B> {
+    type Error = AndError<A::Error, B::Error>;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let tx = self.first.ensure_can_send(tx).await.map_err(AndError::First)?;
+        match self.second.ensure_can_send(tx.clone()).await {
+            Ok(tx) => Ok(tx),
+            Err(err) => {
+                self.first.on_not_sent(&tx);
+                Err(AndError::Second(err))
+            }
+        }
+    }
+
+    fn on_sent(&self, tx: &TypedTransaction) {
+        self.first.on_sent(tx);
+        self.second.on_sent(tx);
+    }
+
+    fn on_not_sent(&self, tx: &TypedTransaction) {
+        self.first.on_not_sent(tx);
+        self.second.on_not_sent(tx);
+    }
+}
+
+/// A policy which requires either of its policies to accept a transaction, see [`Policy::or`].
+///
+/// Only the policy which accepted a transaction is notified once it was sent or not.
+#[derive(Debug)]
+pub struct Or<A, B> {
+    first: A,
+    second: B,
+    /// The accepted transactions which were not yet sent, with `true` if the first policy
+    /// accepted them
+    accepted: Mutex<Vec<(TypedTransaction, bool)>>,
+}
+
+impl<A, B> Or<A, B> {
+    /// Returns `true` if the first policy accepted `tx`, `None` if `tx` was not accepted
+    fn take_accepted(&self, tx: &TypedTransaction) -> Option<bool> {
+        let mut accepted = self.accepted.lock().unwrap();
+        let idx = accepted.iter().position(|(accepted, _)| accepted == tx)?;
+        Some(accepted.swap_remove(idx).1)
+    }
+}
+
+/// Error thrown by the [`Or`] policy when both policies rejected the transaction.
+#[derive(Debug, Error)]
+#[error("{first:?}, {second:?}")]
+pub struct OrError<A: Debug, B: Debug> {
+    /// The error of the first policy
+    pub first: A,
+    /// The error of the second policy
+    pub second: B,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<A: Policy, B: Policy> Policy for Or<A, B> {
+    type Error = OrError<A::Error, B::Error>;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let (tx, by_first) = match self.first.ensure_can_send(tx.clone()).await {
+            Ok(tx) => (tx, true),
+            Err(first) => {
+                let tx = self
+                    .second
+                    .ensure_can_send(tx)
+                    .await
+                    .map_err(|second| OrError { first, second })?;
+                (tx, false)
+            }
+        };
+        self.accepted.lock().unwrap().push((tx.clone(), by_first));
+        Ok(tx)
+    }
+
+    fn on_sent(&self, tx: &TypedTransaction) {
+        match self.take_accepted(tx) {
+            Some(true) => self.first.on_sent(tx),
+            Some(false) => self.second.on_sent(tx),
+            None => {}
+        }
+    }
+
+    fn on_not_sent(&self, tx: &TypedTransaction) {
+        match self.take_accepted(tx) {
+            Some(true) => self.first.on_not_sent(tx),
+            Some(false) => self.second.on_not_sent(tx),
+            None => {}
+        }
+    }
+}
+
+/// Middleware used to enforce certain policies for transactions.
+#[derive(Clone, Debug)]
+pub struct PolicyMiddleware<M, P> {
+    pub(crate) inner: M,
+    pub(crate) policy: P,
+}
+
+impl<M, P> PolicyMiddleware<M, P>
+where
+    M: Middleware,
+    P: Policy,
+{
+    /// Creates a new client from the provider and policy.
+    pub fn new(inner: M, policy: P) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the client interacts with the policy middleware.
+pub enum PolicyMiddlewareError<M: Middleware, P: Policy> {
+    /// Thrown when the internal policy errors
+    #[error("{0:?}")]
+    PolicyError(P::Error),
+    /// Thrown when an internal middleware errors
+    #[error(transparent)]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware, P: Policy> MiddlewareError for PolicyMiddlewareError<M, P> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        PolicyMiddlewareError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            PolicyMiddlewareError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, P> Middleware for PolicyMiddleware<M, P>
+where
+    M: Middleware,
+    P: Policy,
+{
+    type Error = PolicyMiddlewareError<M, P>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// This ensures the tx complies with the registered policy.
+    /// If so then this simply delegates the transaction to the inner middleware, and notifies the
+    /// policy once the transaction was sent
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let tx = self
+            .policy
+            .ensure_can_send(tx.into())
+            .await
+            .map_err(PolicyMiddlewareError::PolicyError)?;
+        let pending = match self.inner.send_transaction(tx.clone(), block).await {
+            Ok(pending) => pending,
+            Err(err) => {
+                self.policy.on_not_sent(&tx);
+                return Err(PolicyMiddlewareError::MiddlewareError(err))
+            }
+        };
+        self.policy.on_sent(&tx);
+        Ok(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::TransactionRequest;
+
+    #[tokio::test]
+    async fn combines_policies() {
+        let to = Address::repeat_byte(1);
+        let tx: TypedTransaction = TransactionRequest::new().to(to).value(10).into();
+
+        let policy = AllowList::new([to]).and(MaxValue::new(5));
+        assert!(matches!(
+            policy.ensure_can_send(tx.clone()).await.unwrap_err(),
+            AndError::Second(PolicyError::ValueTooHigh { .. })
+        ));
+        assert!(AllowList::new([to])
+            .and(AllowEverything)
+            .ensure_can_send(tx.clone())
+            .await
+            .is_ok());
+
+        let policy = MaxValue::new(5).or(AllowList::new([to]));
+        assert!(policy.ensure_can_send(tx.clone()).await.is_ok());
+        let err = MaxValue::new(5).or(RejectEverything).ensure_can_send(tx).await.unwrap_err();
+        assert!(matches!(err.first, PolicyError::ValueTooHigh { .. }));
+    }
+
+    #[tokio::test]
+    async fn spends_only_sent_transactions() {
+        let (provider, _mock) = ethers_providers::Provider::mocked();
+        let policy = SpendLimit::new(std::time::Duration::from_secs(60)).native(100);
+        let tx = TransactionRequest::new().to(Address::repeat_byte(1)).value(60);
+
+        // rejected by another policy
+        let client = PolicyMiddleware::new(provider.clone(), policy.and(RejectEverything));
+        assert!(matches!(
+            client.send_transaction(tx.clone(), None).await.unwrap_err(),
+            PolicyMiddlewareError::PolicyError(AndError::Second(()))
+        ));
+        assert_eq!(client.policy.first.remaining(None), Some(100.into()));
+
+        // failed to be sent, the mock has no responses
+        let client = PolicyMiddleware::new(provider, client.policy.first);
+        assert!(matches!(
+            client.send_transaction(tx.clone(), None).await.unwrap_err(),
+            PolicyMiddlewareError::MiddlewareError(_)
+        ));
+        assert_eq!(client.policy.remaining(None), Some(100.into()));
+
+        // concurrent transactions can't exceed the limit together
+        let tx: TypedTransaction = tx.into();
+        let (first, second) = futures_util::join!(
+            client.policy.ensure_can_send(tx.clone()),
+            client.policy.ensure_can_send(tx.clone())
+        );
+        assert!(first.is_ok());
+        assert!(second.is_err());
+        assert_eq!(client.policy.remaining(None), Some(40.into()));
+    }
+
+    #[tokio::test]
+    async fn notifies_accepting_policy() {
+        let policy = SpendLimit::new(std::time::Duration::from_secs(60)).native(100);
+        let policy = policy.or(AllowEverything);
+        let limited: TypedTransaction =
+            TransactionRequest::new().to(Address::repeat_byte(1)).value(60).into();
+        let allowed: TypedTransaction =
+            TransactionRequest::new().to(Address::repeat_byte(2)).value(60).into();
+
+        policy.ensure_can_send(limited.clone()).await.unwrap();
+        // rejected by the spend limit, accepted by the second policy
+        policy.ensure_can_send(allowed.clone()).await.unwrap();
+        assert_eq!(policy.first.remaining(None), Some(40.into()));
+
+        // the spend limit did not accept the transaction, so it keeps its reservation
+        policy.on_not_sent(&allowed);
+        assert_eq!(policy.first.remaining(None), Some(40.into()));
+        policy.on_not_sent(&limited);
+        assert_eq!(policy.first.remaining(None), Some(100.into()));
+    }
+}
@@ -0,0 +1,239 @@
+use super::{Policy, PolicyError};
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, NameOrAddress, Selector, U256,
+};
+use std::collections::HashSet;
+
+/// Returns the address the transaction is sent to, if it is not a deployment or sent to an ENS name
+fn destination(tx: &TypedTransaction) -> Option<Address> {
+    match tx.to() {
+        Some(NameOrAddress::Address(addr)) => Some(*addr),
+        _ => None,
+    }
+}
+
+/// A policy which only allows transactions to the given addresses.
+///
+/// Contract deployments and transactions to ENS names are rejected, names must be resolved before
+/// the policy is evaluated.
+#[derive(Debug, Clone, Default)]
+pub struct AllowList {
+    addresses: HashSet<Address>,
+}
+
+impl AllowList {
+    /// Creates a policy allowing transactions to `addresses`
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { addresses: addresses.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for AllowList {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        match destination(&tx) {
+            Some(to) if self.addresses.contains(&to) => Ok(tx),
+            _ => Err(PolicyError::DestinationNotAllowed(tx.to().cloned())),
+        }
+    }
+}
+
+/// A policy which rejects transactions to the given addresses.
+///
+/// Transactions to ENS names are rejected, names must be resolved before the policy is evaluated.
+/// Contract deployments are allowed.
+#[derive(Debug, Clone, Default)]
+pub struct DenyList {
+    addresses: HashSet<Address>,
+}
+
+impl DenyList {
+    /// Creates a policy rejecting transactions to `addresses`
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { addresses: addresses.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for DenyList {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        match tx.to() {
+            None => Ok(tx),
+            Some(NameOrAddress::Address(to)) if !self.addresses.contains(to) => Ok(tx),
+            Some(to) => Err(PolicyError::DestinationNotAllowed(Some(to.clone()))),
+        }
+    }
+}
+
+/// A policy which only allows calls of the functions with the given selectors.
+///
+/// Transactions without calldata, such as plain ether transfers, are allowed.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedSelectors {
+    selectors: HashSet<Selector>,
+}
+
+impl AllowedSelectors {
+    /// Creates a policy allowing calls of the functions with the given `selectors`
+    pub fn new(selectors: impl IntoIterator<Item = Selector>) -> Self {
+        Self { selectors: selectors.into_iter().collect() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for AllowedSelectors {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let data = tx.data().map(|data| data.as_ref()).unwrap_or_default();
+        if data.is_empty() {
+            return Ok(tx)
+        }
+        // calldata too short to hold a selector is padded, so that it is rejected
+        let mut selector = Selector::default();
+        let len = data.len().min(4);
+        selector[..len].copy_from_slice(&data[..len]);
+        if data.len() >= 4 && self.selectors.contains(&selector) {
+            Ok(tx)
+        } else {
+            Err(PolicyError::SelectorNotAllowed(selector))
+        }
+    }
+}
+
+/// A policy which rejects transactions sending more than the given amount of ether.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxValue {
+    max: U256,
+}
+
+impl MaxValue {
+    /// Creates a policy rejecting transactions with a value above `max`
+    pub fn new(max: impl Into<U256>) -> Self {
+        Self { max: max.into() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for MaxValue {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let value = tx.value().copied().unwrap_or_default();
+        if value > self.max {
+            return Err(PolicyError::ValueTooHigh { value, max: self.max })
+        }
+        Ok(tx)
+    }
+}
+
+/// A policy which rejects transactions which may cost more than the given fee, the product of
+/// their gas limit and their (maximum) gas price.
+///
+/// Transactions whose gas limit or gas price are not set are rejected, so they must be filled, for
+/// example with [`Middleware::fill_transaction`](ethers_providers::Middleware::fill_transaction),
+/// before being sent through the [`PolicyMiddleware`](super::PolicyMiddleware).
+#[derive(Debug, Clone, Copy)]
+pub struct MaxFee {
+    max: U256,
+}
+
+impl MaxFee {
+    /// Creates a policy rejecting transactions with a fee above `max`
+    pub fn new(max: impl Into<U256>) -> Self {
+        Self { max: max.into() }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for MaxFee {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let (gas, gas_price) = match (tx.gas(), tx.gas_price()) {
+            (Some(gas), Some(gas_price)) => (*gas, gas_price),
+            _ => return Err(PolicyError::UnknownFee),
+        };
+        let fee = gas.saturating_mul(gas_price);
+        if fee > self.max {
+            return Err(PolicyError::FeeTooHigh { fee, max: self.max })
+        }
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::TransactionRequest;
+
+    #[tokio::test]
+    async fn checks_destinations() {
+        let allowed = Address::repeat_byte(1);
+        let other = Address::repeat_byte(2);
+
+        let allow = AllowList::new([allowed]);
+        assert!(allow.ensure_can_send(TransactionRequest::new().to(allowed).into()).await.is_ok());
+        assert_eq!(
+            allow.ensure_can_send(TransactionRequest::new().to(other).into()).await.unwrap_err(),
+            PolicyError::DestinationNotAllowed(Some(other.into()))
+        );
+        assert!(allow.ensure_can_send(TransactionRequest::new().into()).await.is_err());
+
+        let deny = DenyList::new([allowed]);
+        assert!(deny.ensure_can_send(TransactionRequest::new().to(allowed).into()).await.is_err());
+        assert!(deny.ensure_can_send(TransactionRequest::new().to(other).into()).await.is_ok());
+        assert!(deny.ensure_can_send(TransactionRequest::new().into()).await.is_ok());
+        assert!(deny
+            .ensure_can_send(TransactionRequest::new().to("vitalik.eth").into())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_selectors() {
+        let policy = AllowedSelectors::new([[0xa9, 0x05, 0x9c, 0xbb]]);
+        let call = |data: Vec<u8>| TransactionRequest::new().data(data).into();
+
+        assert!(policy.ensure_can_send(call(vec![])).await.is_ok());
+        assert!(policy.ensure_can_send(call(vec![0xa9, 0x05, 0x9c, 0xbb, 0])).await.is_ok());
+        assert_eq!(
+            policy.ensure_can_send(call(vec![0x09, 0x5e, 0xa7, 0xb3])).await.unwrap_err(),
+            PolicyError::SelectorNotAllowed([0x09, 0x5e, 0xa7, 0xb3])
+        );
+        assert!(policy.ensure_can_send(call(vec![0xa9, 0x05])).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_value_and_fee() {
+        let tx: TypedTransaction = TransactionRequest::new().value(100).gas(21_000).into();
+
+        assert!(MaxValue::new(100).ensure_can_send(tx.clone()).await.is_ok());
+        assert_eq!(
+            MaxValue::new(99).ensure_can_send(tx.clone()).await.unwrap_err(),
+            PolicyError::ValueTooHigh { value: 100.into(), max: 99.into() }
+        );
+
+        assert_eq!(
+            MaxFee::new(u64::MAX).ensure_can_send(tx.clone()).await.unwrap_err(),
+            PolicyError::UnknownFee
+        );
+        let mut tx = tx;
+        tx.set_gas_price(10);
+        assert!(MaxFee::new(210_000).ensure_can_send(tx.clone()).await.is_ok());
+        assert_eq!(
+            MaxFee::new(1).ensure_can_send(tx).await.unwrap_err(),
+            PolicyError::FeeTooHigh { fee: 210_000.into(), max: 1.into() }
+        );
+    }
+}
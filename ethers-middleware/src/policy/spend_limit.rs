@@ -0,0 +1,251 @@
+use super::{Policy, PolicyError};
+use async_trait::async_trait;
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, Selector, U256},
+};
+use instant::{Duration, Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+/// The selector of the ERC-20 `transfer(address,uint256)` function
+const TRANSFER_SELECTOR: Selector = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// The selector of the ERC-20 `approve(address,uint256)` function
+const APPROVE_SELECTOR: Selector = [0x09, 0x5e, 0xa7, 0xb3];
+
+/// The amounts spent within the window by token, `None` for ether, oldest first
+type Spends = HashMap<Option<Address>, VecDeque<(Instant, U256)>>;
+
+/// A policy limiting the amounts of ether and of ERC-20 tokens spent within a rolling time window.
+///
+/// The value of a transaction is spent from the ether limit. Calls of the `transfer` and `approve`
+/// functions of a token with a limit spend their amount from the limit of the token. Calls of
+/// other functions of the token, as well as transfers of tokens without a limit, are not
+/// restricted. Transactions to ENS names are rejected as their destination could be a limited
+/// token, names must be resolved before the policy is evaluated.
+///
+/// The amounts of a transaction are reserved as soon as it is accepted, so transactions evaluated
+/// concurrently can't exceed the limits together. The [`PolicyMiddleware`](super::PolicyMiddleware)
+/// releases them again if the transaction is rejected by another policy or fails to be sent.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::{Address, U256};
+/// use ethers_middleware::policy::{MaxValue, Policy, SpendLimit};
+/// use std::time::Duration;
+///
+/// let usdc: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap();
+/// // at most 1 ether and 10,000 USDC per day, and 0.5 ether per transaction
+/// let policy = SpendLimit::new(Duration::from_secs(24 * 60 * 60))
+///     .native(U256::exp10(18))
+///     .token(usdc, U256::from(10_000) * U256::exp10(6))
+///     .and(MaxValue::new(U256::exp10(18) / 2));
+/// ```
+#[derive(Debug)]
+pub struct SpendLimit {
+    window: Duration,
+    /// The limits by token, `None` for ether
+    limits: HashMap<Option<Address>, U256>,
+    spent: Mutex<Spends>,
+}
+
+impl SpendLimit {
+    /// Creates a policy for the given time window, without any limits
+    pub fn new(window: Duration) -> Self {
+        Self { window, limits: Default::default(), spent: Default::default() }
+    }
+
+    /// Limits the amount of ether spent within the window
+    pub fn native(mut self, limit: impl Into<U256>) -> Self {
+        self.limits.insert(None, limit.into());
+        self
+    }
+
+    /// Limits the amount of the ERC-20 `token` spent within the window
+    pub fn token(mut self, token: Address, limit: impl Into<U256>) -> Self {
+        self.limits.insert(Some(token), limit.into());
+        self
+    }
+
+    /// Returns the amount of `token`, `None` for ether, which can still be spent within the
+    /// current window, or `None` if the token is not limited
+    pub fn remaining(&self, token: Option<Address>) -> Option<U256> {
+        let limit = *self.limits.get(&token)?;
+        let mut spent = self.spent.lock().unwrap();
+        Some(self.remaining_at(&mut spent, token, limit, Instant::now()))
+    }
+
+    fn remaining_at(
+        &self,
+        spent: &mut Spends,
+        token: Option<Address>,
+        limit: U256,
+        now: Instant,
+    ) -> U256 {
+        let spends = match spent.get_mut(&token) {
+            Some(spends) => spends,
+            None => return limit,
+        };
+        while spends.front().map_or(false, |(at, _)| now.duration_since(*at) >= self.window) {
+            spends.pop_front();
+        }
+        let total =
+            spends.iter().fold(U256::zero(), |total, (_, amount)| total.saturating_add(*amount));
+        limit.saturating_sub(total)
+    }
+
+    /// Returns the amounts spent by `tx` of all limited tokens
+    fn spends(&self, tx: &TypedTransaction) -> Result<Vec<(Option<Address>, U256)>, PolicyError> {
+        let mut spends = Vec::new();
+        if let Some(value) = tx.value().filter(|value| !value.is_zero()) {
+            if self.limits.contains_key(&None) {
+                spends.push((None, *value));
+            }
+        }
+
+        let token = match tx.to() {
+            Some(NameOrAddress::Address(to)) if self.limits.contains_key(&Some(*to)) => *to,
+            Some(to @ NameOrAddress::Name(_)) => {
+                return Err(PolicyError::DestinationNotAllowed(Some(to.clone())))
+            }
+            _ => return Ok(spends),
+        };
+        let data = tx.data().map(|data| data.as_ref()).unwrap_or_default();
+        if data.len() < 4 ||
+            ![TRANSFER_SELECTOR, APPROVE_SELECTOR].contains(&data[..4].try_into().unwrap())
+        {
+            return Ok(spends)
+        }
+        let amount = abi::decode(&[ParamType::Address, ParamType::Uint(256)], &data[4..])
+            .ok()
+            .and_then(|tokens| match tokens.as_slice() {
+                [_, Token::Uint(amount)] => Some(*amount),
+                _ => None,
+            })
+            .ok_or(PolicyError::InvalidCalldata(token))?;
+        spends.push((Some(token), amount));
+        Ok(spends)
+    }
+
+    /// Checks the amounts spent by `tx` against the limits and reserves them
+    fn ensure_at(&self, tx: &TypedTransaction, now: Instant) -> Result<(), PolicyError> {
+        let spends = self.spends(tx)?;
+        let mut spent = self.spent.lock().unwrap();
+        for &(token, amount) in &spends {
+            let remaining = self.remaining_at(&mut spent, token, self.limits[&token], now);
+            if amount > remaining {
+                return Err(PolicyError::SpendLimitExceeded { token, amount, remaining })
+            }
+        }
+        for (token, amount) in spends {
+            spent.entry(token).or_default().push_back((now, amount));
+        }
+        Ok(())
+    }
+
+    /// Releases the amounts reserved for `tx`
+    fn release(&self, tx: &TypedTransaction) {
+        // the transaction was accepted, so its calldata is valid
+        let spends = self.spends(tx).unwrap_or_default();
+        let mut spent = self.spent.lock().unwrap();
+        for (token, amount) in spends {
+            let Some(spends) = spent.get_mut(&token) else { continue };
+            // the reservation may have left the window in the meantime
+            if let Some(idx) = spends.iter().rposition(|(_, spent)| *spent == amount) {
+                spends.remove(idx);
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for SpendLimit {
+    type Error = PolicyError;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        self.ensure_at(&tx, Instant::now())?;
+        Ok(tx)
+    }
+
+    fn on_not_sent(&self, tx: &TypedTransaction) {
+        self.release(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::TransactionRequest;
+
+    fn transfer(token: Address, amount: u64) -> TypedTransaction {
+        let mut data = TRANSFER_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::Address(Address::zero()), Token::Uint(amount.into())]));
+        TransactionRequest::new().to(token).data(data).into()
+    }
+
+    #[test]
+    fn limits_spends_within_window() {
+        let token = Address::repeat_byte(1);
+        let window = Duration::from_secs(60);
+        let policy = SpendLimit::new(window).native(100).token(token, 1_000);
+        let start = Instant::now();
+
+        let spend = |tx: &TypedTransaction, at: Instant| policy.ensure_at(tx, at);
+
+        spend(&transfer(token, 600), start).unwrap();
+        assert_eq!(
+            spend(&transfer(token, 500), start).unwrap_err(),
+            PolicyError::SpendLimitExceeded {
+                token: Some(token),
+                amount: 500.into(),
+                remaining: 400.into()
+            }
+        );
+        // released amounts are available again
+        spend(&transfer(token, 400), start).unwrap();
+        policy.release(&transfer(token, 400));
+        // tokens without a limit are not restricted
+        spend(&transfer(Address::repeat_byte(2), 5_000), start).unwrap();
+
+        // the ether limit is checked before the token is spent
+        let mut tx = transfer(token, 400);
+        tx.set_value(101);
+        assert!(spend(&tx, start).is_err());
+        tx.set_value(100);
+        spend(&tx, start).unwrap();
+        assert!(spend(&TransactionRequest::new().value(1).into(), start).is_err());
+
+        // the spends leave the window
+        let later = start + window;
+        spend(&transfer(token, 1_000), later).unwrap();
+        spend(&TransactionRequest::new().value(100).into(), later).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_calldata() {
+        let token = Address::repeat_byte(1);
+        let policy = SpendLimit::new(Duration::from_secs(60)).token(token, 1_000);
+        let tx = TransactionRequest::new().to(token).data(APPROVE_SELECTOR.to_vec()).into();
+        assert_eq!(
+            policy.ensure_at(&tx, Instant::now()).unwrap_err(),
+            PolicyError::InvalidCalldata(token)
+        );
+        assert_eq!(policy.remaining(Some(token)), Some(1_000.into()));
+        assert_eq!(policy.remaining(None), None);
+    }
+
+    #[test]
+    fn rejects_ens_names() {
+        let policy = SpendLimit::new(Duration::from_secs(60)).token(Address::repeat_byte(1), 1_000);
+        let tx = TransactionRequest::new().to("usdc.eth").data(TRANSFER_SELECTOR.to_vec()).into();
+        assert_eq!(
+            policy.ensure_at(&tx, Instant::now()).unwrap_err(),
+            PolicyError::DestinationNotAllowed(Some("usdc.eth".into()))
+        );
+    }
+}
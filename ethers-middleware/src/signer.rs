@@ -154,8 +154,8 @@ where
         let signature =
             self.signer.sign_transaction(&tx).await.map_err(SignerMiddlewareError::SignerError)?;
 
-        // Return the raw rlp-encoded signed transaction
-        Ok(tx.rlp_signed(&signature))
+        // Return the raw rlp-encoded signed transaction, including the blob sidecar if any
+        Ok(tx.rlp_signed_pooled(&signature))
     }
 
     /// Returns the client's address
@@ -0,0 +1,311 @@
+use async_trait::async_trait;
+use ethers_contract::{ContractRevert, EthError};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, BlockNumber, Bytes,
+    GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions,
+    GethDebugTracingOptions, GethTrace, GethTraceFrame, U256,
+};
+use ethers_providers::{
+    JsonRpcError, Middleware, MiddlewareError, PendingTransaction, RpcErrorKind,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use thiserror::Error;
+
+/// The default percentage added to the estimated gas of a transaction
+pub const DEFAULT_GAS_BUFFER_PERCENT: u64 = 10;
+
+/// Middleware simulating every outgoing transaction at the pending block before sending it.
+///
+/// Transactions which would revert are not sent, the [`SimulationError::Reverted`] error holds
+/// the revert data instead. If the transaction has no gas limit, it is set to the estimated gas
+/// plus a buffer of [`DEFAULT_GAS_BUFFER_PERCENT`] percent, configurable via
+/// [`SimulationMiddleware::gas_buffer`].
+///
+/// Transactions are simulated with `eth_call`, or with `debug_traceCall` if
+/// [enabled](SimulationMiddleware::trace) and supported by the node.
+///
+/// The middleware must be placed above the [`SignerMiddleware`](crate::SignerMiddleware), which
+/// sends signed transactions directly to its inner middleware.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::{SignerMiddleware, SimulationMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let wallet: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let client = SimulationMiddleware::new(SignerMiddleware::new(provider, wallet)).gas_buffer(20);
+///
+/// let tx = TransactionRequest::new().to(Address::zero()).data(vec![0x12, 0x34]);
+/// match client.send_transaction(tx, None).await {
+///     Err(err) if err.is_revert() => println!("would revert: {:?}", err.reason()),
+///     res => println!("{:?}", res?.await?),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SimulationMiddleware<M> {
+    inner: M,
+    gas_buffer: u64,
+    /// Whether transactions are simulated with `debug_traceCall`. Disabled once the node turns
+    /// out not to support it.
+    trace: AtomicBool,
+}
+
+impl<M: Middleware> SimulationMiddleware<M> {
+    /// Creates a new middleware simulating transactions with `eth_call`
+    pub fn new(inner: M) -> Self {
+        Self { inner, gas_buffer: DEFAULT_GAS_BUFFER_PERCENT, trace: AtomicBool::new(false) }
+    }
+
+    /// Sets the percentage added to the estimated gas of transactions without a gas limit
+    #[must_use]
+    pub fn gas_buffer(mut self, percent: u64) -> Self {
+        self.gas_buffer = percent;
+        self
+    }
+
+    /// Simulates transactions with `debug_traceCall` instead of `eth_call`, falling back to
+    /// `eth_call` if the node does not support it
+    #[must_use]
+    pub fn trace(self, trace: bool) -> Self {
+        self.trace.store(trace, Ordering::Relaxed);
+        self
+    }
+
+    /// Simulates `tx` at the pending block, returning the revert data if it would revert
+    pub async fn simulate(&self, tx: &TypedTransaction) -> Result<(), SimulationError<M>> {
+        let block = Some(BlockNumber::Pending.into());
+        if self.trace.load(Ordering::Relaxed) {
+            match self.trace_call(tx, block).await {
+                Err(SimulationError::MiddlewareError(err))
+                    if err.error_kind() == Some(RpcErrorKind::MethodNotFound) =>
+                {
+                    tracing::debug!("debug_traceCall is not supported, simulating with eth_call");
+                    self.trace.store(false, Ordering::Relaxed);
+                }
+                res => return res,
+            }
+        }
+        self.inner.call(tx, block).await.map_err(SimulationError::from_middleware_error)?;
+        Ok(())
+    }
+
+    async fn trace_call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), SimulationError<M>> {
+        let options = GethDebugTracingCallOptions {
+            tracing_options: GethDebugTracingOptions {
+                tracer: Some(GethDebugTracerType::BuiltInTracer(
+                    GethDebugBuiltInTracerType::CallTracer,
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let trace = self
+            .inner
+            .debug_trace_call(tx.clone(), block, options)
+            .await
+            .map_err(SimulationError::from_middleware_error)?;
+        match trace {
+            GethTrace::Known(GethTraceFrame::CallTracer(frame)) if frame.error.is_some() => {
+                Err(SimulationError::Reverted(frame.output.unwrap_or_default()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Thrown when an error happens at the simulation middleware
+pub enum SimulationError<M: Middleware> {
+    /// The transaction would revert
+    #[error("transaction would revert with data: {0}")]
+    Reverted(Bytes),
+
+    /// Thrown when an internal middleware errors
+    #[error(transparent)]
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> SimulationError<M> {
+    /// Converts a [`MiddlewareError`] to a `SimulationError`, extracting the revert data of
+    /// reverted calls
+    pub fn from_middleware_error(e: M::Error) -> Self {
+        if let Some(data) = e.as_error_response().and_then(JsonRpcError::as_revert_data) {
+            SimulationError::Reverted(data)
+        } else {
+            SimulationError::MiddlewareError(e)
+        }
+    }
+
+    /// Returns the revert data, if the transaction would revert
+    pub fn as_revert(&self) -> Option<&Bytes> {
+        match self {
+            SimulationError::Reverted(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// True if the transaction would revert, false otherwise
+    pub fn is_revert(&self) -> bool {
+        matches!(self, SimulationError::Reverted(_))
+    }
+
+    /// Returns the revert reason string, if the transaction would revert with one
+    pub fn reason(&self) -> Option<String> {
+        self.decode_revert()
+    }
+
+    /// Decode revert data into an [`EthError`] type. Returns `None` if
+    /// decoding fails, or if this is not a revert
+    pub fn decode_revert<Err: EthError>(&self) -> Option<Err> {
+        self.as_revert().and_then(|data| Err::decode_with_selector(data))
+    }
+
+    /// Decode revert data into a [`ContractRevert`] type. Returns `None` if
+    /// decoding fails, or if this is not a revert
+    pub fn decode_contract_revert<Err: ContractRevert>(&self) -> Option<Err> {
+        self.as_revert().and_then(|data| Err::decode_with_selector(data))
+    }
+}
+
+impl<M: Middleware> MiddlewareError for SimulationError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        SimulationError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            SimulationError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for SimulationMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = SimulationError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Simulates the transaction and sends it if it would not revert
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if tx.from().is_none() {
+            if let Some(sender) = self.default_sender() {
+                tx.set_from(sender);
+            }
+        }
+
+        self.simulate(&tx).await?;
+        if tx.gas().is_none() {
+            let gas = self
+                .inner
+                .estimate_gas(&tx, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(SimulationError::from_middleware_error)?;
+            tx.set_gas(gas + gas * U256::from(self.gas_buffer) / 100);
+        }
+
+        self.inner.send_transaction(tx, block).await.map_err(SimulationError::MiddlewareError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{Address, TransactionRequest},
+    };
+    use ethers_providers::{MockResponse, Provider};
+
+    fn tx() -> TransactionRequest {
+        TransactionRequest::new()
+            .from(Address::repeat_byte(1))
+            .to(Address::repeat_byte(2))
+            .gas_price(1)
+    }
+
+    #[tokio::test]
+    async fn refuses_reverting_transactions() {
+        let (provider, mock) = Provider::mocked();
+        let client = SimulationMiddleware::new(provider);
+
+        let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+        data.extend("insufficient balance".to_string().encode());
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted: insufficient balance".to_string(),
+            data: Some(serde_json::json!(Bytes::from(data))),
+        }));
+
+        let err = client.send_transaction(tx(), None).await.unwrap_err();
+        assert!(err.is_revert());
+        assert_eq!(err.reason().as_deref(), Some("insufficient balance"));
+        mock.assert_request("eth_call", (TypedTransaction::Legacy(tx()), "pending")).unwrap();
+        // nothing was sent
+        assert!(mock.assert_request("eth_sendTransaction", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn sends_with_buffered_gas() {
+        let (provider, mock) = Provider::mocked();
+        let client = SimulationMiddleware::new(provider).gas_buffer(20);
+        let hash = ethers_core::types::TxHash::repeat_byte(3);
+
+        mock.push(hash).unwrap();
+        mock.push(U256::from(100_000)).unwrap();
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+
+        let pending = client.send_transaction(tx(), None).await.unwrap();
+        assert_eq!(*pending, hash);
+
+        mock.assert_request("eth_call", (TypedTransaction::Legacy(tx()), "pending")).unwrap();
+        mock.assert_request("eth_estimateGas", (TypedTransaction::Legacy(tx()), "pending"))
+            .unwrap();
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx().gas(120_000))])
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_eth_call() {
+        let (provider, mock) = Provider::mocked();
+        let client = SimulationMiddleware::new(provider).trace(true);
+
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32601,
+            message: "the method debug_traceCall does not exist/is not available".to_string(),
+            data: None,
+        }));
+
+        client.simulate(&tx().into()).await.unwrap();
+        assert!(!client.trace.load(Ordering::Relaxed));
+    }
+}
@@ -1,6 +1,9 @@
 pub mod ds_proxy;
 pub use ds_proxy::DsProxy;
 
+pub mod safe;
+pub use safe::Safe;
+
 mod middleware;
 pub use middleware::TransformerMiddleware;
 
@@ -0,0 +1,432 @@
+use super::{Transformer, TransformerError};
+use ethers_contract::{BaseContract, ContractError};
+use ethers_core::{
+    abi::{encode, parse_abi, Token},
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::{EIP712Domain, Eip712},
+        },
+        Address, Bytes, TransactionRequest, H160, H256, U256,
+    },
+    utils::keccak256,
+};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use std::convert::Infallible;
+
+/// The function signature of the Safe's execTransaction function.
+const SAFE_EXEC_TRANSACTION: &str =
+    "function execTransaction(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, bytes signatures) public payable returns (bool success)";
+/// The function signature of the Safe's nonce getter.
+const SAFE_NONCE: &str = "function nonce() public view returns (uint256)";
+/// The function signature of the MultiSend contract's multiSend function.
+const MULTI_SEND: &str = "function multiSend(bytes transactions) public payable";
+
+/// The EIP-712 type of a Safe transaction.
+const SAFE_TX_TYPE: &str = "SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)";
+
+/// The address of the canonical `MultiSendCallOnly` contract (v1.3.0), deployed at the same
+/// address on most chains.
+pub const MULTI_SEND_CALL_ONLY_ADDRESS: Address = H160([
+    0x40, 0xa2, 0xac, 0xcb, 0xd9, 0x2b, 0xca, 0x93, 0x8b, 0x02, 0x01, 0x0e, 0x17, 0xa5, 0xb8, 0x92,
+    0x9b, 0x49, 0x13, 0x0d,
+]);
+
+/// How a Safe transaction executes its call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Operation {
+    /// A regular call
+    #[default]
+    Call = 0,
+    /// A delegatecall, executing the code of the target in the context of the Safe
+    DelegateCall = 1,
+}
+
+/// A transaction executed by a Safe, signed by its owners.
+///
+/// The gas refund fields default to zero, so the sender of the `execTransaction` transaction pays
+/// for the gas.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SafeTx {
+    /// The address of the Safe
+    pub safe: Address,
+    /// The chain id of the Safe
+    pub chain_id: U256,
+    /// The target of the call
+    pub to: Address,
+    /// The ether sent by the Safe
+    pub value: U256,
+    /// The calldata
+    pub data: Bytes,
+    /// Whether the target is called or delegatecalled
+    pub operation: Operation,
+    /// The gas available to the call, 0 for all remaining gas
+    pub safe_tx_gas: U256,
+    /// The gas costs independent of the call, refunded to the `refund_receiver`
+    pub base_gas: U256,
+    /// The gas price of the refund
+    pub gas_price: U256,
+    /// The token the refund is paid in, the zero address for ether
+    pub gas_token: Address,
+    /// The receiver of the refund, the zero address for `tx.origin`
+    pub refund_receiver: Address,
+    /// The nonce of the Safe
+    pub nonce: U256,
+}
+
+impl SafeTx {
+    /// Returns the hash signed by the owners of the Safe
+    pub fn hash(&self) -> H256 {
+        H256(self.encode_eip712().unwrap())
+    }
+
+    /// Signs the transaction with every signer, returning the signatures in the format expected
+    /// by `execTransaction`, ordered by the addresses of the signers.
+    pub async fn sign<S: Signer>(&self, signers: &[S]) -> Result<Bytes, S::Error> {
+        let mut signatures = Vec::with_capacity(signers.len());
+        for signer in signers {
+            signatures.push((signer.address(), signer.sign_typed_data(self).await?));
+        }
+        signatures.sort_by_key(|(owner, _)| *owner);
+        Ok(signatures.into_iter().flat_map(|(_, signature)| signature.to_vec()).collect())
+    }
+}
+
+impl Eip712 for SafeTx {
+    type Error = Infallible;
+
+    fn domain(&self) -> Result<EIP712Domain, Self::Error> {
+        Ok(EIP712Domain {
+            chain_id: Some(self.chain_id),
+            verifying_contract: Some(self.safe),
+            ..Default::default()
+        })
+    }
+
+    fn type_hash() -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(SAFE_TX_TYPE))
+    }
+
+    fn struct_hash(&self) -> Result<[u8; 32], Self::Error> {
+        Ok(keccak256(encode(&[
+            Token::FixedBytes(Self::type_hash()?.to_vec()),
+            Token::Address(self.to),
+            Token::Uint(self.value),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+            Token::Uint((self.operation as u8).into()),
+            Token::Uint(self.safe_tx_gas),
+            Token::Uint(self.base_gas),
+            Token::Uint(self.gas_price),
+            Token::Address(self.gas_token),
+            Token::Address(self.refund_receiver),
+            Token::Uint(self.nonce),
+        ])))
+    }
+}
+
+/// Represents a [Safe](https://safe.global) (formerly Gnosis Safe) and implements the
+/// [Transformer] trait.
+///
+/// As a [Transformer], the Safe wraps transactions in `execTransaction`, approved by the sender of
+/// the transaction. This requires the sender to be an owner of a Safe with a threshold of one.
+/// Safes with a higher threshold execute [`SafeTx`]s signed by their owners instead:
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::transformer::Safe;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// # let safe_address = Address::random();
+/// # let owners: Vec<LocalWallet> = vec![];
+/// let safe = Safe::new(safe_address, 1);
+///
+/// // batch two transfers in a single Safe transaction
+/// let transfers = [
+///     TransactionRequest::new().to(Address::random()).value(1).into(),
+///     TransactionRequest::new().to(Address::random()).value(2).into(),
+/// ];
+/// let nonce = safe.nonce(&provider).await?;
+/// let safe_tx = safe.batch(&transfers, nonce)?;
+/// let signatures = safe_tx.sign(&owners).await?;
+///
+/// let tx = safe.exec_transaction(&safe_tx, signatures)?;
+/// let receipt = provider.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Safe {
+    address: Address,
+    chain_id: U256,
+    multi_send: Address,
+    owner: Option<Address>,
+    contract: BaseContract,
+}
+
+impl Safe {
+    /// Creates a new instance of the Safe deployed at `address` on the chain with `chain_id`.
+    pub fn new(address: Address, chain_id: impl Into<U256>) -> Self {
+        let contract = parse_abi(&[SAFE_EXEC_TRANSACTION, SAFE_NONCE, MULTI_SEND])
+            .expect("could not parse ABI")
+            .into();
+
+        Self {
+            address,
+            chain_id: chain_id.into(),
+            multi_send: MULTI_SEND_CALL_ONLY_ADDRESS,
+            owner: None,
+            contract,
+        }
+    }
+
+    /// Sets the address of the MultiSend contract used to batch transactions, defaults to
+    /// [`MULTI_SEND_CALL_ONLY_ADDRESS`].
+    #[must_use]
+    pub fn multi_send(mut self, multi_send: Address) -> Self {
+        self.multi_send = multi_send;
+        self
+    }
+
+    /// Sets the owner approving transformed transactions which do not specify their sender.
+    #[must_use]
+    pub fn owner(mut self, owner: Address) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// The address of the Safe.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Fetches the nonce of the next transaction executed by the Safe.
+    pub async fn nonce<M: Middleware>(&self, client: &M) -> Result<U256, ContractError<M>> {
+        let tx =
+            TransactionRequest::new().to(self.address).data(self.contract.encode("nonce", ())?);
+        let data =
+            client.call(&tx.into(), None).await.map_err(ContractError::from_middleware_error)?;
+        Ok(self.contract.decode_output("nonce", data)?)
+    }
+
+    /// Returns the Safe transaction executing the call of `tx`.
+    pub fn safe_tx(&self, tx: &TypedTransaction, nonce: U256) -> Result<SafeTx, TransformerError> {
+        let to = *tx.to_addr().ok_or_else(|| TransformerError::MissingField("to".to_string()))?;
+        Ok(SafeTx {
+            safe: self.address,
+            chain_id: self.chain_id,
+            to,
+            value: tx.value().copied().unwrap_or_default(),
+            data: tx.data().cloned().unwrap_or_default(),
+            nonce,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the Safe transaction executing the calls of all `txs`, batched by the MultiSend
+    /// contract.
+    pub fn batch(&self, txs: &[TypedTransaction], nonce: U256) -> Result<SafeTx, TransformerError> {
+        let mut transactions = Vec::new();
+        for tx in txs {
+            let to =
+                tx.to_addr().ok_or_else(|| TransformerError::MissingField("to".to_string()))?;
+            let data = tx.data().cloned().unwrap_or_default();
+            // operation, to, value, data length and data, packed
+            transactions.push(Operation::Call as u8);
+            transactions.extend_from_slice(to.as_bytes());
+            transactions
+                .extend_from_slice(&<[u8; 32]>::from(tx.value().copied().unwrap_or_default()));
+            transactions.extend_from_slice(&<[u8; 32]>::from(U256::from(data.len())));
+            transactions.extend_from_slice(&data);
+        }
+
+        Ok(SafeTx {
+            safe: self.address,
+            chain_id: self.chain_id,
+            to: self.multi_send,
+            data: self.contract.encode("multiSend", Bytes::from(transactions))?,
+            operation: Operation::DelegateCall,
+            nonce,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the transaction executing `safe_tx` with the `signatures` of the owners.
+    pub fn exec_transaction(
+        &self,
+        safe_tx: &SafeTx,
+        signatures: Bytes,
+    ) -> Result<TypedTransaction, TransformerError> {
+        let data = self.exec_transaction_data(safe_tx, signatures)?;
+        Ok(TransactionRequest::new().to(self.address).data(data).into())
+    }
+
+    fn exec_transaction_data(
+        &self,
+        safe_tx: &SafeTx,
+        signatures: Bytes,
+    ) -> Result<Bytes, TransformerError> {
+        Ok(self.contract.encode(
+            "execTransaction",
+            (
+                safe_tx.to,
+                safe_tx.value,
+                safe_tx.data.clone(),
+                safe_tx.operation as u8,
+                safe_tx.safe_tx_gas,
+                safe_tx.base_gas,
+                safe_tx.gas_price,
+                safe_tx.gas_token,
+                safe_tx.refund_receiver,
+                signatures,
+            ),
+        )?)
+    }
+}
+
+/// Returns the signature approving a Safe transaction by `owner` as the sender of the
+/// `execTransaction` transaction
+fn approved_by_sender(owner: Address) -> Bytes {
+    let mut signature = H256::from(owner).as_bytes().to_vec();
+    signature.extend_from_slice(&[0u8; 32]);
+    signature.push(1);
+    signature.into()
+}
+
+impl Transformer for Safe {
+    fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError> {
+        // the sender approves the Safe transaction by sending it, the Safe checks its nonce
+        let owner = tx
+            .from()
+            .copied()
+            .or(self.owner)
+            .ok_or_else(|| TransformerError::MissingField("from".to_string()))?;
+        let safe_tx = self.safe_tx(tx, U256::zero())?;
+        let data = self.exec_transaction_data(&safe_tx, approved_by_sender(owner))?;
+
+        // update appropriate fields of the Safe tx, the Safe sends the value.
+        tx.set_data(data);
+        tx.set_to(self.address);
+        tx.set_value(U256::zero());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::{AbiDecode, ParamType};
+    use ethers_signers::LocalWallet;
+
+    #[test]
+    fn safe_tx_hash() {
+        assert_eq!(
+            H256(SafeTx::type_hash().unwrap()),
+            "0xbb8310d486368db6bd6f849402fdd73ad53d316b5a4b2644ad6efe0f941286d8".parse().unwrap()
+        );
+        assert_eq!(
+            H256(keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")),
+            "0x47e79534a245952e8b16893a336b85a3d9ea9fa8c573f3d803afb92a79469218".parse().unwrap()
+        );
+
+        let safe_tx =
+            SafeTx { safe: Address::repeat_byte(1), chain_id: 1.into(), ..Default::default() };
+        let separator = keccak256(encode(&[
+            Token::FixedBytes(
+                keccak256("EIP712Domain(uint256 chainId,address verifyingContract)").to_vec(),
+            ),
+            Token::Uint(1.into()),
+            Token::Address(Address::repeat_byte(1)),
+        ]));
+        assert_eq!(safe_tx.domain_separator().unwrap(), separator);
+    }
+
+    #[tokio::test]
+    async fn signs_in_owner_order() {
+        let signers =
+            [LocalWallet::new(&mut rand::thread_rng()), LocalWallet::new(&mut rand::thread_rng())];
+        let safe_tx = Safe::new(Address::repeat_byte(1), 1)
+            .safe_tx(
+                &TransactionRequest::new().to(Address::repeat_byte(2)).value(3).into(),
+                4.into(),
+            )
+            .unwrap();
+
+        let signatures = safe_tx.sign(&signers).await.unwrap();
+        assert_eq!(signatures.len(), 130);
+        let owners = signatures
+            .chunks(65)
+            .map(|signature| {
+                ethers_core::types::Signature::try_from(signature)
+                    .unwrap()
+                    .recover(safe_tx.hash())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut expected = signers.iter().map(Signer::address).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(owners, expected);
+    }
+
+    #[test]
+    fn transforms_into_exec_transaction() {
+        let safe = Safe::new(Address::repeat_byte(1), 1);
+        let owner = Address::repeat_byte(2);
+        let target = Address::repeat_byte(3);
+        let mut tx: TypedTransaction =
+            TransactionRequest::new().from(owner).to(target).value(5).data(vec![1, 2]).into();
+
+        safe.transform(&mut tx).unwrap();
+        assert_eq!(tx.to_addr(), Some(&safe.address()));
+        assert_eq!(tx.value(), Some(&U256::zero()));
+
+        let tokens = ethers_core::abi::decode(
+            &[
+                ParamType::Address,
+                ParamType::Uint(256),
+                ParamType::Bytes,
+                ParamType::Uint(8),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Address,
+                ParamType::Address,
+                ParamType::Bytes,
+            ],
+            &tx.data().unwrap()[4..],
+        )
+        .unwrap();
+        assert_eq!(tokens[0], Token::Address(target));
+        assert_eq!(tokens[1], Token::Uint(5.into()));
+        assert_eq!(tokens[2], Token::Bytes(vec![1, 2]));
+        assert_eq!(tokens[9], Token::Bytes(approved_by_sender(owner).to_vec()));
+    }
+
+    #[test]
+    fn batches_with_multi_send() {
+        let safe = Safe::new(Address::repeat_byte(1), 1);
+        let txs = [
+            TransactionRequest::new().to(Address::repeat_byte(2)).value(1).into(),
+            TransactionRequest::new().to(Address::repeat_byte(3)).data(vec![0xab]).into(),
+        ];
+        let safe_tx = safe.batch(&txs, 7.into()).unwrap();
+        assert_eq!(safe_tx.to, MULTI_SEND_CALL_ONLY_ADDRESS);
+        assert_eq!(safe_tx.operation, Operation::DelegateCall);
+        assert_eq!(safe_tx.nonce, 7.into());
+
+        let transactions = Bytes::decode(&safe_tx.data[4..]).unwrap();
+        assert_eq!(transactions.len(), 2 * 85 + 1);
+        assert_eq!(transactions[0], 0);
+        assert_eq!(&transactions[1..21], Address::repeat_byte(2).as_bytes());
+        assert_eq!(U256::from_big_endian(&transactions[21..53]), 1.into());
+        assert_eq!(U256::from_big_endian(&transactions[53..85]), 0.into());
+        assert_eq!(&transactions[86..106], Address::repeat_byte(3).as_bytes());
+        assert_eq!(U256::from_big_endian(&transactions[138..170]), 1.into());
+        assert_eq!(transactions[170], 0xab);
+    }
+}
@@ -0,0 +1,669 @@
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::FileTxStore;
+pub use store::{MemoryTxStore, TxStore, TxStoreError};
+
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, BlockNumber, Eip1559TransactionRequest,
+    TransactionReceipt, TransactionRequest, TxHash, H256, U256, U64,
+};
+use ethers_providers::{Middleware, MiddlewareError, PendingTransaction, RpcErrorKind};
+use futures_util::lock::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The default number of confirmations after which a transaction is final
+pub const DEFAULT_CONFIRMATIONS: u64 = 12;
+
+/// The default percentage by which [`TxManager::speed_up`] and [`TxManager::cancel`] raise the
+/// fees of a transaction
+pub const DEFAULT_FEE_BUMP_PERCENTAGE: u64 = 20;
+
+/// A transaction tracked by the [`TxManager`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedTx {
+    /// The signing hash of the transaction as it was first sent, which identifies it. Unlike the
+    /// transaction hash, it is known before the transaction is signed and broadcast.
+    pub id: TxHash,
+    /// The transaction as it was last sent
+    pub tx: TypedTransaction,
+    /// The hashes of all versions of the transaction that were sent, the latest last. Empty if
+    /// the transaction was recorded but not broadcast yet.
+    pub hashes: Vec<TxHash>,
+    /// The current status
+    pub status: TxStatus,
+    /// Whether the transaction was replaced by a cancellation
+    pub cancelled: bool,
+}
+
+impl ManagedTx {
+    /// The hash of the latest version of the transaction, `None` if it was not broadcast yet
+    pub fn hash(&self) -> Option<TxHash> {
+        self.hashes.last().copied()
+    }
+
+    /// Returns `true` if the transaction will not change its status anymore
+    pub fn is_done(&self) -> bool {
+        matches!(self.status, TxStatus::Final { .. } | TxStatus::Dropped)
+    }
+}
+
+/// The status of a [`ManagedTx`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum TxStatus {
+    /// The transaction was sent but is not included in a block
+    Pending,
+    /// The transaction is included in a block, but not yet final
+    #[serde(rename_all = "camelCase")]
+    Included {
+        /// The hash of the included version
+        hash: TxHash,
+        /// The number of the block
+        block_number: U64,
+        /// The hash of the block
+        block_hash: H256,
+    },
+    /// The transaction is included in a block with enough confirmations
+    #[serde(rename_all = "camelCase")]
+    Final {
+        /// The hash of the included version
+        hash: TxHash,
+        /// The number of the block
+        block_number: U64,
+        /// The hash of the block
+        block_hash: H256,
+    },
+    /// The nonce of the transaction was used by a transaction which is not tracked
+    Dropped,
+}
+
+/// A change of a [`ManagedTx`] observed by [`TxManager::poll`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxEvent {
+    /// The transaction was included in a block
+    Included {
+        /// The id of the transaction
+        id: TxHash,
+        /// The hash of the included version
+        hash: TxHash,
+        /// The number of the block
+        block_number: U64,
+    },
+    /// The block including the transaction was reorged out of the chain
+    Reorged {
+        /// The id of the transaction
+        id: TxHash,
+        /// The hash of the version that was included
+        hash: TxHash,
+    },
+    /// The transaction reached the required number of confirmations
+    Finalized {
+        /// The id of the transaction
+        id: TxHash,
+        /// The receipt of the included version
+        receipt: Box<TransactionReceipt>,
+    },
+    /// The transaction was no longer known to the node and was sent again
+    Rebroadcast {
+        /// The id of the transaction
+        id: TxHash,
+        /// The hash of the sent version
+        hash: TxHash,
+    },
+    /// The nonce of the transaction was used by a transaction which is not tracked
+    Dropped {
+        /// The id of the transaction
+        id: TxHash,
+    },
+}
+
+/// Error thrown by the [`TxManager`]
+#[derive(Debug, Error)]
+pub enum TxManagerError<M: Middleware> {
+    /// Thrown when the internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+
+    /// Thrown when the store fails
+    #[error(transparent)]
+    StoreError(#[from] TxStoreError),
+
+    /// Thrown when no transaction with the given id is tracked
+    #[error("unknown transaction {0:?}")]
+    UnknownTransaction(TxHash),
+
+    /// Thrown when replacing a transaction which is no longer pending
+    #[error("transaction {0:?} is no longer pending")]
+    NotPending(TxHash),
+
+    /// Thrown when the nonce or sender of a transaction can not be determined
+    #[error("the sender and nonce of the transaction must be known")]
+    MissingSender,
+}
+
+impl<M: Middleware> MiddlewareError for TxManagerError<M> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        TxManagerError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            TxManagerError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Middleware owning every transaction sent through it, from its submission to its finality.
+///
+/// Every transaction is recorded in a pluggable [`TxStore`] before it is broadcast, and its hash
+/// is added once the node accepted it. [`TxManager::poll`] checks the state of all tracked
+/// transactions:
+/// - transactions which are no longer known to the node, or were recorded but never broadcast, are
+///   sent again
+/// - included transactions are tracked until they have `confirmations` confirmations
+/// - transactions whose block was reorged out of the chain are tracked as pending again
+///
+/// Pending transactions can be replaced with higher fees by [`TxManager::speed_up`], or by a
+/// 0-value transfer to the sender with the same nonce by [`TxManager::cancel`].
+///
+/// With a store that survives a restart, such as the [`FileTxStore`], a new manager picks up the
+/// transactions of the previous process on its first poll.
+///
+/// The manager should wrap a middleware that signs transactions, such as the
+/// [`SignerMiddleware`](crate::SignerMiddleware), so that resending a transaction reproduces its
+/// hash.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::TransactionRequest;
+/// use ethers_middleware::tx_manager::{FileTxStore, TxEvent, TxManager};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::{convert::TryFrom, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let manager = TxManager::new(provider, FileTxStore::new("txs.json")?).confirmations(6);
+///
+/// let tx = TransactionRequest::pay("vitalik.eth", 100);
+/// let hash = *manager.send_transaction(tx, None).await?;
+/// let id = manager.transaction(hash)?.id;
+///
+/// loop {
+///     for event in manager.poll().await? {
+///         if let TxEvent::Finalized { id: finalized, .. } = event {
+///             if finalized == id {
+///                 return Ok(())
+///             }
+///         }
+///     }
+///     tokio::time::sleep(Duration::from_secs(12)).await;
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TxManager<M, S> {
+    inner: M,
+    store: S,
+    confirmations: u64,
+    fee_bump: u64,
+    /// Serializes the updates of the store
+    lock: Mutex<()>,
+}
+
+impl<M, S> TxManager<M, S>
+where
+    M: Middleware,
+    S: TxStore,
+{
+    /// Creates a new manager recording its transactions in `store`
+    pub fn new(inner: M, store: S) -> Self {
+        Self {
+            inner,
+            store,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            fee_bump: DEFAULT_FEE_BUMP_PERCENTAGE,
+            lock: Default::default(),
+        }
+    }
+
+    /// Sets the number of confirmations after which a transaction is final (default: 12)
+    #[must_use]
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations.max(1);
+        self
+    }
+
+    /// Sets the percentage by which replacements raise the fees (default: 20)
+    #[must_use]
+    pub fn fee_bump(mut self, percentage: u64) -> Self {
+        self.fee_bump = percentage;
+        self
+    }
+
+    /// Returns the store of the manager
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns all tracked transactions
+    pub fn transactions(&self) -> Result<Vec<ManagedTx>, TxManagerError<M>> {
+        Ok(self.store.load()?)
+    }
+
+    /// Returns the transaction with the given id, or with a version with the given hash
+    pub fn transaction(&self, id: TxHash) -> Result<ManagedTx, TxManagerError<M>> {
+        self.store
+            .load()?
+            .into_iter()
+            .find(|tx| tx.id == id || tx.hashes.contains(&id))
+            .ok_or(TxManagerError::UnknownTransaction(id))
+    }
+
+    /// Stops tracking the transaction with the given id, or with a version with the given hash
+    pub async fn remove(&self, id: TxHash) -> Result<(), TxManagerError<M>> {
+        let _lock = self.lock.lock().await;
+        let id = self.transaction(id).map_or(id, |tx| tx.id);
+        Ok(self.store.remove(id)?)
+    }
+
+    /// Checks the state of all tracked transactions which are not final, returning the observed
+    /// changes
+    pub async fn poll(&self) -> Result<Vec<TxEvent>, TxManagerError<M>> {
+        let _lock = self.lock.lock().await;
+        let txs = self.store.load()?.into_iter().filter(|tx| !tx.is_done()).collect::<Vec<_>>();
+        if txs.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let head = self.inner.get_block_number().await.map_err(TxManagerError::from_err)?;
+        let mut events = Vec::new();
+        for mut tx in txs {
+            let before = tx.clone();
+            self.poll_tx(&mut tx, head, &mut events).await?;
+            if tx != before {
+                self.store.save(&tx)?;
+            }
+        }
+        Ok(events)
+    }
+
+    async fn poll_tx(
+        &self,
+        tx: &mut ManagedTx,
+        head: U64,
+        events: &mut Vec<TxEvent>,
+    ) -> Result<(), TxManagerError<M>> {
+        // any of the versions may have been included
+        let mut receipt = None;
+        for hash in tx.hashes.iter().rev() {
+            receipt = self
+                .inner
+                .get_transaction_receipt(*hash)
+                .await
+                .map_err(TxManagerError::from_err)?;
+            if receipt.is_some() {
+                break
+            }
+        }
+
+        match receipt {
+            Some(receipt) if receipt.block_number.is_some() && receipt.block_hash.is_some() => {
+                let hash = receipt.transaction_hash;
+                let block_number = receipt.block_number.unwrap_or_default();
+                let block_hash = receipt.block_hash.unwrap_or_default();
+                match tx.status {
+                    TxStatus::Included { hash: included, block_hash: included_in, .. }
+                        if included_in != block_hash =>
+                    {
+                        events.push(TxEvent::Reorged { id: tx.id, hash: included });
+                        events.push(TxEvent::Included { id: tx.id, hash, block_number });
+                    }
+                    TxStatus::Included { .. } => {}
+                    _ => events.push(TxEvent::Included { id: tx.id, hash, block_number }),
+                }
+
+                if head.saturating_sub(block_number).as_u64() + 1 >= self.confirmations {
+                    tx.status = TxStatus::Final { hash, block_number, block_hash };
+                    events.push(TxEvent::Finalized { id: tx.id, receipt: Box::new(receipt) });
+                } else {
+                    tx.status = TxStatus::Included { hash, block_number, block_hash };
+                }
+            }
+            _ => {
+                if let TxStatus::Included { hash, .. } = tx.status {
+                    events.push(TxEvent::Reorged { id: tx.id, hash });
+                    tx.status = TxStatus::Pending;
+                }
+
+                // make sure the node still knows the transaction
+                let known = match tx.hash() {
+                    Some(hash) => self
+                        .inner
+                        .get_transaction(hash)
+                        .await
+                        .map_err(TxManagerError::from_err)?
+                        .is_some(),
+                    None => false,
+                };
+                if !known {
+                    match self.inner.send_transaction(tx.tx.clone(), None).await {
+                        Ok(pending) => {
+                            let hash = *pending;
+                            tracing::debug!(id = ?tx.id, ?hash, "rebroadcast transaction");
+                            if !tx.hashes.contains(&hash) {
+                                tx.hashes.push(hash);
+                            }
+                            events.push(TxEvent::Rebroadcast { id: tx.id, hash });
+                        }
+                        Err(err) if err.error_kind() == Some(RpcErrorKind::AlreadyKnown) => {}
+                        Err(err) if err.error_kind() == Some(RpcErrorKind::NonceTooLow) => {
+                            tracing::debug!(id = ?tx.id, "nonce used by another transaction");
+                            tx.status = TxStatus::Dropped;
+                            events.push(TxEvent::Dropped { id: tx.id });
+                        }
+                        Err(err) => return Err(TxManagerError::from_err(err)),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the pending transaction with the given id by the same transaction with fees
+    /// raised by the fee bump, returning the hash of the replacement
+    pub async fn speed_up(&self, id: TxHash) -> Result<TxHash, TxManagerError<M>> {
+        let mut replacement = self.pending(id)?.tx;
+        self.bump_fees(&mut replacement);
+        self.replace(id, replacement, false).await
+    }
+
+    /// Replaces the pending transaction with the given id by a 0-value transfer from the sender
+    /// to itself with the same nonce and raised fees, returning the hash of the replacement
+    pub async fn cancel(&self, id: TxHash) -> Result<TxHash, TxManagerError<M>> {
+        let tx = self.pending(id)?.tx;
+        let from = tx.from().copied().ok_or(TxManagerError::MissingSender)?;
+        let nonce = tx.nonce().copied().ok_or(TxManagerError::MissingSender)?;
+
+        let mut replacement: TypedTransaction = match &tx {
+            TypedTransaction::Eip1559(inner) => {
+                let mut replacement = Eip1559TransactionRequest::new()
+                    .from(from)
+                    .to(from)
+                    .value(0)
+                    .nonce(nonce)
+                    .gas(21000);
+                replacement.max_fee_per_gas = inner.max_fee_per_gas;
+                replacement.max_priority_fee_per_gas = inner.max_priority_fee_per_gas;
+                replacement.chain_id = inner.chain_id;
+                replacement.into()
+            }
+            _ => {
+                let mut replacement =
+                    TransactionRequest::new().from(from).to(from).value(0).nonce(nonce).gas(21000);
+                replacement.gas_price = tx.gas_price();
+                replacement.chain_id = tx.chain_id();
+                replacement.into()
+            }
+        };
+        self.bump_fees(&mut replacement);
+        self.replace(id, replacement, true).await
+    }
+
+    fn pending(&self, id: TxHash) -> Result<ManagedTx, TxManagerError<M>> {
+        let tx = self.transaction(id)?;
+        if tx.status != TxStatus::Pending {
+            return Err(TxManagerError::NotPending(id))
+        }
+        Ok(tx)
+    }
+
+    async fn replace(
+        &self,
+        id: TxHash,
+        replacement: TypedTransaction,
+        cancelled: bool,
+    ) -> Result<TxHash, TxManagerError<M>> {
+        let _lock = self.lock.lock().await;
+        let hash = *self
+            .inner
+            .send_transaction(replacement.clone(), None)
+            .await
+            .map_err(TxManagerError::from_err)?;
+
+        // the record may have changed while sending
+        let mut tx = self.transaction(id)?;
+        tx.tx = replacement;
+        tx.hashes.push(hash);
+        tx.cancelled |= cancelled;
+        self.store.save(&tx)?;
+        Ok(hash)
+    }
+
+    fn bump_fees(&self, tx: &mut TypedTransaction) {
+        let bump = |fee: U256| fee + (fee * self.fee_bump + 99) / 100;
+        match tx {
+            TypedTransaction::Eip1559(inner) => {
+                inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+                inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
+            }
+            _ => {
+                if let Some(gas_price) = tx.gas_price() {
+                    tx.set_gas_price(bump(gas_price));
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for TxManager<M, S>
+where
+    M: Middleware,
+    S: TxStore,
+{
+    type Error = TxManagerError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Sends the transaction and starts tracking it. The transaction is filled and recorded
+    /// before it is broadcast, so that it is tracked even if the process stops in between.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if tx.from().is_none() {
+            if let Some(from) = self.inner.default_sender() {
+                tx.set_from(from);
+            }
+        }
+        self.inner.fill_transaction(&mut tx, block).await.map_err(TxManagerError::from_err)?;
+        if tx.nonce().is_none() {
+            let from = tx.from().copied().ok_or(TxManagerError::MissingSender)?;
+            let nonce = self
+                .inner
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(TxManagerError::from_err)?;
+            tx.set_nonce(nonce);
+        }
+
+        let _lock = self.lock.lock().await;
+        let mut managed = ManagedTx {
+            id: tx.sighash(),
+            tx,
+            hashes: Vec::new(),
+            status: TxStatus::Pending,
+            cancelled: false,
+        };
+        self.store.save(&managed)?;
+
+        let pending = match self.inner.send_transaction(managed.tx.clone(), block).await {
+            Ok(pending) => pending,
+            Err(err) => {
+                // keep tracking the transaction if it may have been broadcast
+                if !matches!(err.error_kind(), None | Some(RpcErrorKind::AlreadyKnown)) {
+                    self.store.remove(managed.id)?;
+                }
+                return Err(TxManagerError::from_err(err))
+            }
+        };
+        managed.hashes.push(*pending);
+        self.store.save(&managed)?;
+        Ok(pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, Transaction};
+    use ethers_providers::{JsonRpcError, MockProvider, MockResponse, Provider};
+
+    fn manager() -> (TxManager<Provider<MockProvider>, MemoryTxStore>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        (TxManager::new(provider, MemoryTxStore::new()).confirmations(2), mock)
+    }
+
+    fn tx() -> TransactionRequest {
+        TransactionRequest::new()
+            .from(Address::repeat_byte(1))
+            .to(Address::repeat_byte(2))
+            .gas(21000)
+            .gas_price(100)
+            .nonce(7)
+    }
+
+    fn receipt(hash: TxHash, block_number: u64, block_hash: H256) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: hash,
+            block_number: Some(block_number.into()),
+            block_hash: Some(block_hash),
+            ..Default::default()
+        }
+    }
+
+    /// The id and the hash of `tx()`
+    fn ids() -> (TxHash, TxHash) {
+        (TypedTransaction::Legacy(tx()).sighash(), TxHash::repeat_byte(9))
+    }
+
+    #[tokio::test]
+    async fn tracks_transaction_until_final() {
+        let (manager, mock) = manager();
+        let (id, hash) = ids();
+        // the mock pops from the back
+        mock.push(receipt(hash, 10, H256::repeat_byte(2))).unwrap();
+        mock.push(U64::from(11)).unwrap();
+        mock.push(receipt(hash, 10, H256::repeat_byte(1))).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(hash).unwrap();
+
+        assert_eq!(*manager.send_transaction(tx(), None).await.unwrap(), hash);
+        let managed = manager.transaction(hash).unwrap();
+        assert_eq!((managed.id, managed.status), (id, TxStatus::Pending));
+
+        let events = manager.poll().await.unwrap();
+        assert_eq!(events, vec![TxEvent::Included { id, hash, block_number: 10.into() }]);
+
+        // the block was reorged, but the transaction was included in the new block
+        let events = manager.poll().await.unwrap();
+        assert_eq!(events[0], TxEvent::Reorged { id, hash });
+        assert!(matches!(events[2], TxEvent::Finalized { .. }));
+        assert!(manager.transaction(id).unwrap().is_done());
+
+        // final transactions are not polled anymore
+        assert!(manager.poll().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rebroadcasts_dropped_transaction() {
+        let (manager, mock) = manager();
+        let (id, hash) = ids();
+        mock.push(hash).unwrap();
+        mock.push::<Option<Transaction>, _>(None).unwrap();
+        mock.push::<Option<TransactionReceipt>, _>(None).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(hash).unwrap();
+
+        manager.send_transaction(tx(), None).await.unwrap();
+        let events = manager.poll().await.unwrap();
+        assert_eq!(events, vec![TxEvent::Rebroadcast { id, hash }]);
+
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx())]).unwrap();
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getTransactionReceipt", [hash]).unwrap();
+        mock.assert_request("eth_getTransactionByHash", [hash]).unwrap();
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx())]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn records_transaction_before_broadcast() {
+        let (manager, mock) = manager();
+        let (id, hash) = ids();
+
+        // the mock has no response, so it is unknown whether the transaction was broadcast
+        manager.send_transaction(tx(), None).await.unwrap_err();
+        let managed = manager.transaction(id).unwrap();
+        assert_eq!(managed.tx, TypedTransaction::Legacy(tx()));
+        assert!(managed.hashes.is_empty());
+
+        mock.push(hash).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        let events = manager.poll().await.unwrap();
+        assert_eq!(events, vec![TxEvent::Rebroadcast { id, hash }]);
+        assert_eq!(manager.transaction(id).unwrap().hashes, vec![hash]);
+
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx())]).unwrap();
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx())]).unwrap();
+
+        // transactions rejected by the node are not tracked
+        let (manager, mock) = self::manager();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "insufficient funds for gas * price + value".to_string(),
+            data: None,
+        }));
+        manager.send_transaction(tx(), None).await.unwrap_err();
+        assert!(manager.transactions().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancels_with_self_transfer() {
+        let (manager, mock) = manager();
+        let ((id, hash), cancellation) = (ids(), TxHash::repeat_byte(10));
+        mock.push(cancellation).unwrap();
+        mock.push(hash).unwrap();
+
+        manager.send_transaction(tx(), None).await.unwrap();
+        assert_eq!(manager.cancel(hash).await.unwrap(), cancellation);
+
+        let expected = TransactionRequest::new()
+            .from(Address::repeat_byte(1))
+            .to(Address::repeat_byte(1))
+            .value(0)
+            .nonce(7)
+            .gas(21000)
+            .gas_price(120);
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx())]).unwrap();
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(expected)]).unwrap();
+
+        let tx = manager.transaction(id).unwrap();
+        assert!(tx.cancelled);
+        assert_eq!(tx.hashes, vec![hash, cancellation]);
+    }
+}
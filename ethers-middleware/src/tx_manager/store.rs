@@ -0,0 +1,155 @@
+use super::ManagedTx;
+use auto_impl::auto_impl;
+use ethers_core::types::TxHash;
+use std::{collections::BTreeMap, fmt::Debug, sync::Mutex};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::file_store;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+/// Error thrown by a [`TxStore`]
+#[derive(Debug, Error)]
+pub enum TxStoreError {
+    /// An I/O error of an on-disk store
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The stored transactions could not be (de)serialized
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    /// An error of a custom store
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// A store persisting the transactions managed by the [`TxManager`](super::TxManager).
+///
+/// Records are keyed by their [`ManagedTx::id`]. A store which survives a restart of the process,
+/// such as the [`FileTxStore`], allows the manager to resume tracking its transactions.
+#[auto_impl(&, Box, Arc)]
+pub trait TxStore: Debug + Send + Sync {
+    /// Returns all stored transactions, ordered by their id
+    fn load(&self) -> Result<Vec<ManagedTx>, TxStoreError>;
+
+    /// Stores `tx`, replacing any previous record with the same id
+    fn save(&self, tx: &ManagedTx) -> Result<(), TxStoreError>;
+
+    /// Removes the record with the given id, if any
+    fn remove(&self, id: TxHash) -> Result<(), TxStoreError>;
+}
+
+/// An in-memory [`TxStore`], which loses its transactions when dropped
+#[derive(Debug, Default)]
+pub struct MemoryTxStore {
+    txs: Mutex<BTreeMap<TxHash, ManagedTx>>,
+}
+
+impl MemoryTxStore {
+    /// Creates a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TxStore for MemoryTxStore {
+    fn load(&self) -> Result<Vec<ManagedTx>, TxStoreError> {
+        Ok(self.txs.lock().unwrap().values().cloned().collect())
+    }
+
+    fn save(&self, tx: &ManagedTx) -> Result<(), TxStoreError> {
+        self.txs.lock().unwrap().insert(tx.id, tx.clone());
+        Ok(())
+    }
+
+    fn remove(&self, id: TxHash) -> Result<(), TxStoreError> {
+        self.txs.lock().unwrap().remove(&id);
+        Ok(())
+    }
+}
+
+/// An on-disk [`TxStore`], keeping all transactions in a single JSON file.
+///
+/// The file is rewritten on every change, so the store is meant for the transactions of a single
+/// process, not for a long history.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct FileTxStore {
+    path: PathBuf,
+    txs: Mutex<BTreeMap<TxHash, ManagedTx>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileTxStore {
+    /// Opens the store at `path`, loading the transactions stored by a previous run
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, TxStoreError> {
+        let path = path.into();
+        let txs = match file_store::read(&path)? {
+            Some(content) => serde_json::from_str(&content)?,
+            None => Default::default(),
+        };
+        Ok(Self { path, txs: Mutex::new(txs) })
+    }
+
+    /// The file the transactions are stored in
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    fn write(&self, txs: &BTreeMap<TxHash, ManagedTx>) -> Result<(), TxStoreError> {
+        Ok(file_store::write(&self.path, serde_json::to_vec_pretty(txs)?)?)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TxStore for FileTxStore {
+    fn load(&self) -> Result<Vec<ManagedTx>, TxStoreError> {
+        Ok(self.txs.lock().unwrap().values().cloned().collect())
+    }
+
+    fn save(&self, tx: &ManagedTx) -> Result<(), TxStoreError> {
+        let mut txs = self.txs.lock().unwrap();
+        txs.insert(tx.id, tx.clone());
+        self.write(&txs)
+    }
+
+    fn remove(&self, id: TxHash) -> Result<(), TxStoreError> {
+        let mut txs = self.txs.lock().unwrap();
+        if txs.remove(&id).is_some() {
+            self.write(&txs)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::tx_manager::TxStatus;
+    use ethers_core::types::TransactionRequest;
+
+    #[test]
+    fn file_store_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("txs.json");
+        let tx = ManagedTx {
+            id: TxHash::repeat_byte(1),
+            tx: TransactionRequest::new().nonce(3).into(),
+            hashes: vec![TxHash::repeat_byte(1)],
+            status: TxStatus::Pending,
+            cancelled: false,
+        };
+
+        let store = FileTxStore::new(&path).unwrap();
+        assert!(store.load().unwrap().is_empty());
+        store.save(&tx).unwrap();
+
+        let store = FileTxStore::new(&path).unwrap();
+        assert_eq!(store.load().unwrap(), vec![tx.clone()]);
+        store.remove(tx.id).unwrap();
+        assert!(FileTxStore::new(&path).unwrap().load().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,430 @@
+use async_trait::async_trait;
+use ethers_contract::{AbiError, BaseContract};
+use ethers_core::{
+    abi::parse_abi,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, TransactionRequest,
+        UserOperation, UserOperationRequest, UserOperationV07, ENTRY_POINT_V07, H256, U256,
+    },
+};
+use ethers_providers::{interval, Middleware, MiddlewareError, PendingTransaction, StreamExt};
+use ethers_signers::Signer;
+use instant::Duration;
+use thiserror::Error;
+
+/// The function signature of the EntryPoint's nonce getter.
+const ENTRY_POINT_GET_NONCE: &str =
+    "function getNonce(address sender, uint192 key) public view returns (uint256 nonce)";
+/// The function signature of the smart account's execute function.
+const ACCOUNT_EXECUTE: &str = "function execute(address dest, uint256 value, bytes func)";
+
+/// A signature of the length of an ECDSA signature, which the accounts can recover an address
+/// from, used while estimating the gas of an operation.
+const DUMMY_SIGNATURE: [u8; 65] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x7a, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+    0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+    0x1c,
+];
+
+/// The default time to wait for a sent operation to be included
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The version of an ERC-4337 EntryPoint contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryPointVersion {
+    /// The v0.6 EntryPoint, executing [`UserOperation`]s
+    V06,
+    /// The v0.7 EntryPoint, executing
+    /// [`PackedUserOperation`](ethers_core::types::PackedUserOperation)s
+    V07,
+}
+
+/// Middleware sending transactions as ERC-4337 user operations of a smart account.
+///
+/// Every transaction is turned into a call of the `execute(address,uint256,bytes)` function of
+/// the account, as implemented by the reference `SimpleAccount`. The operation is signed by the
+/// owner of the account, who signs its hash as a message (EIP-191), and is sent to the bundler
+/// which the inner middleware connects to.
+///
+/// [`Middleware::send_transaction`] waits until the operation is included and returns the
+/// transaction which included it. [`UserOperationMiddleware::send_as_user_operation`] returns the
+/// hash of the operation as soon as the bundler accepted it.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::UserOperationMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let bundler = Provider::<Http>::try_from("http://localhost:4337")?;
+/// let owner: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// # let account = Address::random();
+/// let client = UserOperationMiddleware::new(bundler, owner, account);
+///
+/// let tx = TransactionRequest::new().to(Address::random()).value(100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UserOperationMiddleware<M, S> {
+    inner: M,
+    signer: S,
+    account: Address,
+    entry_point: Address,
+    version: EntryPointVersion,
+    factory: Option<(Address, Bytes)>,
+    receipt_timeout: Duration,
+    entry_point_contract: BaseContract,
+    account_contract: BaseContract,
+}
+
+impl<M, S> UserOperationMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    /// Creates a new middleware sending operations of `account`, signed by its owner `signer`,
+    /// to the v0.7 EntryPoint.
+    pub fn new(inner: M, signer: S, account: Address) -> Self {
+        Self {
+            inner,
+            signer,
+            account,
+            entry_point: ENTRY_POINT_V07,
+            version: EntryPointVersion::V07,
+            factory: None,
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            entry_point_contract: parse_abi(&[ENTRY_POINT_GET_NONCE])
+                .expect("could not parse ABI")
+                .into(),
+            account_contract: parse_abi(&[ACCOUNT_EXECUTE]).expect("could not parse ABI").into(),
+        }
+    }
+
+    /// Sets the EntryPoint contract and its version,
+    /// [`ENTRY_POINT_V06`](ethers_core::types::ENTRY_POINT_V06) and [`ENTRY_POINT_V07`] are the
+    /// canonical deployments.
+    #[must_use]
+    pub fn entry_point(mut self, entry_point: Address, version: EntryPointVersion) -> Self {
+        self.entry_point = entry_point;
+        self.version = version;
+        self
+    }
+
+    /// Sets the factory and its calldata deploying the account with its first operation.
+    #[must_use]
+    pub fn factory(mut self, factory: Address, data: impl Into<Bytes>) -> Self {
+        self.factory = Some((factory, data.into()));
+        self
+    }
+
+    /// Sets how long [`Middleware::send_transaction`] waits for operations to be included.
+    #[must_use]
+    pub fn receipt_timeout(mut self, timeout: Duration) -> Self {
+        self.receipt_timeout = timeout;
+        self
+    }
+
+    /// The smart account sending the operations.
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    /// Returns a reference to the owner of the account.
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Builds a user operation executing `tx`, with its gas estimated by the bundler but without
+    /// a signature.
+    pub async fn build_user_operation(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<UserOperationRequest, UserOperationError<M, S>> {
+        let to = *tx.to_addr().ok_or(UserOperationError::NoRecipient)?;
+        let call_data = self.account_contract.encode(
+            "execute",
+            (to, tx.value().copied().unwrap_or_default(), tx.data().cloned().unwrap_or_default()),
+        )?;
+
+        let get_nonce =
+            self.entry_point_contract.encode("getNonce", (self.account, U256::zero()))?;
+        let get_nonce = TransactionRequest::new().to(self.entry_point).data(get_nonce).into();
+        let nonce =
+            self.inner.call(&get_nonce, None).await.map_err(UserOperationError::MiddlewareError)?;
+        let nonce: U256 = self.entry_point_contract.decode_output("getNonce", nonce)?;
+
+        let factory = match &self.factory {
+            Some(factory) => {
+                let code = self
+                    .inner
+                    .get_code(self.account, None)
+                    .await
+                    .map_err(UserOperationError::MiddlewareError)?;
+                code.is_empty().then(|| factory.clone())
+            }
+            None => None,
+        };
+
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(tx) if tx.max_fee_per_gas.is_some() => {
+                let max_fee = tx.max_fee_per_gas.unwrap_or_default();
+                (max_fee, tx.max_priority_fee_per_gas.unwrap_or(max_fee))
+            }
+            _ => match tx.gas_price() {
+                Some(gas_price) => (gas_price, gas_price),
+                None => self
+                    .inner
+                    .estimate_eip1559_fees(None)
+                    .await
+                    .map_err(UserOperationError::MiddlewareError)?,
+            },
+        };
+
+        let mut user_op: UserOperationRequest = match self.version {
+            EntryPointVersion::V06 => UserOperation {
+                sender: self.account,
+                nonce,
+                init_code: factory
+                    .map(|(factory, data)| [factory.as_bytes(), &data].concat().into())
+                    .unwrap_or_default(),
+                call_data,
+                ..Default::default()
+            }
+            .into(),
+            EntryPointVersion::V07 => {
+                let (factory, factory_data) = match factory {
+                    Some((factory, data)) => (Some(factory), Some(data)),
+                    None => (None, None),
+                };
+                UserOperationV07 {
+                    sender: self.account,
+                    nonce,
+                    factory,
+                    factory_data,
+                    call_data,
+                    ..Default::default()
+                }
+                .into()
+            }
+        };
+        user_op
+            .set_fees(max_fee_per_gas, max_priority_fee_per_gas)
+            .set_signature(DUMMY_SIGNATURE.to_vec());
+
+        let estimate = self
+            .inner
+            .estimate_user_operation_gas(user_op.clone(), self.entry_point)
+            .await
+            .map_err(UserOperationError::MiddlewareError)?;
+        user_op.set_gas(&estimate);
+        Ok(user_op)
+    }
+
+    /// Signs the user operation as the owner of the account.
+    pub async fn sign_user_operation(
+        &self,
+        user_op: &mut UserOperationRequest,
+    ) -> Result<(), UserOperationError<M, S>> {
+        let chain_id =
+            self.inner.get_chainid().await.map_err(UserOperationError::MiddlewareError)?;
+        let hash = user_op.hash(self.entry_point, chain_id);
+        let signature =
+            self.signer.sign_message(hash).await.map_err(UserOperationError::SignerError)?;
+        user_op.set_signature(signature.to_vec());
+        Ok(())
+    }
+
+    /// Sends `tx` as a signed user operation, returning the hash of the operation.
+    pub async fn send_as_user_operation(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<H256, UserOperationError<M, S>> {
+        let mut user_op = self.build_user_operation(tx).await?;
+        self.sign_user_operation(&mut user_op).await?;
+        self.inner
+            .send_user_operation(user_op, self.entry_point)
+            .await
+            .map_err(UserOperationError::MiddlewareError)
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the client sends user operations
+pub enum UserOperationError<M: Middleware, S: Signer> {
+    /// Thrown when the internal call to the signer fails
+    #[error("{0}")]
+    SignerError(S::Error),
+
+    /// Thrown when an internal middleware errors
+    #[error("{0}")]
+    MiddlewareError(M::Error),
+
+    /// Thrown when encoding or decoding a contract call fails
+    #[error(transparent)]
+    AbiError(#[from] AbiError),
+
+    /// Thrown if the transaction has no recipient, accounts do not deploy contracts
+    #[error("the transaction has no recipient")]
+    NoRecipient,
+
+    /// Thrown if a sent operation was not included in time
+    #[error("user operation {0:?} was not included")]
+    NotIncluded(H256),
+}
+
+impl<M: Middleware, S: Signer> MiddlewareError for UserOperationError<M, S> {
+    type Inner = M::Error;
+
+    fn from_err(src: M::Error) -> Self {
+        UserOperationError::MiddlewareError(src)
+    }
+
+    fn as_inner(&self) -> Option<&Self::Inner> {
+        match self {
+            UserOperationError::MiddlewareError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for UserOperationMiddleware<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    type Error = UserOperationError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    fn default_sender(&self) -> Option<Address> {
+        Some(self.account)
+    }
+
+    /// Sends the transaction as a user operation and waits until it is included, returning the
+    /// transaction which included it.
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        _: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let user_op_hash = self.send_as_user_operation(&tx.into()).await?;
+
+        let poll_interval = self.provider().get_interval();
+        let polls = (self.receipt_timeout.as_millis() / poll_interval.as_millis().max(1)).max(1);
+        let mut ticks = interval(poll_interval).take(polls as usize);
+        loop {
+            let receipt = self
+                .inner
+                .get_user_operation_receipt(user_op_hash)
+                .await
+                .map_err(UserOperationError::MiddlewareError)?;
+            if let Some(receipt) = receipt {
+                return Ok(PendingTransaction::new(
+                    receipt.receipt.transaction_hash,
+                    self.provider(),
+                ))
+            }
+            if ticks.next().await.is_none() {
+                return Err(UserOperationError::NotIncluded(user_op_hash))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::Token,
+        types::{
+            Eip1559TransactionRequest, TransactionReceipt, UserOperationGasEstimate,
+            UserOperationReceipt, ENTRY_POINT_V06,
+        },
+    };
+    use ethers_providers::Provider;
+    use ethers_signers::LocalWallet;
+
+    #[tokio::test]
+    async fn sends_signed_user_operation() {
+        let (provider, mock) = Provider::mocked();
+        let owner = LocalWallet::new(&mut rand::thread_rng());
+        let account = Address::repeat_byte(1);
+        let client = UserOperationMiddleware::new(provider, owner.clone(), account)
+            .entry_point(ENTRY_POINT_V06, EntryPointVersion::V06);
+        let user_op_hash = H256::repeat_byte(2);
+        let tx_hash = H256::repeat_byte(3);
+
+        // responses are consumed in reverse order
+        mock.push(UserOperationReceipt {
+            user_op_hash,
+            receipt: TransactionReceipt { transaction_hash: tx_hash, ..Default::default() },
+            ..Default::default()
+        })
+        .unwrap();
+        mock.push(user_op_hash).unwrap();
+        mock.push(U256::from(5)).unwrap();
+        mock.push(UserOperationGasEstimate {
+            pre_verification_gas: 10.into(),
+            verification_gas_limit: 20.into(),
+            call_gas_limit: 30.into(),
+            ..Default::default()
+        })
+        .unwrap();
+        let nonce = Bytes::from(ethers_core::abi::encode(&[Token::Uint(7.into())]));
+        mock.push::<Bytes, _>(nonce).unwrap();
+
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::repeat_byte(4))
+            .value(100)
+            .max_fee_per_gas(50)
+            .max_priority_fee_per_gas(2);
+        let pending = client.send_transaction(tx, None).await.unwrap();
+        assert_eq!(*pending, tx_hash);
+
+        let mut expected = UserOperation {
+            sender: account,
+            nonce: 7.into(),
+            call_data: client
+                .account_contract
+                .encode("execute", (Address::repeat_byte(4), U256::from(100), Bytes::default()))
+                .unwrap(),
+            call_gas_limit: 30.into(),
+            verification_gas_limit: 20.into(),
+            pre_verification_gas: 10.into(),
+            max_fee_per_gas: 50.into(),
+            max_priority_fee_per_gas: 2.into(),
+            signature: DUMMY_SIGNATURE.to_vec().into(),
+            ..Default::default()
+        };
+        let mut estimated = expected.clone();
+        estimated.call_gas_limit = 0.into();
+        estimated.verification_gas_limit = 0.into();
+        estimated.pre_verification_gas = 0.into();
+        expected.signature =
+            owner.sign_message(expected.hash(ENTRY_POINT_V06, 5)).await.unwrap().to_vec().into();
+
+        let get_nonce =
+            client.entry_point_contract.encode("getNonce", (account, U256::zero())).unwrap();
+        let get_nonce = TransactionRequest::new().to(ENTRY_POINT_V06).data(get_nonce);
+        mock.assert_request("eth_call", (TypedTransaction::Legacy(get_nonce), "latest")).unwrap();
+        mock.assert_request("eth_estimateUserOperationGas", (estimated, ENTRY_POINT_V06)).unwrap();
+        mock.assert_request("eth_chainId", ()).unwrap();
+        mock.assert_request("eth_sendUserOperation", (expected, ENTRY_POINT_V06)).unwrap();
+        mock.assert_request("eth_getUserOperationReceipt", [user_op_hash]).unwrap();
+    }
+}
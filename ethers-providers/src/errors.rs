@@ -45,6 +45,14 @@ pub trait RpcError: Error + Debug + Send + Sync {
     fn is_serde_error(&self) -> bool {
         self.as_serde_error().is_some()
     }
+
+    /// Classify the underlying error (if any) into a [`RpcErrorKind`]
+    ///
+    /// Returns `None` if the error is neither a JSON-RPC error response nor
+    /// otherwise recognized by the client, e.g. a rate limiting HTTP status.
+    fn error_kind(&self) -> Option<RpcErrorKind> {
+        self.as_error_response().map(JsonRpcError::kind)
+    }
 }
 
 /// [`MiddlewareError`] is a companion trait to [`crate::Middleware`]. It
@@ -131,6 +139,12 @@ pub trait MiddlewareError: Error + Sized + Send + Sync {
     fn is_error_response(&self) -> bool {
         self.as_error_response().is_some()
     }
+
+    /// Classify the underlying error (if any) into a [`RpcErrorKind`], usually
+    /// by traversing the entire middleware stack
+    fn error_kind(&self) -> Option<RpcErrorKind> {
+        self.as_inner()?.error_kind()
+    }
 }
 
 #[derive(Debug, Error)]
@@ -148,6 +162,10 @@ pub enum ProviderError {
     #[error("reverse ens name not pointing to itself: {0}")]
     EnsNotOwned(String),
 
+    /// An error during a CCIP-Read offchain lookup
+    #[error(transparent)]
+    CcipError(#[from] crate::CcipError),
+
     /// Error in underlying lib `serde_json`
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
@@ -193,6 +211,14 @@ impl RpcError for ProviderError {
             _ => None,
         }
     }
+
+    fn error_kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            ProviderError::JsonRpcClientError(e) => e.error_kind(),
+            ProviderError::HTTPError(e) => RpcErrorKind::from_http_status(e.status()?),
+            _ => None,
+        }
+    }
 }
 
 // Do not change these implementations, they are critical to proper middleware
@@ -208,6 +234,10 @@ impl MiddlewareError for ProviderError {
         RpcError::as_serde_error(self)
     }
 
+    fn error_kind(&self) -> Option<RpcErrorKind> {
+        RpcError::error_kind(self)
+    }
+
     fn from_err(e: Self::Inner) -> Self {
         e
     }
@@ -217,3 +247,254 @@ impl MiddlewareError for ProviderError {
         None
     }
 }
+
+/// A normalized classification of the errors returned by nodes and RPC providers.
+///
+/// Clients word the same failure differently, e.g. geth's `nonce too low` is
+/// `OldNonce` in nethermind and `NONCE_TOO_LOW` in besu. The kind is derived
+/// from the error code and message of a [`JsonRpcError`], see
+/// [`JsonRpcError::kind`], and is accessible from any error via
+/// [`RpcError::error_kind`] and [`MiddlewareError::error_kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RpcErrorKind {
+    /// The nonce of the transaction was already used
+    NonceTooLow,
+    /// The nonce of the transaction is too far ahead of the account's nonce
+    NonceTooHigh,
+    /// A transaction with the same nonce is pending, and the fees of the
+    /// replacement are not high enough
+    ReplacementUnderpriced,
+    /// The transaction is already in the mempool
+    AlreadyKnown,
+    /// The account cannot pay for the gas and value of the transaction
+    InsufficientFunds,
+    /// The gas limit is lower than the intrinsic gas of the transaction
+    IntrinsicGasTooLow,
+    /// The gas limit exceeds the block gas limit
+    GasLimitExceeded,
+    /// The gas price or max fee is below the base fee or the node's minimum
+    FeeTooLow,
+    /// The max priority fee is higher than the max fee
+    TipAboveFeeCap,
+    /// The execution ran out of gas, e.g. during gas estimation
+    OutOfGas,
+    /// The execution reverted
+    ExecutionReverted,
+    /// The request was rate limited by the node or provider
+    RateLimited,
+    /// The requested block is not (yet) known to the node, e.g. because of a
+    /// load balancer forwarding the request to a node which is behind
+    BlockNotFound,
+    /// The request exceeded a limit of the node, such as the block range or
+    /// the number of results of `eth_getLogs`
+    LimitExceeded,
+    /// The method does not exist or is not available
+    MethodNotFound,
+    /// The error is not recognized
+    Unknown,
+}
+
+/// Message fragments (lowercased, with `_` replaced by spaces) and the kind they
+/// classify as. More specific fragments must come first.
+const MESSAGE_KINDS: &[(&str, RpcErrorKind)] = &[
+    // geth, erigon, anvil, besu, and ganache ("revert"). Comes first, as the
+    // revert reason may contain any of the other fragments
+    ("revert", RpcErrorKind::ExecutionReverted),
+    // geth, erigon, anvil, besu (`NONCE_TOO_LOW`)
+    ("nonce too low", RpcErrorKind::NonceTooLow),
+    // parity, openethereum
+    ("nonce is too low", RpcErrorKind::NonceTooLow),
+    // nethermind
+    ("oldnonce", RpcErrorKind::NonceTooLow),
+    // arbitrum
+    ("invalid transaction nonce", RpcErrorKind::NonceTooLow),
+    // geth, besu (`NONCE_TOO_HIGH`)
+    ("nonce too high", RpcErrorKind::NonceTooHigh),
+    // nethermind
+    ("noncegap", RpcErrorKind::NonceTooHigh),
+    ("nonce too far in future", RpcErrorKind::NonceTooHigh),
+    // geth, anvil, besu (`REPLACEMENT_UNDERPRICED`)
+    ("replacement transaction underpriced", RpcErrorKind::ReplacementUnderpriced),
+    ("replacement underpriced", RpcErrorKind::ReplacementUnderpriced),
+    // erigon
+    ("could not replace existing tx", RpcErrorKind::ReplacementUnderpriced),
+    // nethermind
+    ("replacementnotallowed", RpcErrorKind::ReplacementUnderpriced),
+    // geth, anvil
+    ("already known", RpcErrorKind::AlreadyKnown),
+    // nethermind
+    ("alreadyknown", RpcErrorKind::AlreadyKnown),
+    // besu
+    ("known transaction", RpcErrorKind::AlreadyKnown),
+    ("transaction already known", RpcErrorKind::AlreadyKnown),
+    // erigon
+    ("already exists", RpcErrorKind::AlreadyKnown),
+    // parity
+    ("same hash was already imported", RpcErrorKind::AlreadyKnown),
+    // geth, erigon, anvil
+    ("insufficient funds", RpcErrorKind::InsufficientFunds),
+    // nethermind
+    ("insufficientfunds", RpcErrorKind::InsufficientFunds),
+    // besu
+    ("upfront cost exceeds", RpcErrorKind::InsufficientFunds),
+    // geth, erigon, anvil
+    ("intrinsic gas too low", RpcErrorKind::IntrinsicGasTooLow),
+    // nethermind
+    ("intrinsicgastoolow", RpcErrorKind::IntrinsicGasTooLow),
+    // besu
+    ("intrinsic gas exceeds gas limit", RpcErrorKind::IntrinsicGasTooLow),
+    // geth, besu
+    ("exceeds block gas limit", RpcErrorKind::GasLimitExceeded),
+    // nethermind
+    ("gaslimitexceeded", RpcErrorKind::GasLimitExceeded),
+    // geth, anvil, besu
+    ("max priority fee per gas higher than max fee per gas", RpcErrorKind::TipAboveFeeCap),
+    ("max priority fee per gas exceeds max fee per gas", RpcErrorKind::TipAboveFeeCap),
+    // geth, erigon, anvil
+    ("max fee per gas less than block base fee", RpcErrorKind::FeeTooLow),
+    ("transaction underpriced", RpcErrorKind::FeeTooLow),
+    // besu
+    ("gas price too low", RpcErrorKind::FeeTooLow),
+    ("gas price below configured minimum", RpcErrorKind::FeeTooLow),
+    // nethermind
+    ("feetoolow", RpcErrorKind::FeeTooLow),
+    // geth gas estimation
+    ("gas required exceeds allowance", RpcErrorKind::OutOfGas),
+    ("out of gas", RpcErrorKind::OutOfGas),
+    // infura, alchemy, quicknode and others
+    ("rate limit", RpcErrorKind::RateLimited),
+    ("too many requests", RpcErrorKind::RateLimited),
+    ("request limit reached", RpcErrorKind::RateLimited),
+    ("daily request count exceeded", RpcErrorKind::RateLimited),
+    ("compute units per second capacity", RpcErrorKind::RateLimited),
+    // infura, load balancers
+    ("header not found", RpcErrorKind::BlockNotFound),
+    ("unknown block", RpcErrorKind::BlockNotFound),
+    ("block not found", RpcErrorKind::BlockNotFound),
+    // `eth_getLogs` limits of geth, erigon, infura, alchemy and others
+    ("query returned more than", RpcErrorKind::LimitExceeded),
+    ("response size exceeded", RpcErrorKind::LimitExceeded),
+    ("response size should not greater than", RpcErrorKind::LimitExceeded),
+    ("block range", RpcErrorKind::LimitExceeded),
+    ("limit exceeded", RpcErrorKind::LimitExceeded),
+    ("method not found", RpcErrorKind::MethodNotFound),
+    ("does not exist/is not available", RpcErrorKind::MethodNotFound),
+];
+
+impl RpcErrorKind {
+    /// Classify a JSON-RPC error by its code and message
+    pub fn classify(code: i64, message: &str) -> Self {
+        match code {
+            // EIP-1474 / EIP-3085 execution error, its data is the revert data
+            3 => return RpcErrorKind::ExecutionReverted,
+            // alchemy
+            429 => return RpcErrorKind::RateLimited,
+            -32601 => return RpcErrorKind::MethodNotFound,
+            _ => {}
+        }
+
+        let message = message.to_lowercase().replace('_', " ");
+        if let Some((_, kind)) =
+            MESSAGE_KINDS.iter().find(|(fragment, _)| message.contains(fragment))
+        {
+            return *kind
+        }
+
+        match code {
+            // infura's `exceeded project rate limit`
+            -32005 => RpcErrorKind::RateLimited,
+            _ => RpcErrorKind::Unknown,
+        }
+    }
+
+    /// Classify an HTTP status code, returns `None` if the status does not
+    /// indicate a recognized error
+    pub fn from_http_status(status: http::StatusCode) -> Option<Self> {
+        (status == http::StatusCode::TOO_MANY_REQUESTS).then_some(RpcErrorKind::RateLimited)
+    }
+
+    /// Returns `true` if the kind relates to the nonce of the transaction
+    pub fn is_nonce_error(&self) -> bool {
+        matches!(self, RpcErrorKind::NonceTooLow | RpcErrorKind::NonceTooHigh)
+    }
+
+    /// Returns `true` if the kind relates to the fees of the transaction, and
+    /// resending it with higher fees may succeed
+    pub fn is_fee_error(&self) -> bool {
+        matches!(self, RpcErrorKind::ReplacementUnderpriced | RpcErrorKind::FeeTooLow)
+    }
+
+    /// Returns `true` if the same request may succeed when retried later
+    pub fn is_transient(&self) -> bool {
+        matches!(self, RpcErrorKind::RateLimited | RpcErrorKind::BlockNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_client_dialects() {
+        let cases = [
+            (-32000, "nonce too low", RpcErrorKind::NonceTooLow),
+            (
+                -32010,
+                "Transaction nonce is too low. Try incrementing the nonce.",
+                RpcErrorKind::NonceTooLow,
+            ),
+            (
+                -32010,
+                "OldNonce, Current nonce: 5, nonce of rejected tx: 4",
+                RpcErrorKind::NonceTooLow,
+            ),
+            (-32001, "NONCE_TOO_LOW", RpcErrorKind::NonceTooLow),
+            (-32000, "nonce too high", RpcErrorKind::NonceTooHigh),
+            (-32000, "replacement transaction underpriced", RpcErrorKind::ReplacementUnderpriced),
+            (-32000, "REPLACEMENT_UNDERPRICED", RpcErrorKind::ReplacementUnderpriced),
+            (-32000, "already known", RpcErrorKind::AlreadyKnown),
+            (-32010, "AlreadyKnown", RpcErrorKind::AlreadyKnown),
+            (-32000, "insufficient funds for gas * price + value", RpcErrorKind::InsufficientFunds),
+            (-32004, "Upfront cost exceeds account balance", RpcErrorKind::InsufficientFunds),
+            (-32000, "intrinsic gas too low", RpcErrorKind::IntrinsicGasTooLow),
+            (-32000, "exceeds block gas limit", RpcErrorKind::GasLimitExceeded),
+            (-32000, "max fee per gas less than block base fee", RpcErrorKind::FeeTooLow),
+            (-32000, "transaction underpriced", RpcErrorKind::FeeTooLow),
+            (
+                -32000,
+                "max priority fee per gas higher than max fee per gas",
+                RpcErrorKind::TipAboveFeeCap,
+            ),
+            (-32000, "gas required exceeds allowance (30000000)", RpcErrorKind::OutOfGas),
+            (3, "execution reverted: nonce too low", RpcErrorKind::ExecutionReverted),
+            (
+                -32015,
+                "VM Exception while processing transaction: revert",
+                RpcErrorKind::ExecutionReverted,
+            ),
+            (429, "Too many requests", RpcErrorKind::RateLimited),
+            (-32005, "project ID request rate exceeded", RpcErrorKind::RateLimited),
+            (-32000, "header not found", RpcErrorKind::BlockNotFound),
+            (-32005, "query returned more than 10000 results", RpcErrorKind::LimitExceeded),
+            (
+                -32601,
+                "the method eth_foo does not exist/is not available",
+                RpcErrorKind::MethodNotFound,
+            ),
+            (-32000, "something else", RpcErrorKind::Unknown),
+        ];
+        for (code, message, kind) in cases {
+            assert_eq!(RpcErrorKind::classify(code, message), kind, "{message}");
+        }
+    }
+
+    #[test]
+    fn kind_is_reachable_from_provider_error() {
+        let err: ProviderError =
+            JsonRpcError { code: -32000, message: "nonce too low".to_string(), data: None }.into();
+        assert_eq!(RpcError::error_kind(&err), Some(RpcErrorKind::NonceTooLow));
+        assert_eq!(MiddlewareError::error_kind(&err), Some(RpcErrorKind::NonceTooLow));
+        assert_eq!(RpcError::error_kind(&ProviderError::UnsupportedRPC), None);
+    }
+}
@@ -0,0 +1,340 @@
+//! [CCIP-Read](https://eips.ethereum.org/EIPS/eip-3668) offchain lookup support
+//!
+//! Contracts implementing CCIP-Read revert `eth_call`s with an `OffchainLookup` error, asking the
+//! client to fetch the answer from one of a list of gateway URLs and to pass it back to the
+//! contract through a callback function.
+use async_trait::async_trait;
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{Address, Bytes, Selector},
+};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::{fmt::Debug, sync::Arc};
+use thiserror::Error;
+
+/// `OffchainLookup(address,string[],bytes,bytes4,bytes)`
+pub const OFFCHAIN_LOOKUP_SELECTOR: Selector = [85, 111, 24, 48];
+
+/// The default maximum number of offchain lookups performed for a single call
+pub const DEFAULT_MAX_REDIRECTS: u8 = 4;
+
+/// The HTTP client shared by all default [`HttpCcipFetcher`]s
+static DEFAULT_CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+/// An error during a CCIP-Read offchain lookup
+#[derive(Debug, Error)]
+pub enum CcipError {
+    /// The gateway rejected the request with a 4xx status. Per EIP-3668 the lookup is aborted
+    /// without trying the remaining gateways
+    #[error("gateway `{url}` rejected the request ({status}): {message}")]
+    GatewayRejected {
+        /// The gateway URL
+        url: String,
+        /// The HTTP status returned by the gateway
+        status: u16,
+        /// The error message returned by the gateway
+        message: String,
+    },
+
+    /// The gateway could not be reached or returned an unusable response
+    #[error("gateway `{url}` failed: {message}")]
+    GatewayFailed {
+        /// The gateway URL
+        url: String,
+        /// A description of the failure
+        message: String,
+    },
+
+    /// The `OffchainLookup` revert did not list any gateway URLs
+    #[error("offchain lookup did not specify any gateway urls")]
+    NoGateways,
+
+    /// The `sender` of the `OffchainLookup` is not the contract which was called
+    #[error("offchain lookup sender {sender:?} does not match the called contract {to:?}")]
+    SenderMismatch {
+        /// The sender encoded in the `OffchainLookup` revert
+        sender: Address,
+        /// The address of the called contract
+        to: Option<Address>,
+    },
+
+    /// The call kept reverting with `OffchainLookup` after the maximum number of redirects
+    #[error("too many offchain lookup redirects (max: {0})")]
+    TooManyRedirects(u8),
+}
+
+/// The decoded `OffchainLookup(address,string[],bytes,bytes4,bytes)` revert
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OffchainLookup {
+    /// The contract which reverted
+    pub sender: Address,
+    /// The gateway URL templates
+    pub urls: Vec<String>,
+    /// The data to send to the gateway
+    pub call_data: Bytes,
+    /// The selector of the function to call with the gateway's response
+    pub callback_function: Selector,
+    /// Data to pass back to the callback function
+    pub extra_data: Bytes,
+}
+
+impl OffchainLookup {
+    /// Decodes an `OffchainLookup` revert from the provided revert data. Returns `None` if the data
+    /// is not an `OffchainLookup` revert.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() < 4 || data[..4] != OFFCHAIN_LOOKUP_SELECTOR {
+            return None
+        }
+        let mut tokens = abi::decode(
+            &[
+                ParamType::Address,
+                ParamType::Array(Box::new(ParamType::String)),
+                ParamType::Bytes,
+                ParamType::FixedBytes(4),
+                ParamType::Bytes,
+            ],
+            &data[4..],
+        )
+        .ok()?
+        .into_iter();
+
+        let sender = tokens.next()?.into_address()?;
+        let urls = tokens
+            .next()?
+            .into_array()?
+            .into_iter()
+            .map(Token::into_string)
+            .collect::<Option<Vec<_>>>()?;
+        let call_data = tokens.next()?.into_bytes()?.into();
+        let callback_function = tokens.next()?.into_fixed_bytes()?.try_into().ok()?;
+        let extra_data = tokens.next()?.into_bytes()?.into();
+
+        Some(Self { sender, urls, call_data, callback_function, extra_data })
+    }
+
+    /// Returns the calldata for the callback function, i.e.
+    /// `callbackFunction(response, extraData)`
+    pub fn callback_data(&self, response: &Bytes) -> Bytes {
+        let params =
+            abi::encode(&[Token::Bytes(response.to_vec()), Token::Bytes(self.extra_data.to_vec())]);
+        [&self.callback_function[..], &params].concat().into()
+    }
+}
+
+/// Performs the request to a single CCIP-Read gateway.
+///
+/// The default implementation is [`HttpCcipFetcher`]. A custom fetcher can be provided to the
+/// [`Provider`](crate::Provider) via [`CcipRead::new`], e.g. to route requests through a proxy
+/// or to serve them from a local gateway in tests.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CcipFetcher: Debug + Send + Sync {
+    /// Fetches the response for `data` from the gateway at `url`. The `url` is the unmodified
+    /// template from the `OffchainLookup` revert, which may contain the `{sender}` and `{data}`
+    /// parameters.
+    async fn fetch(&self, url: &str, sender: Address, data: &Bytes) -> Result<Bytes, CcipError>;
+}
+
+/// A [`CcipFetcher`] which queries the gateways over HTTP, following EIP-3668: a `GET` request is
+/// made if the URL contains the `{data}` parameter, a `POST` request otherwise.
+#[derive(Clone, Debug)]
+pub struct HttpCcipFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpCcipFetcher {
+    /// Creates a new fetcher using the provided HTTP client
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Default for HttpCcipFetcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_CLIENT.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct GatewayResponse {
+    data: Bytes,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CcipFetcher for HttpCcipFetcher {
+    async fn fetch(&self, url: &str, sender: Address, data: &Bytes) -> Result<Bytes, CcipError> {
+        let sender = format!("{sender:?}");
+        let data = data.to_string();
+        let href = url.replace("{sender}", &sender).replace("{data}", &data);
+        let failed = |message: String| CcipError::GatewayFailed { url: href.clone(), message };
+
+        let request = if url.contains("{data}") {
+            self.client.get(&href)
+        } else {
+            self.client.post(&href).json(&serde_json::json!({ "data": data, "sender": sender }))
+        };
+        let response = request.send().await.map_err(|err| failed(err.to_string()))?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(|err| failed(err.to_string()))?;
+        if status.is_client_error() {
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|value| value.get("message")?.as_str().map(String::from))
+                .unwrap_or(body);
+            return Err(CcipError::GatewayRejected { url: href, status: status.as_u16(), message })
+        }
+        if !status.is_success() {
+            return Err(failed(format!("unexpected status {status}")))
+        }
+
+        let response: GatewayResponse =
+            serde_json::from_str(&body).map_err(|err| failed(err.to_string()))?;
+        Ok(response.data)
+    }
+}
+
+/// CCIP-Read configuration of a [`Provider`](crate::Provider)
+#[derive(Clone, Debug)]
+pub struct CcipRead {
+    fetcher: Arc<dyn CcipFetcher>,
+    max_redirects: u8,
+}
+
+impl Default for CcipRead {
+    fn default() -> Self {
+        Self::new(HttpCcipFetcher::default())
+    }
+}
+
+impl CcipRead {
+    /// Creates a new configuration using the provided fetcher
+    pub fn new(fetcher: impl CcipFetcher + 'static) -> Self {
+        Self { fetcher: Arc::new(fetcher), max_redirects: DEFAULT_MAX_REDIRECTS }
+    }
+
+    /// Sets the maximum number of offchain lookups performed for a single call
+    /// (default: 4)
+    #[must_use]
+    pub fn max_redirects(mut self, max_redirects: u8) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Returns the maximum number of offchain lookups performed for a single call
+    pub fn get_max_redirects(&self) -> u8 {
+        self.max_redirects
+    }
+
+    /// Fetches the response to the lookup from its gateways, trying them in order.
+    ///
+    /// Gateways which fail are skipped, while a gateway rejecting the request aborts the lookup.
+    pub async fn fetch(&self, lookup: &OffchainLookup) -> Result<Bytes, CcipError> {
+        let mut error = CcipError::NoGateways;
+        for url in &lookup.urls {
+            match self.fetcher.fetch(url, lookup.sender, &lookup.call_data).await {
+                Ok(response) => return Ok(response),
+                Err(err @ CcipError::GatewayRejected { .. }) => return Err(err),
+                Err(err) => {
+                    tracing::debug!(?err, url, "CCIP-Read gateway failed");
+                    error = err
+                }
+            }
+        }
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashMap, sync::Mutex};
+
+    #[derive(Debug, Default)]
+    struct MockFetcher {
+        responses: Mutex<HashMap<String, Result<Bytes, CcipError>>>,
+    }
+
+    #[async_trait]
+    impl CcipFetcher for MockFetcher {
+        async fn fetch(&self, url: &str, _: Address, _: &Bytes) -> Result<Bytes, CcipError> {
+            self.responses.lock().unwrap().remove(url).unwrap_or(Err(CcipError::NoGateways))
+        }
+    }
+
+    fn lookup(urls: &[&str]) -> OffchainLookup {
+        OffchainLookup {
+            sender: Address::repeat_byte(0x11),
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            call_data: vec![1, 2, 3].into(),
+            callback_function: [0xde, 0xad, 0xbe, 0xef],
+            extra_data: vec![4, 5].into(),
+        }
+    }
+
+    #[test]
+    fn test_decode_offchain_lookup() {
+        let expected = lookup(&["https://example.com/{sender}/{data}.json"]);
+        let data = [
+            &OFFCHAIN_LOOKUP_SELECTOR[..],
+            &abi::encode(&[
+                Token::Address(expected.sender),
+                Token::Array(vec![Token::String(expected.urls[0].clone())]),
+                Token::Bytes(expected.call_data.to_vec()),
+                Token::FixedBytes(expected.callback_function.to_vec()),
+                Token::Bytes(expected.extra_data.to_vec()),
+            ]),
+        ]
+        .concat();
+
+        assert_eq!(OffchainLookup::decode(&data), Some(expected));
+        assert_eq!(OffchainLookup::decode(&data[1..]), None);
+        assert_eq!(OffchainLookup::decode(&data[..4]), None);
+    }
+
+    #[test]
+    fn test_callback_data() {
+        let lookup = lookup(&[]);
+        let data = lookup.callback_data(&vec![0xaa].into());
+        assert_eq!(data[..4], lookup.callback_function);
+        let tokens = abi::decode(&[ParamType::Bytes, ParamType::Bytes], &data[4..]).unwrap();
+        assert_eq!(tokens, vec![Token::Bytes(vec![0xaa]), Token::Bytes(vec![4, 5])]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tries_gateways_in_order() {
+        let fetcher = MockFetcher::default();
+        {
+            let mut responses = fetcher.responses.lock().unwrap();
+            responses.insert(
+                "a".to_string(),
+                Err(CcipError::GatewayFailed { url: "a".to_string(), message: "down".to_string() }),
+            );
+            responses.insert("b".to_string(), Ok(vec![0xbb].into()));
+        }
+        let ccip = CcipRead::new(fetcher);
+        assert_eq!(ccip.fetch(&lookup(&["a", "b"])).await.unwrap(), Bytes::from(vec![0xbb]));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_aborts_on_rejection() {
+        let fetcher = MockFetcher::default();
+        {
+            let mut responses = fetcher.responses.lock().unwrap();
+            responses.insert(
+                "a".to_string(),
+                Err(CcipError::GatewayRejected {
+                    url: "a".to_string(),
+                    status: 404,
+                    message: "not found".to_string(),
+                }),
+            );
+            responses.insert("b".to_string(), Ok(vec![0xbb].into()));
+        }
+        let ccip = CcipRead::new(fetcher);
+        let err = ccip.fetch(&lookup(&["a", "b"])).await.unwrap_err();
+        assert!(matches!(err, CcipError::GatewayRejected { status: 404, .. }));
+    }
+}
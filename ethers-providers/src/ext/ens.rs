@@ -1,6 +1,7 @@
 //! [Ethereum Name Service](https://docs.ens.domains/) support
 //! Adapted from <https://github.com/hhatto/rust-ens/blob/master/src/lib.rs>
 use ethers_core::{
+    abi::{self, Token},
     types::{Address, NameOrAddress, Selector, TransactionRequest, H160, H256},
     utils::keccak256,
 };
@@ -31,6 +32,10 @@ pub const FIELD_SELECTOR: Selector = [89, 209, 212, 60];
 /// supportsInterface(bytes4 interfaceID)
 pub const INTERFACE_SELECTOR: Selector = [1, 255, 201, 167];
 
+/// resolve(bytes,bytes), which is also the interface id of extended resolvers
+/// ([ENSIP-10](https://docs.ens.domains/ensip/10))
+pub const EXTENDED_RESOLVER_SELECTOR: Selector = [144, 97, 185, 35];
+
 /// Returns a transaction request for calling the `resolver` method on the ENS server
 pub fn get_resolver<T: Into<NameOrAddress>>(ens_address: T, name: &str) -> TransactionRequest {
     // keccak256('resolver(bytes32)')
@@ -70,6 +75,49 @@ pub fn resolve<T: Into<NameOrAddress>>(
     }
 }
 
+/// Returns a transaction request for calling `resolve(bytes,bytes)` on an extended resolver
+/// ([ENSIP-10](https://docs.ens.domains/ensip/10)), wrapping the call to `selector`
+pub fn resolve_extended<T: Into<NameOrAddress>>(
+    resolver_address: T,
+    selector: Selector,
+    name: &str,
+    parameters: Option<&[u8]>,
+) -> TransactionRequest {
+    let call = [&selector[..], &namehash(name).0, parameters.unwrap_or_default()].concat();
+    let params = abi::encode(&[Token::Bytes(dns_encode(name)), Token::Bytes(call)]);
+    let data = [&EXTENDED_RESOLVER_SELECTOR[..], &params].concat();
+    TransactionRequest {
+        data: Some(data.into()),
+        to: Some(resolver_address.into()),
+        ..Default::default()
+    }
+}
+
+/// Returns the parent of an ENS name, e.g. `eth` for `foo.eth`, or `None` for a top-level name.
+pub fn parent(name: &str) -> Option<&str> {
+    name.split_once('.').map(|(_, parent)| parent)
+}
+
+/// Returns the DNS wire format encoding of an ENS name, as used by extended resolvers.
+///
+/// Labels longer than 255 bytes are replaced by their hash, encoded as `[<labelhash>]`.
+pub fn dns_encode(name: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let hashed;
+        let label = if label.len() > 255 {
+            hashed = format!("[{}]", hex::encode(keccak256(label.as_bytes())));
+            &hashed
+        } else {
+            label
+        };
+        encoded.push(label.len() as u8);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    encoded
+}
+
 /// Returns the reverse-registrar name of an address.
 pub fn reverse_address(addr: Address) -> String {
     format!("{addr:?}.{ENS_REVERSE_REGISTRAR_DOMAIN}")[2..].to_string()
@@ -128,6 +176,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dns_encode() {
+        assert_eq!(dns_encode(""), vec![0]);
+        assert_eq!(dns_encode("eth"), b"\x03eth\x00".to_vec());
+        assert_eq!(dns_encode("foo.cb.id"), b"\x03foo\x02cb\x02id\x00".to_vec());
+
+        let long = "a".repeat(256);
+        let encoded = dns_encode(&format!("{long}.eth"));
+        assert_eq!(encoded[0], 66);
+        assert_eq!(encoded[1], b'[');
+        assert_eq!(&encoded[67..], b"\x03eth\x00");
+    }
+
+    #[test]
+    fn test_parent() {
+        assert_eq!(parent("foo.cb.id"), Some("cb.id"));
+        assert_eq!(parent("cb.id"), Some("id"));
+        assert_eq!(parent("id"), None);
+    }
+
     #[test]
     fn test_parametershash() {
         assert_eq!(
@@ -5,6 +5,9 @@ pub use admin::{NodeInfo, PeerInfo};
 pub mod ens;
 pub use ens::*;
 
+pub mod ccip;
+pub use ccip::{CcipError, CcipFetcher, CcipRead, HttpCcipFetcher, OffchainLookup};
+
 pub mod erc;
 
 #[cfg(feature = "dev-rpc")]
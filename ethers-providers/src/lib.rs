@@ -19,12 +19,14 @@ pub use utils::{interval, maybe, EscalationPolicy};
 
 /// Errors
 mod errors;
-pub use errors::{MiddlewareError, ProviderError, RpcError};
+pub use errors::{MiddlewareError, ProviderError, RpcError, RpcErrorKind};
 
 mod stream;
 pub use futures_util::StreamExt;
 pub use stream::{
-    tx_stream::TransactionStream, FilterWatcher, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL,
+    reorg::{ChainBlock, ReorgEvent, ReorgStream, ReorgTracker, DEFAULT_REORG_WINDOW},
+    tx_stream::TransactionStream,
+    FilterWatcher, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL,
 };
 
 mod middleware;
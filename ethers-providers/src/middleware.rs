@@ -869,6 +869,57 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().parity_block_receipts(block).await.map_err(MiddlewareError::from_err)
     }
 
+    // ERC-4337 bundler namespace
+
+    /// Sends a user operation to the bundler, returning the hash of the operation.
+    ///
+    /// Ref: [Here](https://eips.ethereum.org/EIPS/eip-4337#eth_senduseroperation)
+    async fn send_user_operation<U: Into<UserOperationRequest> + Send + Sync>(
+        &self,
+        user_op: U,
+        entry_point: Address,
+    ) -> Result<H256, Self::Error> {
+        self.inner()
+            .send_user_operation(user_op, entry_point)
+            .await
+            .map_err(MiddlewareError::from_err)
+    }
+
+    /// Estimates the gas limits of a user operation. The signature of the operation is not
+    /// verified, but must have the length of a valid signature.
+    ///
+    /// Ref: [Here](https://eips.ethereum.org/EIPS/eip-4337#eth_estimateuseroperationgas)
+    async fn estimate_user_operation_gas<U: Into<UserOperationRequest> + Send + Sync>(
+        &self,
+        user_op: U,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate, Self::Error> {
+        self.inner()
+            .estimate_user_operation_gas(user_op, entry_point)
+            .await
+            .map_err(MiddlewareError::from_err)
+    }
+
+    /// Returns the receipt of a user operation, or `None` if it was not included yet.
+    ///
+    /// Ref: [Here](https://eips.ethereum.org/EIPS/eip-4337#eth_getuseroperationreceipt)
+    async fn get_user_operation_receipt(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, Self::Error> {
+        self.inner()
+            .get_user_operation_receipt(user_op_hash)
+            .await
+            .map_err(MiddlewareError::from_err)
+    }
+
+    /// Returns the EntryPoint contracts supported by the bundler.
+    ///
+    /// Ref: [Here](https://eips.ethereum.org/EIPS/eip-4337#eth_supportedentrypoints)
+    async fn supported_entry_points(&self) -> Result<Vec<Address>, Self::Error> {
+        self.inner().supported_entry_points().await.map_err(MiddlewareError::from_err)
+    }
+
     /// Create a new subscription
     ///
     /// This method is hidden as subscription lifecycles are intended to be
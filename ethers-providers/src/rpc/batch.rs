@@ -0,0 +1,251 @@
+use crate::{BatchRequest, JsonRpcClient, JsonRpcError, Provider, ProviderError};
+use ethers_core::{
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Block, BlockId, BlockNumber, Bytes,
+        Filter, Log, Transaction, TransactionReceipt, TxHash, H256, U256, U64,
+    },
+    utils,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fmt, marker::PhantomData};
+
+/// A batch of typed requests, sent together with [`Batch::send`].
+///
+/// Every queued request returns a [`BatchCall`] handle, which is used to get its typed result
+/// from the [`BatchResponse`]. Unlike the methods of the [`Middleware`](crate::Middleware), the
+/// requests do not resolve ENS names.
+#[must_use = "batches do nothing unless sent"]
+#[derive(Debug)]
+pub struct Batch<'a, P> {
+    provider: &'a Provider<P>,
+    requests: Vec<BatchRequest>,
+}
+
+/// A handle to the result of a request queued in a [`Batch`]
+pub struct BatchCall<R> {
+    index: usize,
+    _response: PhantomData<fn() -> R>,
+}
+
+impl<R> BatchCall<R> {
+    /// The position of the request in the batch
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<R> Clone for BatchCall<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for BatchCall<R> {}
+
+impl<R> fmt::Debug for BatchCall<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchCall").field("index", &self.index).finish()
+    }
+}
+
+/// The responses of a sent [`Batch`]
+#[derive(Clone, Debug)]
+pub struct BatchResponse {
+    responses: Vec<Result<Value, JsonRpcError>>,
+}
+
+impl BatchResponse {
+    /// Returns the typed result of the request, or the error response returned for it
+    pub fn get<R: DeserializeOwned>(&self, call: BatchCall<R>) -> Result<R, ProviderError> {
+        match self.responses.get(call.index) {
+            Some(Ok(value)) => Ok(R::deserialize(value)?),
+            Some(Err(err)) => Err(err.clone().into()),
+            None => Err(ProviderError::CustomError(format!(
+                "no response for request {} of the batch",
+                call.index
+            ))),
+        }
+    }
+
+    /// Returns the untyped responses in the order of the requests
+    pub fn into_inner(self) -> Vec<Result<Value, JsonRpcError>> {
+        self.responses
+    }
+}
+
+impl<'a, P: JsonRpcClient> Batch<'a, P> {
+    /// Creates an empty batch sent via `provider`
+    pub fn new(provider: &'a Provider<P>) -> Self {
+        Self { provider, requests: Vec::new() }
+    }
+
+    /// Returns the number of queued requests
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns `true` if no requests are queued
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Queues a request of any method, whose result is deserialized as `R`
+    pub fn add<R: DeserializeOwned, T: Serialize>(
+        &mut self,
+        method: &str,
+        params: T,
+    ) -> BatchCall<R> {
+        self.requests.push(BatchRequest::new(method, params));
+        BatchCall { index: self.requests.len() - 1, _response: PhantomData }
+    }
+
+    /// Sends all queued requests with [`JsonRpcClient::request_batch`].
+    ///
+    /// The HTTP transport sends them as a single JSON-RPC batch, while the socket transports send
+    /// them as concurrent requests.
+    pub async fn send(self) -> Result<BatchResponse, ProviderError> {
+        let responses = self.provider.request_batch(&self.requests).await?;
+        Ok(BatchResponse { responses })
+    }
+
+    /// Queues `eth_blockNumber`
+    pub fn get_block_number(&mut self) -> BatchCall<U64> {
+        self.add("eth_blockNumber", ())
+    }
+
+    /// Queues `eth_chainId`
+    pub fn get_chainid(&mut self) -> BatchCall<U256> {
+        self.add("eth_chainId", ())
+    }
+
+    /// Queues `eth_gasPrice`
+    pub fn get_gas_price(&mut self) -> BatchCall<U256> {
+        self.add("eth_gasPrice", ())
+    }
+
+    /// Queues `eth_getBalance`, at the latest block if `block` is `None`
+    pub fn get_balance(&mut self, from: Address, block: Option<BlockId>) -> BatchCall<U256> {
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.add("eth_getBalance", [utils::serialize(&from), block])
+    }
+
+    /// Queues `eth_getTransactionCount`, at the latest block if `block` is `None`
+    pub fn get_transaction_count(
+        &mut self,
+        from: Address,
+        block: Option<BlockId>,
+    ) -> BatchCall<U256> {
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.add("eth_getTransactionCount", [utils::serialize(&from), block])
+    }
+
+    /// Queues `eth_getCode`, at the latest block if `block` is `None`
+    pub fn get_code(&mut self, at: Address, block: Option<BlockId>) -> BatchCall<Bytes> {
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.add("eth_getCode", [utils::serialize(&at), block])
+    }
+
+    /// Queues `eth_getBlockByHash` or `eth_getBlockByNumber` without the full transactions
+    pub fn get_block<T: Into<BlockId>>(&mut self, block: T) -> BatchCall<Option<Block<TxHash>>> {
+        self.add_get_block(block.into(), false)
+    }
+
+    /// Queues `eth_getBlockByHash` or `eth_getBlockByNumber` with the full transactions
+    pub fn get_block_with_txs<T: Into<BlockId>>(
+        &mut self,
+        block: T,
+    ) -> BatchCall<Option<Block<Transaction>>> {
+        self.add_get_block(block.into(), true)
+    }
+
+    fn add_get_block<R: DeserializeOwned>(
+        &mut self,
+        id: BlockId,
+        include_txs: bool,
+    ) -> BatchCall<R> {
+        let include_txs = utils::serialize(&include_txs);
+        match id {
+            BlockId::Hash(hash) => {
+                self.add("eth_getBlockByHash", [utils::serialize(&hash), include_txs])
+            }
+            BlockId::Number(num) => {
+                self.add("eth_getBlockByNumber", [utils::serialize(&num), include_txs])
+            }
+        }
+    }
+
+    /// Queues `eth_getTransactionByHash`
+    pub fn get_transaction(&mut self, hash: H256) -> BatchCall<Option<Transaction>> {
+        self.add("eth_getTransactionByHash", [hash])
+    }
+
+    /// Queues `eth_getTransactionReceipt`
+    pub fn get_transaction_receipt(&mut self, hash: H256) -> BatchCall<Option<TransactionReceipt>> {
+        self.add("eth_getTransactionReceipt", [hash])
+    }
+
+    /// Queues `eth_call`, at the latest block if `block` is `None`.
+    ///
+    /// CCIP-Read lookups are not followed.
+    pub fn call(&mut self, tx: &TypedTransaction, block: Option<BlockId>) -> BatchCall<Bytes> {
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.add("eth_call", [utils::serialize(tx), block])
+    }
+
+    /// Queues `eth_estimateGas`
+    pub fn estimate_gas(
+        &mut self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> BatchCall<U256> {
+        let mut params = vec![utils::serialize(tx)];
+        if let Some(block) = block {
+            params.push(utils::serialize(&block));
+        }
+        self.add("eth_estimateGas", params)
+    }
+
+    /// Queues `eth_getLogs`
+    pub fn get_logs(&mut self, filter: &Filter) -> BatchCall<Vec<Log>> {
+        self.add("eth_getLogs", [filter])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockResponse, RpcError};
+
+    #[tokio::test]
+    async fn batch_returns_typed_results() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        }));
+        mock.push(U64::from(7)).unwrap();
+        mock.push(U256::from(100)).unwrap();
+
+        let mut batch = provider.batch();
+        let balance = batch.get_balance(Address::zero(), None);
+        let number = batch.get_block_number();
+        let block = batch.get_block(1000u64);
+        assert_eq!(batch.len(), 3);
+
+        let responses = batch.send().await.unwrap();
+        assert_eq!(responses.get(balance).unwrap(), U256::from(100));
+        assert_eq!(responses.get(number).unwrap(), U64::from(7));
+        let err = responses.get(block).unwrap_err();
+        assert_eq!(err.as_error_response().unwrap().message, "header not found");
+
+        mock.assert_request(
+            "eth_getBalance",
+            [Value::from("0x0000000000000000000000000000000000000000"), "latest".into()],
+        )
+        .unwrap();
+        mock.assert_request("eth_blockNumber", Value::Array(vec![])).unwrap();
+        mock.assert_request("eth_getBlockByNumber", [Value::from("0x3e8"), false.into()]).unwrap();
+    }
+}
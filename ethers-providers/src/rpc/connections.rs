@@ -2,11 +2,11 @@ use std::fmt::Debug;
 
 use async_trait::async_trait;
 use auto_impl::auto_impl;
-use ethers_core::types::U256;
-use serde::{de::DeserializeOwned, Serialize};
-use serde_json::value::RawValue;
+use ethers_core::{types::U256, utils};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{value::RawValue, Value};
 
-use crate::{ProviderError, RpcError};
+use crate::{JsonRpcError, ProviderError, RpcError};
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -22,6 +22,56 @@ pub trait JsonRpcClient: Debug + Send + Sync {
     where
         T: Debug + Serialize + Send + Sync,
         R: DeserializeOwned + Send;
+
+    /// Sends a batch of requests, returning the responses in the order of the requests.
+    ///
+    /// Every request fails individually with the error response returned for it, while errors of
+    /// the transport fail the whole batch. The default implementation sends the requests one
+    /// after another.
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            let res = self.request(&request.method, &request.params).await;
+            responses.push(split_error_response(res)?);
+        }
+        Ok(responses)
+    }
+}
+
+/// A single request of a batch sent with [`JsonRpcClient::request_batch`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRequest {
+    /// The JSON-RPC method
+    pub method: String,
+    /// The parameters of the request, an empty array if it has none
+    pub params: Value,
+}
+
+impl BatchRequest {
+    /// Creates a new request with the given method and parameters
+    pub fn new<T: Serialize>(method: impl Into<String>, params: T) -> Self {
+        let params = match utils::serialize(&params) {
+            Value::Null => Value::Array(Vec::new()),
+            params => params,
+        };
+        Self { method: method.into(), params }
+    }
+}
+
+/// Separates the error response of a single request from the errors of the transport
+pub(crate) fn split_error_response<E: RpcError>(
+    res: Result<Value, E>,
+) -> Result<Result<Value, JsonRpcError>, E> {
+    match res {
+        Ok(value) => Ok(Ok(value)),
+        Err(err) => match err.as_error_response() {
+            Some(error) => Ok(Err(error.clone())),
+            None => Err(err),
+        },
+    }
 }
 
 /// A transport implementation supporting pub sub subscriptions.
@@ -1,6 +1,9 @@
 mod provider;
 pub use provider::*;
 
+mod batch;
+pub use batch::{Batch, BatchCall, BatchResponse};
+
 mod transports;
 pub use transports::*;
 
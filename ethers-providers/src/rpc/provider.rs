@@ -3,12 +3,16 @@ use ethers_core::types::SyncingStatus;
 use crate::{
     call_raw::CallBuilder,
     errors::ProviderError,
-    ext::{ens, erc},
+    ext::{
+        ccip::{CcipError, CcipRead, OffchainLookup},
+        ens, erc,
+    },
     rpc::pubsub::{PubsubClient, SubscriptionStream},
     stream::{FilterWatcher, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL},
     utils::maybe,
-    Http as HttpProvider, JsonRpcClient, JsonRpcClientWrapper, LogQuery, MiddlewareError,
-    MockProvider, NodeInfo, PeerInfo, PendingTransaction, QuorumProvider, RwClient,
+    Batch, BatchRequest, FallbackProvider, Http as HttpProvider, JsonRpcClient,
+    JsonRpcClientWrapper, JsonRpcError, LogQuery, MiddlewareError, MockProvider, NodeInfo,
+    PeerInfo, PendingTransaction, QuorumProvider, RwClient,
 };
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,14 +24,15 @@ pub use crate::Middleware;
 use async_trait::async_trait;
 
 use ethers_core::{
-    abi::{self, Detokenize, ParamType},
+    abi::{self, Detokenize, InvalidOutputType, ParamType},
     types::{
         transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
         Address, Block, BlockId, BlockNumber, BlockTrace, Bytes, Chain, EIP1186ProofResponse,
         FeeHistory, Filter, FilterBlockOption, GethDebugTracingCallOptions,
         GethDebugTracingOptions, GethTrace, Log, NameOrAddress, Selector, Signature, Trace,
         TraceFilter, TraceType, Transaction, TransactionReceipt, TransactionRequest, TxHash,
-        TxpoolContent, TxpoolInspect, TxpoolStatus, H256, U256, U64,
+        TxpoolContent, TxpoolInspect, TxpoolStatus, UserOperationGasEstimate, UserOperationReceipt,
+        UserOperationRequest, H256, U256, U64,
     },
     utils,
 };
@@ -97,6 +102,8 @@ pub struct Provider<P> {
     ens: Option<Address>,
     interval: Option<Duration>,
     from: Option<Address>,
+    /// CCIP-Read configuration, `None` if offchain lookups are disabled
+    ccip: Option<CcipRead>,
     /// Node client hasn't been checked yet = `None`
     /// Unsupported node client = `Some(None)`
     /// Supported node client = `Some(Some(NodeClient))`
@@ -131,6 +138,7 @@ impl<P: JsonRpcClient> Provider<P> {
             ens: None,
             interval: None,
             from: None,
+            ccip: None,
             _node_client: Arc::new(Mutex::new(None)),
         }
     }
@@ -180,6 +188,48 @@ impl<P: JsonRpcClient> Provider<P> {
         Ok(res)
     }
 
+    /// Send a batch of RPC requests via the internal connection, and return the response of every
+    /// request in order.
+    ///
+    /// See [`Provider::batch`] for building batches of typed requests.
+    pub async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<serde_json::Value, JsonRpcError>>, ProviderError> {
+        let span = tracing::trace_span!("rpc_batch", requests = requests.len());
+        let res = async move {
+            trace!("tx");
+            let res = self.inner.request_batch(requests).await.map_err(Into::into)?;
+            trace!(rx = ?res);
+            Ok::<_, ProviderError>(res)
+        }
+        .instrument(span)
+        .await?;
+        Ok(res)
+    }
+
+    /// Returns a builder for a batch of typed requests, sent together with [`Batch::send`].
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_core::types::Address;
+    /// use ethers_providers::{Http, Provider};
+    ///
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let mut batch = provider.batch();
+    /// let balance = batch.get_balance(Address::zero(), None);
+    /// let block = batch.get_block(100u64);
+    ///
+    /// let responses = batch.send().await?;
+    /// let balance = responses.get(balance)?;
+    /// let block = responses.get(block)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch(&self) -> Batch<'_, P> {
+        Batch::new(self)
+    }
+
     async fn get_block_gen<Tx: Default + Serialize + DeserializeOwned + Debug + Send>(
         &self,
         id: BlockId,
@@ -310,6 +360,44 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
                         .or(Some(max_priority_fee_per_gas));
                 };
             }
+            TypedTransaction::Eip4844(ref mut inner) => {
+                if inner.max_fee_per_gas.is_none() || inner.max_priority_fee_per_gas.is_none() {
+                    let (max_fee_per_gas, max_priority_fee_per_gas) =
+                        self.estimate_eip1559_fees(None).await?;
+                    // same as for EIP-1559 transactions, the tip cannot be higher than max fee
+                    let mfpg = inner.max_fee_per_gas.get_or_insert(max_fee_per_gas);
+                    inner.max_priority_fee_per_gas = inner
+                        .max_priority_fee_per_gas
+                        .map(|tip| std::cmp::min(tip, *mfpg))
+                        .or(Some(max_priority_fee_per_gas));
+                };
+                #[cfg(not(feature = "celo"))]
+                if inner.max_fee_per_blob_gas.is_none() {
+                    let blob_base_fee = self
+                        .get_block(BlockNumber::Latest)
+                        .await?
+                        .ok_or_else(|| ProviderError::CustomError("Latest block not found".into()))?
+                        .next_block_blob_base_fee()
+                        .ok_or_else(|| {
+                            ProviderError::CustomError("EIP-4844 not activated".into())
+                        })?;
+                    // leave room for the blob base fee to rise until the transaction is included
+                    inner.max_fee_per_blob_gas =
+                        Some(blob_base_fee * utils::EIP4844_BLOB_FEE_ESTIMATION_MULTIPLIER);
+                }
+            }
+            TypedTransaction::Eip7702(ref mut inner) => {
+                if inner.max_fee_per_gas.is_none() || inner.max_priority_fee_per_gas.is_none() {
+                    let (max_fee_per_gas, max_priority_fee_per_gas) =
+                        self.estimate_eip1559_fees(None).await?;
+                    // same as for EIP-1559 transactions, the tip cannot be higher than max fee
+                    let mfpg = inner.max_fee_per_gas.get_or_insert(max_fee_per_gas);
+                    inner.max_priority_fee_per_gas = inner
+                        .max_priority_fee_per_gas
+                        .map(|tip| std::cmp::min(tip, *mfpg))
+                        .or(Some(max_priority_fee_per_gas));
+                };
+            }
             #[cfg(feature = "optimism")]
             TypedTransaction::DepositTransaction(_) => {
                 let gas_price = maybe(tx.gas_price(), self.get_gas_price()).await?;
@@ -430,6 +518,37 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("parity_getBlockReceipts", vec![block.into()]).await
     }
 
+    async fn send_user_operation<U: Into<UserOperationRequest> + Send + Sync>(
+        &self,
+        user_op: U,
+        entry_point: Address,
+    ) -> Result<H256, ProviderError> {
+        let user_op = utils::serialize(&user_op.into());
+        let entry_point = utils::serialize(&entry_point);
+        self.request("eth_sendUserOperation", [user_op, entry_point]).await
+    }
+
+    async fn estimate_user_operation_gas<U: Into<UserOperationRequest> + Send + Sync>(
+        &self,
+        user_op: U,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate, ProviderError> {
+        let user_op = utils::serialize(&user_op.into());
+        let entry_point = utils::serialize(&entry_point);
+        self.request("eth_estimateUserOperationGas", [user_op, entry_point]).await
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        user_op_hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, ProviderError> {
+        self.request("eth_getUserOperationReceipt", [user_op_hash]).await
+    }
+
+    async fn supported_entry_points(&self) -> Result<Vec<Address>, ProviderError> {
+        self.request("eth_supportedEntryPoints", ()).await
+    }
+
     async fn get_gas_price(&self) -> Result<U256, ProviderError> {
         self.request("eth_gasPrice", ()).await
     }
@@ -514,9 +633,10 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         tx: &TypedTransaction,
         block: Option<BlockId>,
     ) -> Result<Bytes, ProviderError> {
-        let tx = utils::serialize(tx);
-        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
-        self.request("eth_call", [tx, block]).await
+        match self.ccip {
+            Some(ref ccip) => self.call_with_ccip(ccip, tx, block).await,
+            None => self.eth_call(tx, block).await,
+        }
     }
 
     async fn estimate_gas(
@@ -828,7 +948,9 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
                             ..Default::default()
                         };
                         let data = self.call(&tx.into(), None).await?;
-                        if decode_bytes::<Address>(ParamType::Address, data) != owner {
+                        let token_owner: Address = decode_bytes(ParamType::Address, data)
+                            .map_err(|err| ProviderError::CustomError(err.to_string()))?;
+                        if token_owner != owner {
                             return Err(ProviderError::CustomError("Incorrect owner.".to_string()))
                         }
                     }
@@ -848,7 +970,9 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
                             ..Default::default()
                         };
                         let data = self.call(&tx.into(), None).await?;
-                        if decode_bytes::<u64>(ParamType::Uint(64), data) == 0 {
+                        let balance: u64 = decode_bytes(ParamType::Uint(64), data)
+                            .map_err(|err| ProviderError::CustomError(err.to_string()))?;
+                        if balance == 0 {
                             return Err(ProviderError::CustomError("Incorrect balance.".to_string()))
                         }
                     }
@@ -875,7 +999,9 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
             ..Default::default()
         };
         let data = self.call(&tx.into(), None).await?;
-        let mut metadata_url = Url::parse(&decode_bytes::<String>(ParamType::String, data))
+        let metadata_url: String = decode_bytes(ParamType::String, data)
+            .map_err(|err| ProviderError::CustomError(err.to_string()))?;
+        let mut metadata_url = Url::parse(&metadata_url)
             .map_err(|e| ProviderError::CustomError(format!("Invalid metadata url: {e}")))?;
 
         if token.type_ == erc::ERCNFTType::ERC1155 {
@@ -1152,6 +1278,54 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 }
 
 impl<P: JsonRpcClient> Provider<P> {
+    async fn eth_call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, ProviderError> {
+        let tx = utils::serialize(tx);
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.request("eth_call", [tx, block]).await
+    }
+
+    /// Performs the call, following `OffchainLookup` reverts as specified by
+    /// [EIP-3668](https://eips.ethereum.org/EIPS/eip-3668).
+    async fn call_with_ccip(
+        &self,
+        ccip: &CcipRead,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, ProviderError> {
+        let mut tx = tx.clone();
+        let mut redirects = 0;
+        loop {
+            let err = match self.eth_call(&tx, block).await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+            let Some(lookup) = err
+                .as_error_response()
+                .and_then(|err| err.as_revert_data())
+                .and_then(|data| OffchainLookup::decode(&data))
+            else {
+                return Err(err)
+            };
+
+            if redirects == ccip.get_max_redirects() {
+                return Err(CcipError::TooManyRedirects(redirects).into())
+            }
+            redirects += 1;
+
+            let to = tx.to().and_then(|to| to.as_address()).copied();
+            if to != Some(lookup.sender) {
+                return Err(CcipError::SenderMismatch { sender: lookup.sender, to }.into())
+            }
+
+            let response = ccip.fetch(&lookup).await?;
+            tx.set_data(lookup.callback_data(&response));
+        }
+    }
+
     async fn query_resolver<T: Detokenize>(
         &self,
         param: ParamType,
@@ -1168,23 +1342,55 @@ impl<P: JsonRpcClient> Provider<P> {
         selector: Selector,
         parameters: Option<&[u8]>,
     ) -> Result<T, ProviderError> {
-        // Get the ENS address, prioritize the local override variable
-        let ens_addr = self.ens.unwrap_or(ens::ENS_ADDRESS);
+        // first get the resolver responsible for this name, or for its closest parent
+        let (resolver_address, is_wildcard) = self.find_resolver(ens_name).await?;
 
-        // first get the resolver responsible for this name
-        // the call will return a Bytes array which we convert to an address
-        let data = self.call(&ens::get_resolver(ens_addr, ens_name).into(), None).await?;
+        // resolvers registered for the name itself are asked directly first
+        let err = if is_wildcard {
+            ProviderError::EnsError(ens_name.to_string())
+        } else {
+            match self
+                .resolve_directly(param.clone(), resolver_address, selector, ens_name, parameters)
+                .await
+            {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            }
+        };
 
-        // otherwise, decode_bytes panics
-        if data.0.is_empty() {
+        // extended resolvers may answer for names they are not directly registered for, and may
+        // resolve them offchain (ENSIP-10), so they are only probed once the direct resolution
+        // failed
+        if !self.supports_wildcard(resolver_address).await {
+            return Err(err)
+        }
+        let data = self
+            .call(
+                &ens::resolve_extended(resolver_address, selector, ens_name, parameters).into(),
+                None,
+            )
+            .await?;
+        if data.is_empty() {
             return Err(ProviderError::EnsError(ens_name.to_string()))
         }
-
-        let resolver_address: Address = decode_bytes(ParamType::Address, data);
-        if resolver_address == Address::zero() {
+        // the resolver and the offchain gateway are not trusted to return well-formed data
+        let invalid = |_| ProviderError::EnsError(ens_name.to_string());
+        let data: Bytes = decode_bytes(ParamType::Bytes, data).map_err(invalid)?;
+        if data.is_empty() {
             return Err(ProviderError::EnsError(ens_name.to_string()))
         }
+        decode_bytes(param, data).map_err(invalid)
+    }
 
+    /// Resolves the name with the resolver registered for it, without ENSIP-10
+    async fn resolve_directly<T: Detokenize>(
+        &self,
+        param: ParamType,
+        resolver_address: Address,
+        selector: Selector,
+        ens_name: &str,
+        parameters: Option<&[u8]>,
+    ) -> Result<T, ProviderError> {
         if let ParamType::Address = param {
             // Reverse resolver reverts when calling `supportsInterface(bytes4)`
             self.validate_resolver(resolver_address, selector, ens_name).await?;
@@ -1194,8 +1400,49 @@ impl<P: JsonRpcClient> Provider<P> {
         let data = self
             .call(&ens::resolve(resolver_address, selector, ens_name, parameters).into(), None)
             .await?;
+        if data.is_empty() {
+            return Err(ProviderError::EnsError(ens_name.to_string()))
+        }
 
-        Ok(decode_bytes(param, data))
+        decode_bytes(param, data).map_err(|_| ProviderError::EnsError(ens_name.to_string()))
+    }
+
+    /// Returns the resolver of the name or, if it has none, of its closest parent. The returned
+    /// flag is `true` if the resolver was found on a parent.
+    async fn find_resolver(&self, ens_name: &str) -> Result<(Address, bool), ProviderError> {
+        // Get the ENS address, prioritize the local override variable
+        let ens_addr = self.ens.unwrap_or(ens::ENS_ADDRESS);
+
+        let mut name = Some(ens_name);
+        while let Some(current) = name {
+            // the call will return a Bytes array which we convert to an address
+            let data = self.call(&ens::get_resolver(ens_addr, current).into(), None).await?;
+
+            if data.0.is_empty() {
+                return Err(ProviderError::EnsError(ens_name.to_string()))
+            }
+
+            let resolver_address: Address = decode_bytes(ParamType::Address, data)
+                .map_err(|_| ProviderError::EnsError(ens_name.to_string()))?;
+            if resolver_address != Address::zero() {
+                return Ok((resolver_address, current != ens_name))
+            }
+            name = ens::parent(current);
+        }
+
+        Err(ProviderError::EnsError(ens_name.to_string()))
+    }
+
+    /// Returns `true` if the resolver implements `resolve(bytes,bytes)` (ENSIP-10).
+    async fn supports_wildcard(&self, resolver_address: Address) -> bool {
+        let tx = ens::supports_interface(resolver_address, ens::EXTENDED_RESOLVER_SELECTOR).into();
+        match self.call(&tx, None).await {
+            Ok(data) => abi::decode(&[ParamType::Bool], data.as_ref())
+                .ok()
+                .and_then(|tokens| tokens[0].clone().into_bool())
+                .unwrap_or_default(),
+            Err(_) => false,
+        }
     }
 
     /// Validates that the resolver supports `selector`.
@@ -1246,6 +1493,24 @@ impl<P: JsonRpcClient> Provider<P> {
         self
     }
 
+    /// Enables CCIP-Read with the given configuration, following offchain lookups in calls and
+    /// ENS resolution (default: disabled).
+    ///
+    /// Offchain lookups make requests to the gateway URLs chosen by the called contract, use
+    /// [`CcipRead::default`] to fetch them over HTTP.
+    #[must_use]
+    pub fn ccip_read(mut self, ccip: CcipRead) -> Self {
+        self.ccip = Some(ccip);
+        self
+    }
+
+    /// Disables CCIP-Read, returning `OffchainLookup` reverts as errors (default)
+    #[must_use]
+    pub fn without_ccip_read(mut self) -> Self {
+        self.ccip = None;
+        self
+    }
+
     /// Sets the default polling interval for event filters and pending transactions
     /// (default: 7 seconds)
     pub fn set_interval<T: Into<Duration>>(&mut self, interval: T) -> &mut Self {
@@ -1314,6 +1579,13 @@ impl<T: JsonRpcClientWrapper> Provider<QuorumProvider<T>> {
     }
 }
 
+impl<T: JsonRpcClientWrapper> Provider<FallbackProvider<T>> {
+    /// Provider that falls back to other providers if a request fails
+    pub fn fallback(inner: FallbackProvider<T>) -> Self {
+        Self::new(inner)
+    }
+}
+
 impl Provider<MockProvider> {
     /// Returns a `Provider` instantiated with an internal "mock" transport.
     ///
@@ -1343,15 +1615,11 @@ impl Provider<MockProvider> {
     }
 }
 
-/// infallible conversion of Bytes to Address/String
-///
-/// # Panics
-///
-/// If the provided bytes were not an interpretation of an address
-fn decode_bytes<T: Detokenize>(param: ParamType, bytes: Bytes) -> T {
+/// Decodes the return data of a call as a single value of type `param`
+fn decode_bytes<T: Detokenize>(param: ParamType, bytes: Bytes) -> Result<T, InvalidOutputType> {
     let tokens = abi::decode(&[param], bytes.as_ref())
-        .expect("could not abi-decode bytes to address tokens");
-    T::from_tokens(tokens).expect("could not parse tokens as address")
+        .map_err(|err| InvalidOutputType(format!("could not abi-decode bytes: {err}")))?;
+    T::from_tokens(tokens)
 }
 
 impl TryFrom<&str> for Provider<HttpProvider> {
@@ -1810,6 +2078,179 @@ mod tests {
             .unwrap();
     }
 
+    #[derive(Debug)]
+    struct StaticFetcher(Bytes);
+
+    #[async_trait]
+    impl crate::CcipFetcher for StaticFetcher {
+        async fn fetch(&self, _: &str, _: Address, _: &Bytes) -> Result<Bytes, CcipError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn offchain_lookup_error(lookup: &OffchainLookup) -> crate::MockResponse {
+        let data = [
+            &crate::ccip::OFFCHAIN_LOOKUP_SELECTOR[..],
+            &abi::encode(&[
+                abi::Token::Address(lookup.sender),
+                abi::Token::Array(
+                    lookup.urls.iter().cloned().map(abi::Token::String).collect::<Vec<_>>(),
+                ),
+                abi::Token::Bytes(lookup.call_data.to_vec()),
+                abi::Token::FixedBytes(lookup.callback_function.to_vec()),
+                abi::Token::Bytes(lookup.extra_data.to_vec()),
+            ]),
+        ]
+        .concat();
+        crate::MockResponse::Error(crate::JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(serde_json::Value::String(Bytes::from(data).to_string())),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_call_follows_offchain_lookup() {
+        let sender = Address::repeat_byte(0x11);
+        let lookup = OffchainLookup {
+            sender,
+            urls: vec!["https://gateway.example/{sender}/{data}.json".to_string()],
+            call_data: vec![1, 2, 3].into(),
+            callback_function: [0xde, 0xad, 0xbe, 0xef],
+            extra_data: vec![4, 5].into(),
+        };
+        let gateway_response = Bytes::from(vec![0xaa, 0xbb]);
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.ccip_read(CcipRead::new(StaticFetcher(gateway_response.clone())));
+
+        let tx: TypedTransaction =
+            TransactionRequest::new().to(sender).data(vec![0x12, 0x34, 0x56, 0x78]).into();
+
+        // responses are popped from the back
+        mock.push::<Bytes, _>(Bytes::from(vec![0x42])).unwrap();
+        mock.push_response(offchain_lookup_error(&lookup));
+
+        let res = provider.call(&tx, None).await.unwrap();
+        assert_eq!(res, Bytes::from(vec![0x42]));
+
+        let block = utils::serialize(&BlockId::from(BlockNumber::Latest));
+        mock.assert_request("eth_call", [utils::serialize(&tx), block.clone()]).unwrap();
+        let mut callback = tx.clone();
+        callback.set_data(lookup.callback_data(&gateway_response));
+        mock.assert_request("eth_call", [utils::serialize(&callback), block]).unwrap();
+
+        // the redirect limit is enforced
+        let provider = provider
+            .ccip_read(CcipRead::new(StaticFetcher(gateway_response.clone())).max_redirects(1));
+        mock.push_response(offchain_lookup_error(&lookup));
+        mock.push_response(offchain_lookup_error(&lookup));
+        let err = provider.call(&tx, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::CcipError(CcipError::TooManyRedirects(1))));
+
+        // the lookup must come from the called contract
+        let tx: TypedTransaction = TransactionRequest::new().to(Address::repeat_byte(0x22)).into();
+        mock.push_response(offchain_lookup_error(&lookup));
+        let err = provider.call(&tx, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::CcipError(CcipError::SenderMismatch { .. })));
+
+        // the revert is returned as is when CCIP-Read is disabled
+        let provider = provider.without_ccip_read();
+        mock.push_response(offchain_lookup_error(&lookup));
+        let err = provider.call(&tx, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::JsonRpcClientError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_name_offchain_wildcard() {
+        let resolver = Address::repeat_byte(0x33);
+        let resolved = Address::repeat_byte(0x44);
+        let lookup = OffchainLookup {
+            sender: resolver,
+            urls: vec!["https://gateway.example".to_string()],
+            call_data: vec![1].into(),
+            callback_function: [0xde, 0xad, 0xbe, 0xef],
+            extra_data: vec![2].into(),
+        };
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.ccip_read(CcipRead::new(StaticFetcher(vec![3].into())));
+
+        let encode = |tokens: &[abi::Token]| Bytes::from(abi::encode(tokens));
+        // responses are popped from the back, so they are pushed in reverse order:
+        // callback -> resolve(bytes,bytes) -> supportsInterface -> resolver("cb.id") ->
+        // resolver("foo.cb.id")
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bytes(abi::encode(&[abi::Token::Address(
+            resolved,
+        )]))]))
+        .unwrap();
+        mock.push_response(offchain_lookup_error(&lookup));
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bool(true)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(resolver)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(Address::zero())])).unwrap();
+
+        assert_eq!(provider.resolve_name("foo.cb.id").await.unwrap(), resolved);
+
+        let block = utils::serialize(&BlockId::from(BlockNumber::Latest));
+        for tx in [
+            ens::get_resolver(ens::ENS_ADDRESS, "foo.cb.id"),
+            ens::get_resolver(ens::ENS_ADDRESS, "cb.id"),
+            ens::supports_interface(resolver, ens::EXTENDED_RESOLVER_SELECTOR),
+            ens::resolve_extended(resolver, ens::ADDR_SELECTOR, "foo.cb.id", None),
+        ] {
+            let tx: TypedTransaction = tx.into();
+            mock.assert_request("eth_call", [utils::serialize(&tx), block.clone()]).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_name_skips_wildcard_probe() {
+        let resolver = Address::repeat_byte(0x33);
+        let resolved = Address::repeat_byte(0x44);
+        let (provider, mock) = Provider::mocked();
+
+        let encode = |tokens: &[abi::Token]| Bytes::from(abi::encode(tokens));
+        // addr(bytes32) -> supportsInterface -> resolver("foo.eth")
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(resolved)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bool(true)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(resolver)])).unwrap();
+
+        assert_eq!(provider.resolve_name("foo.eth").await.unwrap(), resolved);
+
+        let block = utils::serialize(&BlockId::from(BlockNumber::Latest));
+        for tx in [
+            ens::get_resolver(ens::ENS_ADDRESS, "foo.eth"),
+            ens::supports_interface(resolver, ens::ADDR_SELECTOR),
+            ens::resolve(resolver, ens::ADDR_SELECTOR, "foo.eth", None),
+        ] {
+            let tx: TypedTransaction = tx.into();
+            mock.assert_request("eth_call", [utils::serialize(&tx), block.clone()]).unwrap();
+        }
+        // the resolver is not asked whether it supports ENSIP-10
+        assert!(mock.assert_request("eth_call", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_name_rejects_malformed_wildcard_response() {
+        let resolver = Address::repeat_byte(0x33);
+        let (provider, mock) = Provider::mocked();
+
+        let encode = |tokens: &[abi::Token]| Bytes::from(abi::encode(tokens));
+        // resolve(bytes,bytes) -> supportsInterface -> resolver("cb.id") -> resolver("foo.cb.id")
+        mock.push::<Bytes, _>(Bytes::from(vec![0xff; 5])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bool(true)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(resolver)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(Address::zero())])).unwrap();
+        let err = provider.resolve_name("foo.cb.id").await.unwrap_err();
+        assert!(matches!(err, ProviderError::EnsError(name) if name == "foo.cb.id"));
+
+        // the returned bytes don't hold an address
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bytes(vec![0x01])])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Bool(true)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(resolver)])).unwrap();
+        mock.push::<Bytes, _>(encode(&[abi::Token::Address(Address::zero())])).unwrap();
+        let err = provider.resolve_name("foo.cb.id").await.unwrap_err();
+        assert!(matches!(err, ProviderError::EnsError(name) if name == "foo.cb.id"));
+    }
+
     #[tokio::test]
     async fn test_fill_transaction_1559() {
         let (mut provider, mock) = Provider::mocked();
@@ -1883,6 +2324,36 @@ mod tests {
         assert!(matches!(res, Err(ProviderError::JsonRpcClientError(_))));
     }
 
+    #[tokio::test]
+    #[cfg(not(feature = "celo"))]
+    async fn test_fill_transaction_4844() {
+        use ethers_core::types::transaction::eip4844::Eip4844TransactionRequest;
+
+        let (mut provider, mock) = Provider::mocked();
+        provider.from = Some(Address::repeat_byte(1));
+
+        let block: Block<TxHash> = Block {
+            excess_blob_gas: Some(10_000_000.into()),
+            blob_gas_used: Some(786_432.into()),
+            ..Default::default()
+        };
+        mock.push(block.clone()).unwrap();
+
+        let mut tx = Eip4844TransactionRequest::new()
+            .to(Address::repeat_byte(2))
+            .gas(21000)
+            .max_fee_per_gas(25)
+            .max_priority_fee_per_gas(2)
+            .into();
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+
+        let TypedTransaction::Eip4844(tx) = tx else { panic!("not a blob transaction") };
+        let blob_base_fee = block.next_block_blob_base_fee().unwrap();
+        assert!(blob_base_fee > U256::one());
+        assert_eq!(tx.max_fee_per_blob_gas, Some(blob_base_fee * 2));
+        mock.assert_request("eth_getBlockByNumber", ("latest", false)).unwrap();
+    }
+
     #[tokio::test]
     async fn test_fill_transaction_legacy() {
         let (mut provider, mock) = Provider::mocked();
@@ -0,0 +1,387 @@
+//! A [JsonRpcClient] implementation that coalesces concurrent requests into JSON-RPC batches.
+
+use super::common::JsonRpcError;
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient, RpcError};
+use async_trait::async_trait;
+use futures_channel::oneshot;
+use futures_timer::Delay;
+use futures_util::future::{self, Either};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::Debug,
+    mem,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tracing::trace;
+
+/// The default time requests are collected for before a batch is sent
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(5);
+
+/// The default maximum number of requests in a batch
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+type BatchResult = Result<Value, BatchingClientError>;
+
+/// [BatchingClient] presents as a wrapper around [JsonRpcClient] that collects the requests issued
+/// concurrently within a short window and sends them as a single JSON-RPC batch.
+///
+/// A batch is sent once the window after its first request has passed, or as soon as it holds
+/// `max_batch_size` requests. Every request only fails with the error response returned for it,
+/// unless the batch fails as a whole.
+///
+/// # Example
+///
+/// ```
+/// # async fn demo() {
+/// use ethers_providers::{BatchingClient, Http, Middleware, Provider};
+/// use std::time::Duration;
+/// use url::Url;
+///
+/// let http = Http::new(Url::parse("http://localhost:8545").unwrap());
+/// let client = BatchingClient::new(http).window(Duration::from_millis(10)).max_batch_size(50);
+/// let provider = Provider::new(client);
+///
+/// // both requests are sent in the same batch
+/// let (block, chain_id) = futures_util::join!(provider.get_block_number(), provider.get_chainid());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BatchingClient<T> {
+    inner: T,
+    queue: Mutex<Queue>,
+    window: Duration,
+    max_batch_size: usize,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    /// Incremented whenever the pending requests are taken to be sent
+    generation: u64,
+    pending: Vec<(BatchRequest, oneshot::Sender<BatchResult>)>,
+}
+
+impl Queue {
+    fn take(&mut self) -> Vec<(BatchRequest, oneshot::Sender<BatchResult>)> {
+        self.generation += 1;
+        mem::take(&mut self.pending)
+    }
+}
+
+impl<T: JsonRpcClient> BatchingClient<T> {
+    /// Creates a new `BatchingClient` wrapping `inner`, with the default window and batch size
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            queue: Default::default(),
+            window: DEFAULT_BATCH_WINDOW,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+        }
+    }
+
+    /// Sets how long requests are collected for before a batch is sent
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets the maximum number of requests in a batch
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Returns a reference to the inner client
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Sends the requests and fans the responses back out
+    async fn flush(&self, batch: Vec<(BatchRequest, oneshot::Sender<BatchResult>)>) {
+        let (requests, senders): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+        trace!(requests = requests.len(), "sending batch");
+
+        let responses = if let [request] = &requests[..] {
+            // no need to wrap a single request in a batch
+            self.inner
+                .request(&request.method, &request.params)
+                .await
+                .map(|value| vec![Ok(value)])
+                .or_else(|err| match err.as_error_response() {
+                    Some(error) => Ok(vec![Err(error.clone())]),
+                    None => Err(err),
+                })
+        } else {
+            self.inner.request_batch(&requests).await
+        };
+
+        match responses {
+            Ok(responses) => {
+                for (sender, response) in senders.into_iter().zip(responses) {
+                    let _ = sender.send(response.map_err(BatchingClientError::JsonRpcError));
+                }
+            }
+            Err(err) => {
+                let err = Arc::new(err.into());
+                for sender in senders {
+                    let _ = sender.send(Err(BatchingClientError::BatchFailed(err.clone())));
+                }
+            }
+        }
+    }
+}
+
+/// Error thrown by the [BatchingClient]
+#[derive(Clone, Debug, Error)]
+pub enum BatchingClientError {
+    /// The error response returned for the request
+    #[error(transparent)]
+    JsonRpcError(JsonRpcError),
+    /// The batch containing the request failed as a whole
+    #[error("batch request failed: {0}")]
+    BatchFailed(Arc<ProviderError>),
+    /// (De)Serialization error
+    #[error(transparent)]
+    SerdeJson(Arc<serde_json::Error>),
+    /// The batch containing the state-changing request was dropped while it was sent, so the
+    /// request may or may not have reached the node
+    #[error("batch containing `{0}` was dropped while it was sent")]
+    Dropped(String),
+}
+
+impl crate::RpcError for BatchingClientError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            BatchingClientError::JsonRpcError(err) => Some(err),
+            BatchingClientError::BatchFailed(err) => err.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            BatchingClientError::BatchFailed(err) => err.as_serde_error(),
+            BatchingClientError::SerdeJson(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<BatchingClientError> for ProviderError {
+    fn from(src: BatchingClientError) -> Self {
+        match src {
+            BatchingClientError::BatchFailed(err) => Arc::try_unwrap(err).unwrap_or_else(|err| {
+                ProviderError::JsonRpcClientError(Box::new(BatchingClientError::BatchFailed(err)))
+            }),
+            _ => ProviderError::JsonRpcClientError(Box::new(src)),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T> JsonRpcClient for BatchingClient<T>
+where
+    T: JsonRpcClient,
+{
+    type Error = BatchingClientError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)
+            .map_err(|err| BatchingClientError::SerdeJson(err.into()))?;
+        let request = BatchRequest::new(method, params);
+
+        let (sender, mut receiver) = oneshot::channel();
+        let (generation, full) = {
+            let mut queue = self.queue.lock().unwrap();
+            queue.pending.push((request.clone(), sender));
+            let generation = queue.generation;
+            let full = (queue.pending.len() >= self.max_batch_size).then(|| queue.take());
+            (generation, full)
+        };
+
+        if let Some(batch) = full {
+            self.flush(batch).await;
+        } else {
+            // every request waits for the window, so that the batch is sent even if the request
+            // that opened it is dropped
+            let delay = Delay::new(self.window);
+            receiver = match future::select(receiver, delay).await {
+                Either::Left((res, _)) => return into_response(res, request, &self.inner).await,
+                Either::Right((_, receiver)) => receiver,
+            };
+            let batch = {
+                let mut queue = self.queue.lock().unwrap();
+                (queue.generation == generation).then(|| queue.take())
+            };
+            if let Some(batch) = batch {
+                self.flush(batch).await;
+            }
+        }
+
+        into_response(receiver.await, request, &self.inner).await
+    }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        self.inner
+            .request_batch(requests)
+            .await
+            .map_err(|err| BatchingClientError::BatchFailed(Arc::new(err.into())))
+    }
+}
+
+/// Deserializes the response, sending the request on its own if the batch containing it was
+/// dropped before it was sent.
+///
+/// The dropped batch may already have been sent, so only read requests are sent again.
+async fn into_response<T: JsonRpcClient, R: DeserializeOwned>(
+    res: Result<BatchResult, oneshot::Canceled>,
+    request: BatchRequest,
+    inner: &T,
+) -> Result<R, BatchingClientError> {
+    let value = match res {
+        Ok(res) => res?,
+        Err(_) if !is_idempotent(&request.method) => {
+            return Err(BatchingClientError::Dropped(request.method))
+        }
+        Err(_) => match inner.request(&request.method, &request.params).await {
+            Ok(value) => value,
+            Err(err) => match err.as_error_response() {
+                Some(error) => return Err(BatchingClientError::JsonRpcError(error.clone())),
+                None => return Err(BatchingClientError::BatchFailed(Arc::new(err.into()))),
+            },
+        },
+    };
+    serde_json::from_value(value).map_err(|err| BatchingClientError::SerdeJson(err.into()))
+}
+
+/// Returns `true` if sending the request again has no side effects
+fn is_idempotent(method: &str) -> bool {
+    match method {
+        // consumes the changes of the filter
+        "eth_getFilterChanges" => false,
+        method if method.starts_with("eth_get") => true,
+        "eth_blockNumber" |
+        "eth_call" |
+        "eth_chainId" |
+        "eth_createAccessList" |
+        "eth_estimateGas" |
+        "eth_feeHistory" |
+        "eth_gasPrice" |
+        "eth_maxPriorityFeePerGas" |
+        "eth_syncing" |
+        "net_version" |
+        "web3_clientVersion" => true,
+        _ => false,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, MockResponse, Provider};
+    use ethers_core::types::{H256, U256, U64};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts the batches sent to a mock transport
+    #[derive(Debug, Default)]
+    struct CountingMock {
+        mock: MockProvider,
+        batches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for CountingMock {
+        type Error = <MockProvider as JsonRpcClient>::Error;
+
+        async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+        where
+            A: Debug + Serialize + Send + Sync,
+            R: DeserializeOwned + Send,
+        {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            self.mock.request(method, params).await
+        }
+
+        async fn request_batch(
+            &self,
+            requests: &[BatchRequest],
+        ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            let mut responses = Vec::new();
+            for request in requests {
+                let res = self.mock.request(&request.method, &request.params).await;
+                responses.push(crate::rpc::connections::split_error_response(res)?);
+            }
+            Ok(responses)
+        }
+    }
+
+    #[tokio::test]
+    async fn coalesces_concurrent_requests() {
+        let client = BatchingClient::new(CountingMock::default()).window(Duration::from_millis(50));
+        let mock = &client.inner().mock;
+        // the mock pops from the back
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        mock.push(U256::from(1)).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        let provider = Provider::new(client);
+        let (block, chain_id, code) = futures_util::join!(
+            provider.get_block_number(),
+            provider.get_chainid(),
+            provider.get_code(ethers_core::types::Address::zero(), None)
+        );
+        assert_eq!(block.unwrap(), U64::from(10));
+        assert_eq!(chain_id.unwrap(), U256::from(1));
+        assert_eq!(code.unwrap_err().as_error_response().unwrap().message, "execution reverted");
+        assert_eq!(provider.as_ref().inner().batches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sends_full_batches_immediately() {
+        let client = BatchingClient::new(CountingMock::default())
+            .window(Duration::from_secs(60))
+            .max_batch_size(2);
+        let mock = &client.inner().mock;
+        mock.push(U256::from(1)).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        let (block, chain_id) = futures_util::join!(
+            client.request::<_, U64>("eth_blockNumber", ()),
+            client.request::<_, U256>("eth_chainId", ())
+        );
+        assert_eq!(block.unwrap(), U64::from(10));
+        assert_eq!(chain_id.unwrap(), U256::from(1));
+        assert_eq!(client.inner().batches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn resends_only_reads_of_dropped_batches() {
+        let mock = MockProvider::new();
+        mock.push(U64::from(10)).unwrap();
+
+        let request = BatchRequest::new("eth_blockNumber", ());
+        let block: U64 = into_response(Err(oneshot::Canceled), request, &mock).await.unwrap();
+        assert_eq!(block, U64::from(10));
+
+        let request = BatchRequest::new("eth_sendRawTransaction", ["0x01"]);
+        let err = into_response::<_, H256>(Err(oneshot::Canceled), request, &mock).await;
+        assert!(matches!(err, Err(BatchingClientError::Dropped(_))));
+        mock.assert_request("eth_blockNumber", Vec::<()>::new()).unwrap();
+        assert!(mock.assert_request("eth_sendRawTransaction", ["0x01"]).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 // Code adapted from: https://github.com/althea-net/guac_rs/tree/master/web3/src/jsonrpc
 
+use crate::RpcErrorKind;
 use base64::{engine::general_purpose, Engine};
 use ethers_core::{
     abi::AbiDecode,
@@ -38,6 +39,11 @@ fn spelunk_revert(value: &Value) -> Option<Bytes> {
 }
 
 impl JsonRpcError {
+    /// Classify the error into a [`RpcErrorKind`] by its code and message
+    pub fn kind(&self) -> RpcErrorKind {
+        RpcErrorKind::classify(self.code, &self.message)
+    }
+
     /// Determine if the error output of the `eth_call` RPC request is a revert
     ///
     /// Note that this may return false positives if called on an error from
@@ -68,6 +74,22 @@ impl JsonRpcError {
     }
 }
 
+impl crate::RpcError for JsonRpcError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        Some(self)
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        None
+    }
+}
+
+impl From<JsonRpcError> for crate::ProviderError {
+    fn from(src: JsonRpcError) -> Self {
+        crate::ProviderError::JsonRpcClientError(Box::new(src))
+    }
+}
+
 impl fmt::Display for JsonRpcError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "(code: {}, message: {}, data: {:?})", self.code, self.message, self.data)
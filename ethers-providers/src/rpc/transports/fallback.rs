@@ -0,0 +1,491 @@
+use super::quorum::{JsonRpcClientWrapper, QuorumParams};
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient, JsonRpcError, RpcError};
+use async_trait::async_trait;
+use futures_timer::Delay;
+use futures_util::future::join_all;
+use instant::Instant;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+use tracing::{debug, trace};
+
+/// The default number of consecutive failures after which a provider is considered unhealthy
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// The default time after which an unhealthy provider is probed again
+pub const DEFAULT_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A provider that sends every request to one of multiple providers, falling back to the next one
+/// if the request fails.
+///
+/// Providers are tried in the order they were added, unless another [`FallbackStrategy`] is
+/// configured. Only errors of the transport make the request fall back, error responses of the
+/// node (such as reverts) are returned right away.
+///
+/// A provider is marked unhealthy after `failure_threshold` consecutive failures, or when a
+/// response takes longer than `max_latency`. Unhealthy providers are only used once all healthy
+/// ones failed, until they are probed successfully. Probing happens in the background when
+/// [`FallbackProvider::run_health_checks`] is polled, otherwise an unhealthy provider is tried
+/// with regular requests again once `probe_interval` has passed.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::types::U64;
+/// use ethers_providers::{FallbackProvider, FallbackStrategy, Http, JsonRpcClient, Provider};
+/// use std::{str::FromStr, sync::Arc, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = FallbackProvider::builder()
+///     .add_provider(Http::from_str("http://localhost:8545")?)
+///     .add_provider(Http::from_str("http://localhost:8546")?)
+///     .strategy(FallbackStrategy::Latency)
+///     .max_latency(Duration::from_secs(2))
+///     .build();
+///
+/// let provider = Arc::new(provider);
+/// let health_checks = provider.clone();
+/// tokio::spawn(async move { health_checks.run_health_checks().await });
+///
+/// let block_number: U64 = provider.request("eth_blockNumber", ()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FallbackProvider<T = Box<dyn JsonRpcClientWrapper>> {
+    providers: Vec<FallbackEndpoint<T>>,
+    strategy: FallbackStrategy,
+    failure_threshold: u32,
+    max_latency: Option<Duration>,
+    probe_interval: Duration,
+    /// The provider the next round-robin request starts at
+    next: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct FallbackEndpoint<T> {
+    inner: T,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    failures: u32,
+    /// Set while the provider is unhealthy, the time at which it may be probed again
+    unhealthy_until: Option<Instant>,
+    /// The moving average of the response times
+    latency: Option<Duration>,
+}
+
+/// The health of a provider of a [`FallbackProvider`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProviderHealth {
+    /// Whether the provider is considered healthy
+    pub healthy: bool,
+    /// The number of consecutive failed requests
+    pub failures: u32,
+    /// The moving average of the response times, `None` if no request was answered yet
+    pub latency: Option<Duration>,
+}
+
+/// The order in which a [`FallbackProvider`] tries its healthy providers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FallbackStrategy {
+    /// Always prefer providers added earlier
+    #[default]
+    Priority,
+    /// Spread the requests evenly, starting every request at the next provider
+    RoundRobin,
+    /// Prefer the providers with the lowest average response time
+    Latency,
+}
+
+impl FallbackProvider<Box<dyn JsonRpcClientWrapper>> {
+    /// Create a `FallbackProvider` for different `JsonRpcClient` types
+    pub fn dyn_rpc() -> FallbackProviderBuilder<Box<dyn JsonRpcClientWrapper>> {
+        Self::builder()
+    }
+}
+
+impl<T> FallbackProvider<T> {
+    /// Convenience method for creating a `FallbackProviderBuilder` with same `JsonRpcClient` types
+    pub fn builder() -> FallbackProviderBuilder<T> {
+        FallbackProviderBuilder::default()
+    }
+
+    /// Instantiate a new `FallbackProvider` trying the providers in the given order
+    pub fn new(providers: impl IntoIterator<Item = T>) -> Self {
+        Self::builder().add_providers(providers).build()
+    }
+
+    /// Return an iterator over the inner providers
+    pub fn providers(&self) -> impl Iterator<Item = &T> {
+        self.providers.iter().map(|endpoint| &endpoint.inner)
+    }
+
+    /// Returns the health of every provider
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        self.providers
+            .iter()
+            .map(|endpoint| {
+                let health = endpoint.health.lock().unwrap();
+                ProviderHealth {
+                    healthy: health.unhealthy_until.is_none(),
+                    failures: health.failures,
+                    latency: health.latency,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the providers in the order they should be tried
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let (mut available, unavailable): (Vec<_>, Vec<_>) = (0..self.providers.len())
+            .map(|idx| {
+                let health = self.providers[idx].health.lock().unwrap();
+                (idx, health.unhealthy_until.map_or(true, |until| until <= now), health.latency)
+            })
+            .partition(|(_, available, _)| *available);
+
+        match self.strategy {
+            FallbackStrategy::Priority => {}
+            FallbackStrategy::RoundRobin => {
+                if !available.is_empty() {
+                    let start = self.next.fetch_add(1, Ordering::Relaxed) % available.len();
+                    available.rotate_left(start);
+                }
+            }
+            // providers without a measured latency are tried first, to measure them
+            FallbackStrategy::Latency => available.sort_by_key(|(_, _, latency)| *latency),
+        }
+
+        available.into_iter().chain(unavailable).map(|(idx, _, _)| idx).collect()
+    }
+
+    fn record_response(&self, idx: usize, elapsed: Duration) {
+        let mut health = self.providers[idx].health.lock().unwrap();
+        health.failures = 0;
+        health.latency = Some(match health.latency {
+            Some(latency) => (latency * 4 + elapsed) / 5,
+            None => elapsed,
+        });
+        if self.max_latency.map_or(false, |max| elapsed > max) {
+            debug!(provider = idx, ?elapsed, "provider responded too slowly");
+            health.unhealthy_until = Some(Instant::now() + self.probe_interval);
+        } else {
+            health.unhealthy_until = None;
+        }
+    }
+
+    fn record_failure(&self, idx: usize) {
+        let mut health = self.providers[idx].health.lock().unwrap();
+        health.failures += 1;
+        if health.failures >= self.failure_threshold {
+            debug!(provider = idx, failures = health.failures, "provider is unhealthy");
+            health.unhealthy_until = Some(Instant::now() + self.probe_interval);
+        }
+    }
+}
+
+impl<T: JsonRpcClientWrapper> FallbackProvider<T> {
+    /// Probes all unhealthy providers with an `eth_blockNumber` request, marking them healthy
+    /// again if they respond in time
+    pub async fn probe(&self) {
+        let unhealthy = self
+            .health()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, health)| !health.healthy)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        join_all(unhealthy.into_iter().map(|idx| async move {
+            let start = Instant::now();
+            match self.providers[idx].inner.request("eth_blockNumber", QuorumParams::Zst).await {
+                Ok(_) => self.record_response(idx, start.elapsed()),
+                Err(err) => {
+                    trace!(provider = idx, %err, "probe failed");
+                    self.record_failure(idx)
+                }
+            }
+        }))
+        .await;
+    }
+
+    /// Probes the unhealthy providers every `probe_interval`, never returns.
+    ///
+    /// This future should be spawned on the runtime of the application.
+    pub async fn run_health_checks(&self) {
+        loop {
+            Delay::new(self.probe_interval).await;
+            self.probe().await;
+        }
+    }
+}
+
+/// A builder for a [`FallbackProvider`]
+#[derive(Debug)]
+pub struct FallbackProviderBuilder<T> {
+    providers: Vec<T>,
+    strategy: FallbackStrategy,
+    failure_threshold: u32,
+    max_latency: Option<Duration>,
+    probe_interval: Duration,
+}
+
+impl<T> Default for FallbackProviderBuilder<T> {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            strategy: Default::default(),
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            max_latency: None,
+            probe_interval: DEFAULT_PROBE_INTERVAL,
+        }
+    }
+}
+
+impl<T> FallbackProviderBuilder<T> {
+    /// Add a provider with a lower priority than all providers added before
+    pub fn add_provider(mut self, provider: T) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Add providers in the order of their priority
+    pub fn add_providers(mut self, providers: impl IntoIterator<Item = T>) -> Self {
+        self.providers.extend(providers);
+        self
+    }
+
+    /// Set the order in which healthy providers are tried
+    pub fn strategy(mut self, strategy: FallbackStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the number of consecutive failures after which a provider is considered unhealthy
+    pub fn failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    /// Consider a provider unhealthy if it takes longer than `max_latency` to respond
+    pub fn max_latency(mut self, max_latency: Duration) -> Self {
+        self.max_latency = Some(max_latency);
+        self
+    }
+
+    /// Set the time after which unhealthy providers are probed again
+    pub fn probe_interval(mut self, probe_interval: Duration) -> Self {
+        self.probe_interval = probe_interval;
+        self
+    }
+
+    /// Build the `FallbackProvider`
+    pub fn build(self) -> FallbackProvider<T> {
+        FallbackProvider {
+            providers: self
+                .providers
+                .into_iter()
+                .map(|inner| FallbackEndpoint { inner, health: Default::default() })
+                .collect(),
+            strategy: self.strategy,
+            failure_threshold: self.failure_threshold,
+            max_latency: self.max_latency,
+            probe_interval: self.probe_interval,
+            next: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when all providers of a [`FallbackProvider`] failed
+pub enum FallbackError {
+    #[error("All providers failed.")]
+    /// AllProvidersFailed
+    AllProvidersFailed {
+        /// The error of every provider, in the order they were tried
+        errors: Vec<ProviderError>,
+    },
+}
+
+impl crate::RpcError for FallbackError {
+    fn as_error_response(&self) -> Option<&super::JsonRpcError> {
+        None
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        None
+    }
+}
+
+impl From<FallbackError> for ProviderError {
+    fn from(src: FallbackError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(src))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for FallbackProvider<C>
+where
+    C: JsonRpcClientWrapper,
+{
+    type Error = ProviderError;
+
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, Self::Error> {
+        let params = if std::mem::size_of::<T>() == 0 {
+            // we don't want `()` to become `"null"`.
+            QuorumParams::Zst
+        } else {
+            QuorumParams::Value(serde_json::to_value(params)?)
+        };
+
+        let mut errors = Vec::new();
+        for idx in self.order() {
+            let start = Instant::now();
+            match self.providers[idx].inner.request(method, params.clone()).await {
+                Ok(value) => {
+                    self.record_response(idx, start.elapsed());
+                    return Ok(serde_json::from_value(value)?)
+                }
+                // the node answered, so there is no point in asking another one
+                Err(err) if err.is_error_response() => {
+                    self.record_response(idx, start.elapsed());
+                    return Err(err)
+                }
+                Err(err) => {
+                    trace!(provider = idx, %err, "request failed, falling back");
+                    self.record_failure(idx);
+                    errors.push(err);
+                }
+            }
+        }
+
+        Err(FallbackError::AllProvidersFailed { errors }.into())
+    }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        let mut errors = Vec::new();
+        for idx in self.order() {
+            let start = Instant::now();
+            match self.providers[idx].inner.request_batch(requests).await {
+                Ok(responses) => {
+                    self.record_response(idx, start.elapsed());
+                    return Ok(responses)
+                }
+                Err(err) if err.is_error_response() => {
+                    self.record_response(idx, start.elapsed());
+                    return Err(err)
+                }
+                Err(err) => {
+                    trace!(provider = idx, %err, "batch failed, falling back");
+                    self.record_failure(idx);
+                    errors.push(err);
+                }
+            }
+        }
+
+        Err(FallbackError::AllProvidersFailed { errors }.into())
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, MockResponse, Provider};
+    use ethers_core::types::U64;
+
+    #[tokio::test]
+    async fn falls_back_to_next_provider() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        second.push(U64::from(2)).unwrap();
+        second.push(U64::from(1)).unwrap();
+
+        let fallback = FallbackProvider::builder()
+            .add_providers([first.clone(), second.clone()])
+            .failure_threshold(1)
+            .build();
+        let provider = Provider::fallback(fallback);
+
+        // the first provider has no responses and fails
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        first.assert_request("eth_blockNumber", ()).unwrap();
+        second.assert_request("eth_blockNumber", ()).unwrap();
+
+        // now that it is unhealthy, the first provider is skipped
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(2));
+        assert!(first.assert_request("eth_blockNumber", ()).is_err());
+        let health = provider.as_ref().health();
+        assert!(!health[0].healthy);
+        assert!(health[1].healthy);
+
+        // a successful probe restores it
+        first.push(U64::from(3)).unwrap();
+        provider.as_ref().probe().await;
+        assert!(provider.as_ref().health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn returns_error_responses() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        first.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+
+        let provider = Provider::fallback(FallbackProvider::new([first, second.clone()]));
+        let err = provider.get_block_number().await.unwrap_err();
+        assert_eq!(err.as_error_response().unwrap().message, "execution reverted");
+        assert!(second.assert_request("eth_blockNumber", ()).is_err());
+        assert!(provider.as_ref().health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn falls_back_batches() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        second.push(U64::from(1)).unwrap();
+
+        let provider = Provider::fallback(FallbackProvider::new([first.clone(), second]));
+        let mut batch = provider.batch();
+        let block = batch.get_block_number();
+        let responses = batch.send().await.unwrap();
+        assert_eq!(responses.get(block).unwrap(), U64::from(1));
+        first.assert_request("eth_blockNumber", Vec::<()>::new()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn round_robin() {
+        let (first, second) = (MockProvider::new(), MockProvider::new());
+        first.push(U64::from(1)).unwrap();
+        second.push(U64::from(2)).unwrap();
+
+        let fallback = FallbackProvider::builder()
+            .add_providers([first, second])
+            .strategy(FallbackStrategy::RoundRobin)
+            .build();
+        let provider = Provider::fallback(fallback);
+
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(2));
+    }
+}
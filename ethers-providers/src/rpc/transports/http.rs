@@ -1,10 +1,11 @@
 // Code adapted from: https://github.com/althea-net/guac_rs/tree/master/web3/src/jsonrpc
 
 use super::common::{Authorization, JsonRpcError, Request, Response};
-use crate::{errors::ProviderError, JsonRpcClient};
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient};
 use async_trait::async_trait;
 use reqwest::{header::HeaderValue, Client, Error as ReqwestError};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use std::{
     str::FromStr,
     sync::atomic::{AtomicU64, Ordering},
@@ -78,6 +79,23 @@ impl crate::RpcError for ClientError {
             _ => None,
         }
     }
+
+    fn error_kind(&self) -> Option<crate::RpcErrorKind> {
+        match self {
+            ClientError::ReqwestError(err) => crate::RpcErrorKind::from_http_status(err.status()?),
+            ClientError::JsonRpcError(err) => Some(err.kind()),
+            ClientError::SerdeJson { text, .. } => {
+                // some providers send invalid JSON RPC in the error case (no `id:u64`), but the
+                // text should be a `JsonRpcError`
+                #[derive(Deserialize)]
+                struct Resp {
+                    error: JsonRpcError,
+                }
+
+                serde_json::from_str::<Resp>(text).ok().map(|resp| resp.error.kind())
+            }
+        }
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -119,6 +137,73 @@ impl JsonRpcClient for Provider {
 
         Ok(res)
     }
+
+    /// Sends all requests as a single JSON-RPC batch in one HTTP request
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ClientError> {
+        if requests.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let first_id = self.id.fetch_add(requests.len() as u64, Ordering::SeqCst);
+        let payload = requests
+            .iter()
+            .enumerate()
+            .map(|(idx, req)| Request::new(first_id + idx as u64, &req.method, &req.params))
+            .collect::<Vec<_>>();
+
+        let res = self.client.post(self.url.as_ref()).json(&payload).send().await?;
+        let body = res.bytes().await?;
+        let text = || String::from_utf8_lossy(&body).to_string();
+
+        let batch: Vec<Response<'_>> = match serde_json::from_slice(&body) {
+            Ok(batch) => batch,
+            Err(err) => {
+                // the node may reject the whole batch with a single error
+                if let Ok(Response::Error { error, .. }) = serde_json::from_slice(&body) {
+                    return Err(error.into())
+                }
+                return Err(ClientError::SerdeJson { err, text: text() })
+            }
+        };
+
+        let mut responses = vec![None; requests.len()];
+        for response in batch {
+            let (id, res) = match response {
+                Response::Success { id, result } => {
+                    let value = serde_json::from_str(result.get())
+                        .map_err(|err| ClientError::SerdeJson { err, text: result.to_string() })?;
+                    (id, Ok(value))
+                }
+                Response::Error { id, error } => (id, Err(error)),
+                Response::Notification { .. } => {
+                    return Err(ClientError::SerdeJson {
+                        err: serde::de::Error::custom(
+                            "unexpected notification over HTTP transport",
+                        ),
+                        text: text(),
+                    })
+                }
+            };
+            if let Some(slot) =
+                id.checked_sub(first_id).and_then(|idx| responses.get_mut(idx as usize))
+            {
+                *slot = Some(res);
+            }
+        }
+
+        responses
+            .into_iter()
+            .map(|res| {
+                res.ok_or_else(|| ClientError::SerdeJson {
+                    err: serde::de::Error::custom("missing response in batch"),
+                    text: text(),
+                })
+            })
+            .collect()
+    }
 }
 
 impl Provider {
@@ -216,3 +301,70 @@ pub enum HttpClientError {
     #[error(transparent)]
     ClientBuild(#[from] reqwest::Error),
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    /// Serves a single HTTP request with `response`, returning the request body
+    fn serve_once(response: &'static str) -> (Url, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    len = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break
+                }
+            }
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn sends_batch_in_single_request() {
+        // responses may arrive in any order
+        let (url, server) = serve_once(
+            r#"[{"jsonrpc":"2.0","id":2,"error":{"code":-32000,"message":"header not found"}},{"jsonrpc":"2.0","id":1,"result":"0x10"}]"#,
+        );
+        let provider = Provider::new(url);
+
+        let requests = [
+            BatchRequest::new("eth_blockNumber", ()),
+            BatchRequest::new("eth_getBlockByNumber", ("0x100", false)),
+        ];
+        let responses = provider.request_batch(&requests).await.unwrap();
+        assert_eq!(responses[0].as_ref().unwrap(), "0x10");
+        assert_eq!(responses[1].as_ref().unwrap_err().message, "header not found");
+
+        let body: Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!([
+                {"id": 1, "jsonrpc": "2.0", "method": "eth_blockNumber", "params": []},
+                {"id": 2, "jsonrpc": "2.0", "method": "eth_getBlockByNumber", "params": ["0x100", false]},
+            ])
+        );
+    }
+}
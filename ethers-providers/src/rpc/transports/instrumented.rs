@@ -0,0 +1,371 @@
+//! A [JsonRpcClient] implementation that records metrics of the requests it sends.
+
+use super::common::JsonRpcError;
+use crate::{BatchRequest, JsonRpcClient};
+use async_trait::async_trait;
+use instant::Instant;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug, Write as _},
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing_futures::Instrument;
+
+/// The compute units assumed for methods without a known weight, the weight of `eth_getStorageAt`
+pub const DEFAULT_COMPUTE_UNITS: u64 = 17;
+
+/// Returns the compute units charged for a request of `method`.
+///
+/// The weights are the ones published by Alchemy, which other providers charge similarly:
+/// <https://docs.alchemy.com/reference/compute-units>
+pub fn compute_units(method: &str) -> u64 {
+    match method {
+        "eth_chainId" | "net_version" | "net_listening" | "eth_syncing" => 0,
+        "eth_blockNumber" |
+        "eth_feeHistory" |
+        "eth_maxPriorityFeePerGas" |
+        "eth_subscribe" |
+        "eth_unsubscribe" => 10,
+        "eth_getTransactionReceipt" => 15,
+        "eth_getBlockByNumber" => 16,
+        "eth_getStorageAt" | "eth_getTransactionByHash" => 17,
+        "eth_gasPrice" | "eth_getBalance" | "eth_getCode" => 19,
+        "eth_getFilterChanges" | "eth_newFilter" | "eth_newBlockFilter" => 20,
+        "eth_getBlockByHash" => 21,
+        "eth_call" | "eth_getTransactionCount" => 26,
+        "eth_getLogs" | "eth_getFilterLogs" | "trace_call" | "trace_filter" | "trace_block" => 75,
+        "eth_estimateGas" => 87,
+        "eth_sendRawTransaction" => 250,
+        "debug_traceTransaction" | "debug_traceCall" => 309,
+        _ => DEFAULT_COMPUTE_UNITS,
+    }
+}
+
+/// A collector of the metrics of JSON-RPC requests.
+///
+/// [InstrumentedClient] reports every request it sends, [RetryClient](crate::RetryClient) reports
+/// the retries it makes if it was given the collector with
+/// [`RetryClient::set_metrics`](crate::RetryClient::set_metrics).
+pub trait Metrics: Debug + Send + Sync {
+    /// Records a request of `method` which completed after `latency`, successfully or not.
+    fn record_request(&self, method: &str, latency: Duration, compute_units: u64, success: bool);
+
+    /// Records a retry of a request of `method`.
+    fn record_retry(&self, method: &str);
+}
+
+impl<M: Metrics + ?Sized> Metrics for Arc<M> {
+    fn record_request(&self, method: &str, latency: Duration, compute_units: u64, success: bool) {
+        (**self).record_request(method, latency, compute_units, success)
+    }
+
+    fn record_retry(&self, method: &str) {
+        (**self).record_retry(method)
+    }
+}
+
+/// The metrics of the requests of a method.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodMetrics {
+    /// The number of completed requests
+    pub requests: u64,
+    /// The number of requests which failed
+    pub errors: u64,
+    /// The number of retries
+    pub retries: u64,
+    /// The compute units charged for the requests
+    pub compute_units: u64,
+    /// The sum of the latencies of the requests
+    pub total_latency: Duration,
+    /// The highest latency of a request
+    pub max_latency: Duration,
+}
+
+impl MethodMetrics {
+    /// The average latency of the requests
+    pub fn average_latency(&self) -> Duration {
+        if self.requests == 0 {
+            return Duration::ZERO
+        }
+        self.total_latency / self.requests as u32
+    }
+}
+
+impl fmt::Display for MethodMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests, {} errors, {} retries, {} compute units, {:?} average latency",
+            self.requests,
+            self.errors,
+            self.retries,
+            self.compute_units,
+            self.average_latency()
+        )
+    }
+}
+
+/// [Metrics] collected in memory, per method, which can be exported in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    methods: Mutex<HashMap<String, MethodMetrics>>,
+}
+
+impl RequestMetrics {
+    /// Creates an empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the metrics of every method requested so far
+    pub fn snapshot(&self) -> BTreeMap<String, MethodMetrics> {
+        let methods = self.methods.lock().unwrap();
+        methods.iter().map(|(method, metrics)| (method.clone(), metrics.clone())).collect()
+    }
+
+    /// Returns the metrics of `method`
+    pub fn method(&self, method: &str) -> MethodMetrics {
+        self.methods.lock().unwrap().get(method).cloned().unwrap_or_default()
+    }
+
+    /// Resets all metrics
+    pub fn reset(&self) {
+        self.methods.lock().unwrap().clear();
+    }
+
+    /// Encodes the metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+        let counters: [(&str, &str, fn(&MethodMetrics) -> u64); 4] = [
+            ("ethers_rpc_requests_total", "Number of completed JSON-RPC requests", |m| m.requests),
+            ("ethers_rpc_errors_total", "Number of failed JSON-RPC requests", |m| m.errors),
+            ("ethers_rpc_retries_total", "Number of retries of JSON-RPC requests", |m| m.retries),
+            (
+                "ethers_rpc_compute_units_total",
+                "Compute units charged for JSON-RPC requests",
+                |m| m.compute_units,
+            ),
+        ];
+        for (name, help, value) in counters {
+            write_header(&mut out, name, help, "counter");
+            for (method, metrics) in &snapshot {
+                let _ = writeln!(out, "{name}{{method=\"{method}\"}} {}", value(metrics));
+            }
+        }
+
+        let name = "ethers_rpc_request_duration_seconds";
+        write_header(&mut out, name, "Latency of JSON-RPC requests", "summary");
+        for (method, metrics) in &snapshot {
+            let sum = metrics.total_latency.as_secs_f64();
+            let _ = writeln!(out, "{name}_sum{{method=\"{method}\"}} {sum}");
+            let _ = writeln!(out, "{name}_count{{method=\"{method}\"}} {}", metrics.requests);
+        }
+        out
+    }
+
+    fn update(&self, method: &str, f: impl FnOnce(&mut MethodMetrics)) {
+        let mut methods = self.methods.lock().unwrap();
+        match methods.get_mut(method) {
+            Some(metrics) => f(metrics),
+            None => f(methods.entry(method.to_string()).or_default()),
+        }
+    }
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+impl Metrics for RequestMetrics {
+    fn record_request(&self, method: &str, latency: Duration, compute_units: u64, success: bool) {
+        self.update(method, |metrics| {
+            metrics.requests += 1;
+            metrics.errors += u64::from(!success);
+            metrics.compute_units += compute_units;
+            metrics.total_latency += latency;
+            metrics.max_latency = metrics.max_latency.max(latency);
+        })
+    }
+
+    fn record_retry(&self, method: &str) {
+        self.update(method, |metrics| metrics.retries += 1)
+    }
+}
+
+/// [InstrumentedClient] presents as a wrapper around [JsonRpcClient] that reports the latency,
+/// outcome and compute units of every request to a [Metrics] collector, and sends each request
+/// within a `rpc_request` [tracing] span carrying its method and the size of its parameters.
+///
+/// Wrapping a [RetryClient](crate::RetryClient) records a single request per call, wrapping the
+/// transport beneath it records every attempt.
+///
+/// # Example
+///
+/// ```
+/// # async fn demo() {
+/// use ethers_providers::{
+///     Http, HttpRateLimitRetryPolicy, InstrumentedClient, Provider, RequestMetrics,
+///     RetryClientBuilder,
+/// };
+/// use std::sync::Arc;
+/// use url::Url;
+///
+/// let metrics = Arc::new(RequestMetrics::new());
+/// let http = Http::new(Url::parse("http://localhost:8545").unwrap());
+/// let mut retry =
+///     RetryClientBuilder::default().build(http, Box::new(HttpRateLimitRetryPolicy::default()));
+/// retry.set_metrics(metrics.clone());
+/// let provider = Provider::new(InstrumentedClient::new(retry, metrics.clone()));
+///
+/// // ...
+/// println!("{}", metrics.to_prometheus());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InstrumentedClient<T> {
+    inner: T,
+    metrics: Arc<dyn Metrics>,
+    compute_units: HashMap<String, u64>,
+}
+
+impl<T: JsonRpcClient> InstrumentedClient<T> {
+    /// Creates a new `InstrumentedClient` wrapping `inner`, reporting to `metrics`
+    pub fn new(inner: T, metrics: Arc<dyn Metrics>) -> Self {
+        Self { inner, metrics, compute_units: HashMap::new() }
+    }
+
+    /// Overrides the compute units charged for requests of `method`, see [compute_units]
+    #[must_use]
+    pub fn compute_units(mut self, method: impl Into<String>, units: u64) -> Self {
+        self.compute_units.insert(method.into(), units);
+        self
+    }
+
+    /// Returns the metrics collector
+    pub fn metrics(&self) -> &Arc<dyn Metrics> {
+        &self.metrics
+    }
+
+    /// Returns a reference to the wrapped client
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn units(&self, method: &str) -> u64 {
+        self.compute_units.get(method).copied().unwrap_or_else(|| compute_units(method))
+    }
+}
+
+/// Counts the bytes written to it
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the size of the JSON encoding of `params`
+fn params_size<T: Serialize>(params: &T) -> usize {
+    let mut counter = ByteCounter::default();
+    let _ = serde_json::to_writer(&mut counter, params);
+    counter.0
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T> JsonRpcClient for InstrumentedClient<T>
+where
+    T: JsonRpcClient,
+{
+    type Error = T::Error;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let span = tracing::debug_span!("rpc_request", method, params_size = params_size(&params));
+        let start = Instant::now();
+        let res = self.inner.request(method, params).instrument(span).await;
+        self.metrics.record_request(method, start.elapsed(), self.units(method), res.is_ok());
+        res
+    }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        let params_size: usize = requests.iter().map(|req| params_size(&req.params)).sum();
+        let span =
+            tracing::debug_span!("rpc_batch_request", requests = requests.len(), params_size);
+        let start = Instant::now();
+        let res = self.inner.request_batch(requests).instrument(span).await;
+        let latency = start.elapsed();
+        for (i, request) in requests.iter().enumerate() {
+            let success = match &res {
+                Ok(responses) => responses.get(i).map_or(false, Result::is_ok),
+                Err(_) => false,
+            };
+            let units = self.units(&request.method);
+            self.metrics.record_request(&request.method, latency, units, success);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, MockResponse, Provider};
+    use ethers_core::types::U64;
+
+    #[tokio::test]
+    async fn records_requests() {
+        let mock = MockProvider::new();
+        let metrics = Arc::new(RequestMetrics::new());
+        let provider = Provider::new(
+            InstrumentedClient::new(mock.clone(), metrics.clone()).compute_units("eth_chainId", 1),
+        );
+
+        mock.push(U64::from(1)).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        }));
+        mock.push(U64::from(10)).unwrap();
+
+        provider.get_block_number().await.unwrap();
+        provider.get_block_number().await.unwrap_err();
+        provider.get_chainid().await.unwrap();
+        metrics.record_retry("eth_blockNumber");
+
+        let block_number = metrics.method("eth_blockNumber");
+        assert_eq!(block_number.requests, 2);
+        assert_eq!(block_number.errors, 1);
+        assert_eq!(block_number.retries, 1);
+        assert_eq!(block_number.compute_units, 20);
+        assert_eq!(metrics.method("eth_chainId").compute_units, 1);
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("# TYPE ethers_rpc_requests_total counter\n"));
+        assert!(text.contains("ethers_rpc_requests_total{method=\"eth_blockNumber\"} 2\n"));
+        assert!(text.contains("ethers_rpc_errors_total{method=\"eth_blockNumber\"} 1\n"));
+        assert!(
+            text.contains("ethers_rpc_request_duration_seconds_count{method=\"eth_chainId\"} 1")
+        );
+    }
+}
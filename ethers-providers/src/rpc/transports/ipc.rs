@@ -3,10 +3,10 @@ use async_trait::async_trait;
 use bytes::{Buf, BytesMut};
 use ethers_core::types::U256;
 use futures_channel::mpsc;
-use futures_util::stream::StreamExt;
+use futures_util::{future::join_all, stream::StreamExt};
 use hashers::fx_hash::FxHasher64;
 use serde::{de::DeserializeOwned, Serialize};
-use serde_json::{value::RawValue, Deserializer};
+use serde_json::{value::RawValue, Deserializer, Value};
 use std::{
     cell::RefCell,
     convert::Infallible,
@@ -27,7 +27,10 @@ use tokio::{
 };
 
 use super::common::{JsonRpcError, Request, Response};
-use crate::{errors::ProviderError, JsonRpcClient, PubsubClient};
+use crate::{
+    errors::ProviderError, rpc::connections::split_error_response, BatchRequest, JsonRpcClient,
+    PubsubClient,
+};
 
 type FxHashMap<K, V> = std::collections::HashMap<K, V, BuildHasherDefault<FxHasher64>>;
 
@@ -277,6 +280,17 @@ impl JsonRpcClient for Ipc {
         // Parse JSON response.
         Ok(serde_json::from_str(res.get())?)
     }
+
+    /// Sends the requests concurrently as individual messages, not as a JSON-RPC batch array.
+    /// They are multiplexed over the connection, so no request waits for the response of another.
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, IpcError> {
+        let responses =
+            join_all(requests.iter().map(|req| self.request(&req.method, &req.params))).await;
+        responses.into_iter().map(split_error_response).collect()
+    }
 }
 
 impl PubsubClient for Ipc {
@@ -1,18 +1,22 @@
 use crate::{
     errors::ProviderError,
-    rpc::transports::common::{JsonRpcError, Params, Request, Response},
-    JsonRpcClient, PubsubClient,
+    rpc::{
+        connections::split_error_response,
+        transports::common::{JsonRpcError, Params, Request, Response},
+    },
+    BatchRequest, JsonRpcClient, PubsubClient,
 };
 
 use async_trait::async_trait;
 use ethers_core::types::U256;
 use futures_channel::{mpsc, oneshot};
 use futures_util::{
+    future::join_all,
     sink::{Sink, SinkExt},
     stream::{Fuse, Stream, StreamExt},
 };
 use serde::{de::DeserializeOwned, Serialize};
-use serde_json::value::RawValue;
+use serde_json::{value::RawValue, Value};
 use std::{
     collections::{btree_map::Entry, BTreeMap},
     fmt::{self, Debug},
@@ -201,6 +205,17 @@ impl JsonRpcClient for Ws {
         // parse it
         Ok(serde_json::from_str(res.get())?)
     }
+
+    /// Sends the requests concurrently as individual messages, not as a JSON-RPC batch array.
+    /// They are multiplexed over the connection, so no request waits for the response of another.
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ClientError> {
+        let responses =
+            join_all(requests.iter().map(|req| self.request(&req.method, &req.params))).await;
+        responses.into_iter().map(split_error_response).collect()
+    }
 }
 
 impl PubsubClient for Ws {
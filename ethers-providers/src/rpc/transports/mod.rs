@@ -12,12 +12,29 @@ pub use ipc::{Ipc, IpcError};
 mod quorum;
 pub use quorum::{JsonRpcClientWrapper, Quorum, QuorumError, QuorumProvider, WeightedProvider};
 
+mod fallback;
+pub use fallback::{
+    FallbackError, FallbackProvider, FallbackProviderBuilder, FallbackStrategy, ProviderHealth,
+    DEFAULT_FAILURE_THRESHOLD, DEFAULT_PROBE_INTERVAL,
+};
+
 mod rw;
 pub use rw::{RwClient, RwClientError};
 
 mod retry;
 pub use retry::*;
 
+mod instrumented;
+pub use instrumented::{
+    compute_units, InstrumentedClient, MethodMetrics, Metrics, RequestMetrics,
+    DEFAULT_COMPUTE_UNITS,
+};
+
+mod batching;
+pub use batching::{
+    BatchingClient, BatchingClientError, DEFAULT_BATCH_WINDOW, DEFAULT_MAX_BATCH_SIZE,
+};
+
 #[cfg(all(feature = "ws", not(feature = "legacy-ws")))]
 mod ws;
 #[cfg(all(feature = "ws", not(feature = "legacy-ws")))]
@@ -1,4 +1,4 @@
-use crate::{errors::ProviderError, JsonRpcClient, PubsubClient};
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient, JsonRpcError, PubsubClient};
 use async_trait::async_trait;
 use ethers_core::types::{U256, U64};
 use futures_core::Stream;
@@ -389,6 +389,12 @@ impl From<QuorumError> for ProviderError {
 pub trait JsonRpcClientWrapper: Send + Sync + Debug {
     /// Make a request, as [`crate::JsonRpcClient`]
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError>;
+
+    /// Send a batch of requests, as [`crate::JsonRpcClient::request_batch`]
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ProviderError>;
 }
 type NotificationStream =
     Box<dyn futures_core::Stream<Item = Box<RawValue>> + Send + Unpin + 'static>;
@@ -413,6 +419,13 @@ impl<C: JsonRpcClient> JsonRpcClientWrapper for C {
 
         Ok(fut.await.map_err(C::Error::into)?)
     }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ProviderError> {
+        JsonRpcClient::request_batch(self, requests).await.map_err(C::Error::into)
+    }
 }
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -420,6 +433,13 @@ impl JsonRpcClientWrapper for Box<dyn JsonRpcClientWrapper> {
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ProviderError> {
+        self.as_ref().request_batch(requests).await
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -428,6 +448,13 @@ impl JsonRpcClientWrapper for Box<dyn PubsubClientWrapper> {
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, ProviderError> {
+        self.as_ref().request_batch(requests).await
+    }
 }
 
 impl<C: PubsubClient> PubsubClientWrapper for C
@@ -488,6 +515,76 @@ where
         let value = QuorumRequest::new(self, requests).await?;
         Ok(serde_json::from_value(value)?)
     }
+
+    /// Sends the batch to all providers, a response of the batch is only returned if the
+    /// providers reached a quorum on it
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        let mut normalized = Vec::with_capacity(requests.len());
+        for request in requests {
+            let mut params = QuorumParams::Value(request.params.clone());
+            self.normalize_request(&request.method, &mut params).await;
+            let params = match params {
+                QuorumParams::Value(params) => params,
+                QuorumParams::Zst => Value::Array(Vec::new()),
+            };
+            normalized.push(BatchRequest { method: request.method.clone(), params });
+        }
+
+        let batches = join_all(
+            self.providers.iter().map(|provider| provider.inner.request_batch(&normalized)),
+        )
+        .await;
+
+        // the responses of every request with the cumulative weight of their providers
+        let mut responses: Vec<Vec<(Result<Value, JsonRpcError>, u64)>> =
+            vec![Vec::new(); requests.len()];
+        let mut errors = Vec::new();
+        for (batch, provider) in batches.into_iter().zip(&self.providers) {
+            match batch {
+                Ok(batch) if batch.len() == requests.len() => {
+                    for (response, tally) in batch.into_iter().zip(responses.iter_mut()) {
+                        match tally.iter_mut().find(|(r, _)| same_response(r, &response)) {
+                            Some((_, weight)) => *weight += provider.weight,
+                            None => tally.push((response, provider.weight)),
+                        }
+                    }
+                }
+                Ok(batch) => errors.push(ProviderError::CustomError(format!(
+                    "expected {} responses, got {}",
+                    requests.len(),
+                    batch.len()
+                ))),
+                Err(err) => errors.push(err),
+            }
+        }
+
+        let mut quorum = Vec::with_capacity(requests.len());
+        for mut tally in responses {
+            tally.sort_by(|a, b| b.1.cmp(&a.1));
+            match tally.first() {
+                Some((_, weight)) if *weight >= self.quorum_weight => {
+                    quorum.push(tally.swap_remove(0).0)
+                }
+                _ => {
+                    let values = tally.into_iter().filter_map(|(r, _)| r.ok()).collect();
+                    return Err(QuorumError::NoQuorumReached { values, errors }.into())
+                }
+            }
+        }
+        Ok(quorum)
+    }
+}
+
+/// Returns `true` if both responses of a batch are the same value or the same error response
+fn same_response(a: &Result<Value, JsonRpcError>, b: &Result<Value, JsonRpcError>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(a), Err(b)) => a.code == b.code && a.message == b.message && a.data == b.data,
+        _ => false,
+    }
 }
 
 // A stream that returns a value and the weight of its provider
@@ -672,4 +769,30 @@ mod tests {
     async fn all_quorum() {
         test_quorum(Quorum::All).await
     }
+
+    #[tokio::test]
+    async fn batch_quorum() {
+        let mocked = [MockProvider::new(), MockProvider::new(), MockProvider::new()];
+        for (mock, block) in mocked.iter().zip([1u64, 1, 2]) {
+            mock.push(U64::from(block)).unwrap();
+        }
+        let quorum = QuorumProvider::builder()
+            .add_providers(mocked.iter().cloned().map(WeightedProvider::new))
+            .quorum(Quorum::Majority)
+            .build();
+        let provider = Provider::quorum(quorum);
+
+        let mut batch = provider.batch();
+        let block = batch.get_block_number();
+        let responses = batch.send().await.unwrap();
+        assert_eq!(responses.get(block).unwrap(), U64::from(1));
+        for mock in &mocked {
+            mock.assert_request("eth_blockNumber", Vec::<()>::new()).unwrap();
+        }
+
+        // without responses, no quorum is reached
+        let mut batch = provider.batch();
+        batch.get_block_number();
+        assert!(batch.send().await.is_err());
+    }
 }
@@ -1,13 +1,18 @@
 //! A [JsonRpcClient] implementation that retries requests filtered by [RetryPolicy]
 //! with an exponential backoff.
 
-use super::{common::JsonRpcError, http::ClientError};
-use crate::{errors::ProviderError, JsonRpcClient};
+use super::{common::JsonRpcError, http::ClientError, Metrics};
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient, RpcError};
 use async_trait::async_trait;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use std::{
     fmt::Debug,
-    sync::atomic::{AtomicU32, Ordering},
+    future::Future,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use thiserror::Error;
@@ -64,6 +69,8 @@ where
     initial_backoff: Duration,
     /// available CPU per second
     compute_units_per_second: u64,
+    /// The collector the retries are reported to
+    metrics: Option<Arc<dyn Metrics>>,
 }
 
 impl<T> RetryClient<T>
@@ -112,6 +119,21 @@ where
         self.compute_units_per_second = cpus;
         self
     }
+
+    /// Sets the collector every retry is reported to.
+    ///
+    /// This is usually the collector of the [InstrumentedClient](super::InstrumentedClient)
+    /// wrapping this client.
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn record_retry(&self, method: &str) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_retry(method);
+        }
+    }
 }
 
 /// Builder for a [`RetryClient`]
@@ -178,6 +200,7 @@ impl RetryClientBuilder {
             rate_limit_retries,
             initial_backoff,
             compute_units_per_second,
+            metrics: None,
         }
     }
 }
@@ -246,58 +269,30 @@ impl From<RetryClientError> for ProviderError {
     }
 }
 
-#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
-impl<T> JsonRpcClient for RetryClient<T>
+impl<T> RetryClient<T>
 where
     T: JsonRpcClient + 'static,
     T::Error: Sync + Send + 'static,
 {
-    type Error = RetryClientError;
-
-    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    /// Sends the request built by `send` until it succeeds or the [RetryPolicy] gives up
+    async fn retry<F, Fut, R>(&self, method: &str, send: F) -> Result<R, RetryClientError>
     where
-        A: Debug + Serialize + Send + Sync,
-        R: DeserializeOwned + Send,
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<R, T::Error>>,
     {
-        // Helper type that caches the `params` value across several retries
-        // This is necessary because the wrapper provider is supposed to skip he `params` if it's of
-        // size 0, see `crate::transports::common::Request`
-        enum RetryParams<Params> {
-            Value(Params),
-            Zst(()),
-        }
-
-        let params = if std::mem::size_of::<A>() == 0 {
-            RetryParams::Zst(())
-        } else {
-            let params = serde_json::to_value(params).map_err(RetryClientError::SerdeJson)?;
-            RetryParams::Value(params)
-        };
-
         let ahead_in_queue = self.requests_enqueued.fetch_add(1, Ordering::SeqCst) as u64;
 
         let mut rate_limit_retry_number: u32 = 0;
         let mut timeout_retries: u32 = 0;
 
         loop {
-            let err;
-
-            // hack to not hold `R` across an await in the sleep future and prevent requiring
-            // R: Send + Sync
-            {
-                let resp = match params {
-                    RetryParams::Value(ref params) => self.inner.request(method, params).await,
-                    RetryParams::Zst(unit) => self.inner.request(method, unit).await,
-                };
-                match resp {
-                    Ok(ret) => {
-                        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
-                        return Ok(ret)
-                    }
-                    Err(err_) => err = err_,
+            let err = match send().await {
+                Ok(ret) => {
+                    self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+                    return Ok(ret)
                 }
-            }
+                Err(err) => err,
+            };
 
             let should_retry = self.policy.should_retry(&err);
             if should_retry {
@@ -333,6 +328,7 @@ where
                 next_backoff += Duration::from_secs(seconds_to_wait_for_compute_budget);
 
                 trace!("retrying and backing off for {:?}", next_backoff);
+                self.record_retry(method);
 
                 #[cfg(target_arch = "wasm32")]
                 futures_timer::Delay::new(next_backoff).await;
@@ -344,6 +340,7 @@ where
                 if timeout_retries < self.timeout_retries && maybe_connectivity(&err) {
                     timeout_retries += 1;
                     trace!(err = ?err, "retrying due to spurious network");
+                    self.record_retry(method);
                     continue
                 }
 
@@ -355,61 +352,68 @@ where
     }
 }
 
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T> JsonRpcClient for RetryClient<T>
+where
+    T: JsonRpcClient + 'static,
+    T::Error: Sync + Send + 'static,
+{
+    type Error = RetryClientError;
+
+    async fn request<A, R>(&self, method: &str, params: A) -> Result<R, Self::Error>
+    where
+        A: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // Helper type that caches the `params` value across several retries
+        // This is necessary because the wrapper provider is supposed to skip he `params` if it's of
+        // size 0, see `crate::transports::common::Request`
+        enum RetryParams<Params> {
+            Value(Params),
+            Zst(()),
+        }
+
+        let params = if std::mem::size_of::<A>() == 0 {
+            RetryParams::Zst(())
+        } else {
+            let params = serde_json::to_value(params).map_err(RetryClientError::SerdeJson)?;
+            RetryParams::Value(params)
+        };
+
+        let params = &params;
+        self.retry(method, move || async move {
+            match params {
+                RetryParams::Value(params) => self.inner.request(method, params).await,
+                RetryParams::Zst(unit) => self.inner.request(method, unit).await,
+            }
+        })
+        .await
+    }
+
+    /// Retries the whole batch on errors of the transport, error responses of single requests
+    /// are returned as is.
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        self.retry("batch", || self.inner.request_batch(requests)).await
+    }
+}
+
 /// Implements [RetryPolicy] that will retry requests that errored with
-/// status code 429 i.e. TOO_MANY_REQUESTS
+/// status code 429 i.e. TOO_MANY_REQUESTS, or any other error classified as
+/// [`RpcErrorKind::RateLimited`](crate::RpcErrorKind::RateLimited)
 ///
 /// Infura often fails with a `"header not found"` rpc error which is apparently linked to load
-/// balancing, which are retried as well.
+/// balancing, which are retried as well, see
+/// [`RpcErrorKind::BlockNotFound`](crate::RpcErrorKind::BlockNotFound).
 #[derive(Debug, Default)]
 pub struct HttpRateLimitRetryPolicy;
 
 impl RetryPolicy<ClientError> for HttpRateLimitRetryPolicy {
     fn should_retry(&self, error: &ClientError) -> bool {
-        fn should_retry_json_rpc_error(err: &JsonRpcError) -> bool {
-            let JsonRpcError { code, message, .. } = err;
-            // alchemy throws it this way
-            if *code == 429 {
-                return true
-            }
-
-            // This is an infura error code for `exceeded project rate limit`
-            if *code == -32005 {
-                return true
-            }
-
-            // alternative alchemy error for specific IPs
-            if *code == -32016 && message.contains("rate limit") {
-                return true
-            }
-
-            match message.as_str() {
-                // this is commonly thrown by infura and is apparently a load balancer issue, see also <https://github.com/MetaMask/metamask-extension/issues/7234>
-                "header not found" => true,
-                // also thrown by infura if out of budget for the day and ratelimited
-                "daily request count exceeded, request rate limited" => true,
-                _ => false,
-            }
-        }
-
-        match error {
-            ClientError::ReqwestError(err) => {
-                err.status() == Some(http::StatusCode::TOO_MANY_REQUESTS)
-            }
-            ClientError::JsonRpcError(err) => should_retry_json_rpc_error(err),
-            ClientError::SerdeJson { text, .. } => {
-                // some providers send invalid JSON RPC in the error case (no `id:u64`), but the
-                // text should be a `JsonRpcError`
-                #[derive(Deserialize)]
-                struct Resp {
-                    error: JsonRpcError,
-                }
-
-                if let Ok(resp) = serde_json::from_str::<Resp>(text) {
-                    return should_retry_json_rpc_error(&resp.error)
-                }
-                false
-            }
-        }
+        error.error_kind().map_or(false, |kind| kind.is_transient())
     }
 
     fn backoff_hint(&self, error: &ClientError) -> Option<Duration> {
@@ -1,9 +1,10 @@
 //! A [JsonRpcClient] implementation that serves as a wrapper around two different [JsonRpcClient]
 //! and uses a dedicated client for read and the other for write operations
 
-use crate::{errors::ProviderError, JsonRpcClient};
+use crate::{errors::ProviderError, BatchRequest, JsonRpcClient, JsonRpcError};
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use thiserror::Error;
 
 /// A client containing two clients.
@@ -139,4 +140,20 @@ where
             _ => self.r.request(method, params).await.map_err(RwClientError::Read),
         }
     }
+
+    /// Sends the batch to the _write_ client if it contains a write operation, otherwise to the
+    /// _read_ client
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, Self::Error> {
+        let writes = requests.iter().any(|request| {
+            matches!(request.method.as_str(), "eth_sendTransaction" | "eth_sendRawTransaction")
+        });
+        if writes {
+            self.w.request_batch(requests).await.map_err(RwClientError::Write)
+        } else {
+            self.r.request_batch(requests).await.map_err(RwClientError::Read)
+        }
+    }
 }
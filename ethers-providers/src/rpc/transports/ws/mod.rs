@@ -14,12 +14,19 @@ use types::*;
 mod error;
 pub use error::*;
 
-use crate::{JsonRpcClient, ProviderError, PubsubClient};
+use crate::{
+    rpc::connections::split_error_response, BatchRequest, JsonRpcClient, JsonRpcError,
+    ProviderError, PubsubClient,
+};
 use async_trait::async_trait;
 use ethers_core::types::U256;
 use futures_channel::{mpsc, oneshot};
+use futures_util::future::join_all;
 use serde::{de::DeserializeOwned, Serialize};
-use serde_json::value::{to_raw_value, RawValue};
+use serde_json::{
+    value::{to_raw_value, RawValue},
+    Value,
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::Authorization;
@@ -120,6 +127,17 @@ impl JsonRpcClient for WsClient {
 
         Ok(res)
     }
+
+    /// Sends the requests concurrently as individual messages, not as a JSON-RPC batch array.
+    /// They are multiplexed over the connection, so no request waits for the response of another.
+    async fn request_batch(
+        &self,
+        requests: &[BatchRequest],
+    ) -> Result<Vec<Result<Value, JsonRpcError>>, WsClientError> {
+        let responses =
+            join_all(requests.iter().map(|req| self.request(&req.method, &req.params))).await;
+        responses.into_iter().map(split_error_response).collect()
+    }
 }
 
 impl PubsubClient for WsClient {
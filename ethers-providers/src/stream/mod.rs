@@ -1,3 +1,5 @@
+pub mod reorg;
+
 pub mod tx_stream;
 
 pub mod watcher;
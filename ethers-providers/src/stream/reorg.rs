@@ -0,0 +1,514 @@
+use crate::{utils::interval, Middleware, DEFAULT_POLL_INTERVAL};
+use ethers_core::types::{Block, Filter, Log, TxHash, H256, U64};
+use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+    vec::IntoIter,
+};
+
+/// The default number of recent blocks a [`ReorgTracker`] remembers
+pub const DEFAULT_REORG_WINDOW: usize = 64;
+
+/// A canonical block together with the logs of the tracker's filter it contains
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainBlock {
+    /// The block
+    pub block: Block<TxHash>,
+    /// The logs of the block matching the tracker's filter, empty if no filter is set
+    pub logs: Vec<Log>,
+}
+
+impl ChainBlock {
+    /// The number of the block
+    pub fn number(&self) -> U64 {
+        self.block.number.unwrap_or_default()
+    }
+
+    /// The hash of the block
+    pub fn hash(&self) -> H256 {
+        self.block.hash.unwrap_or_default()
+    }
+}
+
+/// An update of the canonical chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum ReorgEvent {
+    /// A new block extending the canonical chain
+    NewBlock(ChainBlock),
+    /// Blocks that were emitted earlier have been orphaned and replaced.
+    ///
+    /// Both lists are ordered from the oldest to the newest block. The logs of the removed blocks
+    /// are marked with `removed: true`.
+    Reorged {
+        /// The orphaned blocks
+        removed: Vec<ChainBlock>,
+        /// The blocks of the new canonical chain, starting right after the common ancestor
+        added: Vec<ChainBlock>,
+    },
+}
+
+/// Follows the canonical chain by polling a provider and detects reorganizations.
+///
+/// The tracker remembers a window of the most recent blocks it emitted. Whenever a new block's
+/// parent hash does not match the remembered block, it walks back along the new chain until both
+/// chains join again and reports the orphaned and replacing blocks as a
+/// [`ReorgEvent::Reorged`]. Reorgs deeper than the window replace the whole window.
+#[derive(Clone, Debug)]
+pub struct ReorgTracker {
+    window: VecDeque<ChainBlock>,
+    capacity: usize,
+    filter: Option<Filter>,
+}
+
+impl Default for ReorgTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORG_WINDOW)
+    }
+}
+
+impl ReorgTracker {
+    /// Creates a new tracker remembering the last `capacity` blocks
+    pub fn new(capacity: usize) -> Self {
+        Self { window: VecDeque::new(), capacity: capacity.max(1), filter: None }
+    }
+
+    /// Sets a filter whose matching logs are fetched for every block.
+    ///
+    /// The block range of the filter is ignored, logs are always fetched by block hash.
+    #[must_use]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Continues following the chain after `blocks`, e.g. the blocks remembered by a previous
+    /// run, instead of starting at the current head.
+    ///
+    /// The blocks must form a chain, ordered from the oldest to the newest.
+    #[must_use]
+    pub fn with_blocks(mut self, blocks: impl IntoIterator<Item = ChainBlock>) -> Self {
+        self.window.extend(blocks);
+        while self.window.len() > self.capacity {
+            self.window.pop_front();
+        }
+        self
+    }
+
+    /// Returns the most recent canonical block, if any
+    pub fn head(&self) -> Option<&ChainBlock> {
+        self.window.back()
+    }
+
+    /// Returns the remembered blocks, ordered from the oldest to the newest
+    pub fn blocks(&self) -> impl Iterator<Item = &ChainBlock> {
+        self.window.iter()
+    }
+
+    fn get(&self, number: U64) -> Option<&ChainBlock> {
+        self.window.iter().rev().find(|block| block.number() == number)
+    }
+
+    /// Fetches the blocks the provider has seen since the last call and returns the resulting
+    /// events.
+    ///
+    /// The first call only fetches the current head block.
+    ///
+    /// If fetching a block fails after earlier blocks were already applied to the window, the
+    /// events of those blocks are returned and the error is left to the next call, which retries
+    /// from the new head.
+    pub async fn poll<M: Middleware>(&mut self, provider: &M) -> Result<Vec<ReorgEvent>, M::Error> {
+        let head = provider.get_block_number().await?;
+        self.poll_to(provider, head).await
+    }
+
+    /// Like [`Self::poll`], but with the number of the provider's head block already known
+    pub async fn poll_to<M: Middleware>(
+        &mut self,
+        provider: &M,
+        head: U64,
+    ) -> Result<Vec<ReorgEvent>, M::Error> {
+        let mut events = Vec::new();
+
+        let last = match self.head() {
+            Some(block) if block.number() < head => block.number(),
+            Some(_) => {
+                // no new height, but the tip may have been replaced
+                let known = self.get(head).map(ChainBlock::hash);
+                if let Some(block) = provider.get_block(head).await? {
+                    if block.hash.is_some() && block.hash != known {
+                        self.apply(provider, block, &mut events).await?;
+                    }
+                }
+                return Ok(events)
+            }
+            None => {
+                if let Some(block) = provider.get_block(head).await? {
+                    self.apply(provider, block, &mut events).await?;
+                }
+                return Ok(events)
+            }
+        };
+
+        for number in last.as_u64() + 1..=head.as_u64() {
+            let applied = match provider.get_block(number).await {
+                Ok(Some(block)) => self.apply(provider, block, &mut events).await,
+                Ok(None) => break,
+                Err(err) => Err(err),
+            };
+            match applied {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(err) if events.is_empty() => return Err(err),
+                Err(err) => {
+                    // the window already moved past the collected events, hand them out first
+                    tracing::debug!(?err, block = number, "stopped catching up");
+                    break
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Connects `block` to the window, returns `false` if that was not possible yet
+    async fn apply<M: Middleware>(
+        &mut self,
+        provider: &M,
+        block: Block<TxHash>,
+        events: &mut Vec<ReorgEvent>,
+    ) -> Result<bool, M::Error> {
+        if block.number.is_none() || block.hash.is_none() {
+            // pending block
+            return Ok(false)
+        }
+
+        // walk back along the new chain until it joins the window
+        let mut added = vec![block];
+        loop {
+            let oldest = added.last().expect("not empty");
+            let number = oldest.number.unwrap_or_default();
+            let Some(parent) = number.checked_sub(1.into()).and_then(|number| self.get(number))
+            else {
+                break
+            };
+            if parent.hash() == oldest.parent_hash {
+                break
+            }
+            match provider.get_block(oldest.parent_hash).await? {
+                Some(parent) if parent.number.is_some() && parent.hash.is_some() => {
+                    added.push(parent)
+                }
+                // the node does not know the parent yet, retry on the next poll
+                _ => return Ok(false),
+            }
+        }
+        added.reverse();
+
+        // fetch everything before touching the window, so that an error leaves it consistent
+        let mut blocks = Vec::with_capacity(added.len());
+        for block in added {
+            let logs = match &self.filter {
+                Some(filter) => {
+                    let filter = filter.clone().at_block_hash(block.hash.unwrap_or_default());
+                    provider.get_logs(&filter).await?
+                }
+                None => Vec::new(),
+            };
+            blocks.push(ChainBlock { block, logs });
+        }
+
+        let fork = blocks[0].number();
+        let mut removed = Vec::new();
+        while self.window.back().map_or(false, |block| block.number() >= fork) {
+            removed.push(self.window.pop_back().expect("not empty"));
+        }
+        removed.reverse();
+        for log in removed.iter_mut().flat_map(|block| block.logs.iter_mut()) {
+            log.removed = Some(true);
+        }
+
+        self.window.extend(blocks.iter().cloned());
+        while self.window.len() > self.capacity {
+            self.window.pop_front();
+        }
+
+        if removed.is_empty() {
+            events.extend(blocks.into_iter().map(ReorgEvent::NewBlock));
+        } else {
+            events.push(ReorgEvent::Reorged { removed, added: blocks });
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type ReorgFut<'a, E> =
+    Pin<Box<dyn Future<Output = (ReorgTracker, Result<Vec<ReorgEvent>, E>)> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+type ReorgFut<'a, E> =
+    Pin<Box<dyn Future<Output = (ReorgTracker, Result<Vec<ReorgEvent>, E>)> + 'a>>;
+
+enum ReorgStreamState<'a, E> {
+    WaitForInterval,
+    Poll(ReorgFut<'a, E>),
+    NextItem(IntoIter<ReorgEvent>),
+}
+
+/// Streams the canonical chain of a provider as [`ReorgEvent`]s.
+///
+/// Unlike [`Middleware::watch_blocks`], the stream reports when blocks (and their logs) it
+/// emitted earlier have been orphaned. Errors of the provider are yielded without terminating the
+/// stream, the next poll picks up where the failed one stopped.
+///
+/// ```no_run
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// use ethers_core::types::Filter;
+/// use ethers_providers::{Http, Provider, ReorgEvent, ReorgStream};
+/// use futures_util::StreamExt;
+///
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let mut stream = ReorgStream::new(&provider).filter(Filter::new().event("Transfer(address,address,uint256)"));
+/// while let Some(event) = stream.next().await {
+///     match event? {
+///         ReorgEvent::NewBlock(block) => println!("new block {}", block.number()),
+///         ReorgEvent::Reorged { removed, added } => {
+///             println!("{} blocks orphaned, {} blocks added", removed.len(), added.len())
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct ReorgStream<'a, M: Middleware> {
+    provider: &'a M,
+    /// The tracker, taken by the pending poll
+    tracker: Option<ReorgTracker>,
+    // The polling interval
+    interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
+    /// statemachine driven by the Stream impl
+    state: ReorgStreamState<'a, M::Error>,
+}
+
+impl<'a, M: Middleware> ReorgStream<'a, M> {
+    /// Creates a new stream following the chain of `provider`
+    pub fn new(provider: &'a M) -> Self {
+        Self::with_tracker(provider, ReorgTracker::default())
+    }
+
+    /// Creates a new stream driving the given tracker
+    pub fn with_tracker(provider: &'a M, tracker: ReorgTracker) -> Self {
+        Self {
+            provider,
+            tracker: Some(tracker),
+            interval: Box::new(interval(DEFAULT_POLL_INTERVAL)),
+            state: ReorgStreamState::WaitForInterval,
+        }
+    }
+
+    /// Sets the stream's polling interval
+    pub fn interval(mut self, duration: Duration) -> Self {
+        self.interval = Box::new(interval(duration));
+        self
+    }
+
+    /// Sets the number of recent blocks remembered to detect reorgs
+    pub fn window(mut self, capacity: usize) -> Self {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.capacity = capacity.max(1);
+        }
+        self
+    }
+
+    /// Sets a filter whose matching logs are emitted with every block
+    pub fn filter(mut self, filter: Filter) -> Self {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.filter = Some(filter);
+        }
+        self
+    }
+}
+
+impl<'a, M: Middleware + 'a> Stream for ReorgStream<'a, M> {
+    type Item = Result<ReorgEvent, M::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            this.state = match &mut this.state {
+                ReorgStreamState::WaitForInterval => {
+                    let _ready = futures_util::ready!(this.interval.poll_next_unpin(cx));
+                    let mut tracker = this.tracker.take().expect("tracker is returned by the poll");
+                    let provider = this.provider;
+                    ReorgStreamState::Poll(Box::pin(async move {
+                        let res = tracker.poll(provider).await;
+                        (tracker, res)
+                    }))
+                }
+                ReorgStreamState::Poll(fut) => {
+                    let (tracker, res) = futures_util::ready!(fut.as_mut().poll(cx));
+                    this.tracker = Some(tracker);
+                    match res {
+                        Ok(events) => ReorgStreamState::NextItem(events.into_iter()),
+                        Err(err) => {
+                            this.state = ReorgStreamState::WaitForInterval;
+                            return Poll::Ready(Some(Err(err)))
+                        }
+                    }
+                }
+                ReorgStreamState::NextItem(iter) => {
+                    if let Some(event) = iter.next() {
+                        return Poll::Ready(Some(Ok(event)))
+                    }
+                    ReorgStreamState::WaitForInterval
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, MockResponse, Provider};
+
+    fn block(number: u64, hash: u64, parent: u64) -> Block<TxHash> {
+        Block {
+            number: Some(number.into()),
+            hash: Some(H256::from_low_u64_be(hash)),
+            parent_hash: H256::from_low_u64_be(parent),
+            ..Default::default()
+        }
+    }
+
+    fn log(block: u64, hash: u64) -> Log {
+        Log {
+            block_number: Some(block.into()),
+            block_hash: Some(H256::from_low_u64_be(hash)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_reorg_with_removed_logs() {
+        let (provider, mock) = Provider::mocked();
+        let mut tracker = ReorgTracker::default().filter(Filter::new());
+
+        mock.push::<Vec<Log>, _>(vec![log(1, 0xa1)]).unwrap();
+        mock.push(block(1, 0xa1, 0xa0)).unwrap();
+        mock.push(U64::from(1)).unwrap();
+        let events = tracker.poll(&provider).await.unwrap();
+        assert_eq!(
+            events,
+            vec![ReorgEvent::NewBlock(ChainBlock {
+                block: block(1, 0xa1, 0xa0),
+                logs: vec![log(1, 0xa1)]
+            })]
+        );
+
+        mock.push::<Vec<Log>, _>(vec![log(2, 0xa2)]).unwrap();
+        mock.push(block(2, 0xa2, 0xa1)).unwrap();
+        mock.push(U64::from(2)).unwrap();
+        let events = tracker.poll(&provider).await.unwrap();
+        assert!(matches!(&events[..], [ReorgEvent::NewBlock(block)] if block.number() == 2.into()));
+
+        // block 2 gets replaced by a chain of 2 blocks
+        mock.push::<Vec<Log>, _>(Vec::<Log>::new()).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(2, 0xb2)]).unwrap();
+        mock.push(block(2, 0xb2, 0xa1)).unwrap();
+        mock.push(block(3, 0xb3, 0xb2)).unwrap();
+        mock.push(U64::from(3)).unwrap();
+        let events = tracker.poll(&provider).await.unwrap();
+
+        let mut removed_log = log(2, 0xa2);
+        removed_log.removed = Some(true);
+        assert_eq!(
+            events,
+            vec![ReorgEvent::Reorged {
+                removed: vec![ChainBlock { block: block(2, 0xa2, 0xa1), logs: vec![removed_log] }],
+                added: vec![
+                    ChainBlock { block: block(2, 0xb2, 0xa1), logs: vec![log(2, 0xb2)] },
+                    ChainBlock { block: block(3, 0xb3, 0xb2), logs: vec![] },
+                ]
+            }]
+        );
+        let hashes = tracker.blocks().map(ChainBlock::hash).collect::<Vec<_>>();
+        assert_eq!(
+            hashes,
+            vec![
+                H256::from_low_u64_be(0xa1),
+                H256::from_low_u64_be(0xb2),
+                H256::from_low_u64_be(0xb3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_detects_replaced_tip() {
+        let (provider, mock) = Provider::mocked();
+
+        // second poll: same height, different tip
+        mock.push(block(2, 0xb2, 0xa1)).unwrap();
+        mock.push(U64::from(2)).unwrap();
+        // first poll
+        mock.push(block(2, 0xa2, 0xa1)).unwrap();
+        mock.push(U64::from(2)).unwrap();
+
+        let mut stream = ReorgStream::new(&provider).interval(Duration::from_millis(1)).window(2);
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            event,
+            ReorgEvent::NewBlock(ChainBlock { block: block(2, 0xa2, 0xa1), logs: vec![] })
+        );
+
+        let event = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            event,
+            ReorgEvent::Reorged {
+                removed: vec![ChainBlock { block: block(2, 0xa2, 0xa1), logs: vec![] }],
+                added: vec![ChainBlock { block: block(2, 0xb2, 0xa1), logs: vec![] }],
+            }
+        );
+
+        // the mock ran out of responses
+        assert!(stream.next().await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn keeps_events_on_error_while_catching_up() {
+        let (provider, mock) = Provider::mocked();
+        let mut tracker = ReorgTracker::default()
+            .with_blocks([ChainBlock { block: block(1, 0xa1, 0xa0), logs: vec![] }]);
+
+        // block 3 can't be fetched
+        mock.push(block(3, 0xa3, 0xa2)).unwrap();
+        mock.push(U64::from(3)).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        }));
+        mock.push(block(2, 0xa2, 0xa1)).unwrap();
+        mock.push(U64::from(3)).unwrap();
+
+        let events = tracker.poll(&provider).await.unwrap();
+        assert_eq!(
+            events,
+            vec![ReorgEvent::NewBlock(ChainBlock { block: block(2, 0xa2, 0xa1), logs: vec![] })]
+        );
+
+        // the next poll continues after block 2
+        let events = tracker.poll(&provider).await.unwrap();
+        assert_eq!(
+            events,
+            vec![ReorgEvent::NewBlock(ChainBlock { block: block(3, 0xa3, 0xa2), logs: vec![] })]
+        );
+    }
+}
@@ -1,23 +1,51 @@
-use crate::{utils::PinBoxFut, JsonRpcClient, Middleware, Provider, ProviderError};
-use ethers_core::types::{Filter, Log, U64};
+use crate::{
+    utils::{interval, PinBoxFut},
+    JsonRpcClient, Middleware, Provider, ProviderError, RpcError, RpcErrorKind,
+};
+use ethers_core::types::{Filter, Log, U256, U64};
 use futures_core::stream::Stream;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use thiserror::Error;
 
+/// Pages returning fewer logs than this let the page size grow
+const SPARSE_PAGE_LOGS: usize = 1000;
+
 /// A log query provides streaming access to historical logs via a paginated
 /// request. For streaming access to future logs, use [`Middleware::watch`] or
 /// [`Middleware::subscribe_logs`]
+///
+/// When the node rejects a page (e.g. because it contains too many logs or spans too many blocks),
+/// the page is split in half and retried, while other errors are returned as is. If even a single
+/// block is rejected, the error is returned and the query ends. Pages grow again
+/// while they return few logs, up to [`LogQuery::with_max_page_size`]. The progress of the query
+/// can be saved with [`LogQuery::checkpoint`] and resumed with [`LogQuery::with_checkpoint`], and
+/// [`LogQuery::with_live_tail`] keeps following new blocks once the head of the chain is
+/// reached.
 pub struct LogQuery<'a, P> {
     provider: &'a Provider<P>,
     filter: Filter,
     from_block: Option<U64>,
     page_size: u64,
+    /// The size pages may grow to, pages never grow if unset
+    max_page_size: Option<u64>,
+    /// The block range of the page being loaded
+    page: Option<(U64, U64)>,
+    /// The last block of the page being consumed
+    page_end: Option<U64>,
     current_logs: VecDeque<Log>,
     last_block: Option<U64>,
+    /// The position right after the last yielded log
+    cursor: Option<(U64, Option<U256>)>,
+    /// Logs at or before this position are skipped when resuming from a checkpoint
+    skip: Option<(U64, U256)>,
+    live_tail: Option<Box<dyn Stream<Item = ()> + Send + Unpin>>,
     state: LogQueryState<'a>,
 }
 
@@ -26,6 +54,24 @@ enum LogQueryState<'a> {
     LoadLastBlock(PinBoxFut<'a, U64>),
     LoadLogs(PinBoxFut<'a, Vec<Log>>),
     Consume,
+    WaitForInterval,
+    /// The node rejected a single block range, which can't be split any further
+    Failed,
+}
+
+/// The progress of a paginated [`LogQuery`], which can be persisted to resume the query later.
+///
+/// All logs of blocks before `block` and the logs of `block` up to `log_index` have been
+/// yielded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQueryCheckpoint {
+    /// The block to resume from
+    pub block: U64,
+    /// The index of the last yielded log of `block`, `None` if none of its logs were yielded
+    pub log_index: Option<U256>,
+    /// The page size the query had adapted to
+    pub page_size: u64,
 }
 
 impl<'a, P> LogQuery<'a, P>
@@ -34,13 +80,20 @@ where
 {
     /// Instantiate a new `LogQuery`
     pub fn new(provider: &'a Provider<P>, filter: &Filter) -> Self {
+        let from_block = filter.get_from_block();
         Self {
             provider,
             filter: filter.clone(),
-            from_block: filter.get_from_block(),
+            from_block,
             page_size: 10000,
+            max_page_size: None,
+            page: None,
+            page_end: None,
             current_logs: VecDeque::new(),
             last_block: None,
+            cursor: from_block.map(|block| (block, None)),
+            skip: None,
+            live_tail: None,
             state: LogQueryState::Initial,
         }
     }
@@ -50,6 +103,54 @@ where
         self.page_size = page_size;
         self
     }
+
+    /// set the size pages may grow to while they return few logs
+    pub fn with_max_page_size(mut self, max_page_size: u64) -> Self {
+        self.max_page_size = Some(max_page_size);
+        self.page_size = self.page_size.min(max_page_size);
+        self
+    }
+
+    /// Resumes the query from a checkpoint returned by [`LogQuery::checkpoint`]
+    pub fn with_checkpoint(mut self, checkpoint: LogQueryCheckpoint) -> Self {
+        if self.filter.is_paginatable() {
+            self.from_block = Some(checkpoint.block);
+            self.cursor = Some((checkpoint.block, checkpoint.log_index));
+            self.skip = checkpoint.log_index.map(|index| (checkpoint.block, index));
+            self.page_size = self
+                .max_page_size
+                .map_or(checkpoint.page_size, |max| checkpoint.page_size.min(max));
+        }
+        self
+    }
+
+    /// Keeps polling for new blocks at the given interval once all historical logs have been
+    /// yielded, instead of ending the stream.
+    ///
+    /// This has no effect if the filter has a numeric `toBlock` or is not paginatable. Logs are
+    /// not re-checked for reorgs, see [`ReorgStream`](crate::ReorgStream) for that.
+    pub fn with_live_tail(mut self, poll_interval: Duration) -> Self {
+        self.live_tail = Some(Box::new(interval(poll_interval)));
+        self
+    }
+
+    /// Returns the current progress of the query, `None` if the filter is not paginatable
+    pub fn checkpoint(&self) -> Option<LogQueryCheckpoint> {
+        self.cursor.map(|(block, log_index)| LogQueryCheckpoint {
+            block,
+            log_index,
+            page_size: self.page_size,
+        })
+    }
+
+    fn load_page(&mut self, from_block: U64, to_block: U64) -> LogQueryState<'a> {
+        self.page = Some((from_block, to_block));
+        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
+        let provider = self.provider;
+        #[allow(clippy::redundant_async_block)]
+        let fut = Box::pin(async move { provider.get_logs(&filter).await });
+        LogQueryState::LoadLogs(fut)
+    }
 }
 
 macro_rules! rewake_with_new_state {
@@ -96,61 +197,278 @@ where
             LogQueryState::LoadLastBlock(fut) => {
                 match futures_util::ready!(fut.as_mut().poll(ctx)) {
                     Ok(last_block) => {
+                        // never load past the `toBlock` of the filter
+                        let last_block = match self.filter.get_to_block() {
+                            Some(to_block) => to_block.min(last_block),
+                            None => last_block,
+                        };
                         self.last_block = Some(last_block);
-
-                        // this is okay because we will only enter this state when the filter is
-                        // paginatable i.e. from block is set
-                        let from_block = self.filter.get_from_block().unwrap();
-                        let to_block = from_block + self.page_size;
-                        self.from_block = Some(to_block + 1);
-
-                        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
-                        let provider = self.provider;
-                        // load first page of logs
-                        #[allow(clippy::redundant_async_block)]
-                        let fut = Box::pin(async move { provider.get_logs(&filter).await });
-                        rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                        rewake_with_new_state!(ctx, self, LogQueryState::Consume);
+                    }
+                    Err(err) => {
+                        self.state = LogQueryState::Initial;
+                        Poll::Ready(Some(Err(LogQueryError::LoadLastBlockError(err))))
                     }
-                    Err(err) => Poll::Ready(Some(Err(LogQueryError::LoadLastBlockError(err)))),
                 }
             }
             LogQueryState::LoadLogs(fut) => match futures_util::ready!(fut.as_mut().poll(ctx)) {
                 Ok(logs) => {
-                    self.current_logs = VecDeque::from(logs);
+                    if let Some((_, to_block)) = self.page.take() {
+                        match self.max_page_size {
+                            Some(max) if logs.len() < SPARSE_PAGE_LOGS => {
+                                self.page_size = self.page_size.saturating_mul(2).max(1).min(max);
+                            }
+                            _ => {}
+                        }
+                        self.from_block = Some(to_block + 1);
+                        self.page_end = Some(to_block);
+                    }
+                    let skip = self.skip.take();
+                    self.current_logs = logs
+                        .into_iter()
+                        .filter(|log| match (skip, log.block_number, log.log_index) {
+                            (Some((block, index)), Some(number), Some(log_index)) => {
+                                number != block || log_index > index
+                            }
+                            _ => true,
+                        })
+                        .collect();
                     rewake_with_new_state!(ctx, self, LogQueryState::Consume);
                 }
-                Err(err) => Poll::Ready(Some(Err(LogQueryError::LoadLogsError(err)))),
+                Err(err) => {
+                    if let Some((from_block, to_block)) = self.page.take() {
+                        let page_size = (to_block - from_block).as_u64();
+                        // the node rejected the range, retry with half of it and never request a
+                        // range of that size again
+                        if page_size > 0 &&
+                            RpcError::error_kind(&err) == Some(RpcErrorKind::LimitExceeded)
+                        {
+                            self.page_size = page_size / 2;
+                            self.max_page_size =
+                                self.max_page_size.map(|max| max.min(page_size - 1));
+                            let to_block = from_block + self.page_size;
+                            let state = self.load_page(from_block, to_block);
+                            rewake_with_new_state!(ctx, self, state);
+                        }
+                        self.state =
+                            if RpcError::error_kind(&err) == Some(RpcErrorKind::LimitExceeded) {
+                                LogQueryState::Failed
+                            } else {
+                                // the same page is loaded again on the next poll
+                                LogQueryState::Consume
+                            };
+                    } else {
+                        self.state = LogQueryState::Initial;
+                    }
+                    Poll::Ready(Some(Err(LogQueryError::LoadLogsError(err))))
+                }
             },
             LogQueryState::Consume => {
                 let log = self.current_logs.pop_front();
-                if log.is_none() {
-                    // consumed all the logs
-                    if !self.filter.is_paginatable() {
-                        Poll::Ready(None)
-                    } else {
-                        // load new logs if there are still more pages to go through
-                        // can safely assume this will always be set in this state
-                        let from_block = self.from_block.unwrap();
-                        let to_block = from_block + self.page_size;
-
-                        // no more pages to load, and everything is consumed
-                        // can safely assume this will always be set in this state
-                        if from_block > self.last_block.unwrap() {
-                            return Poll::Ready(None)
+                if let Some(log) = log {
+                    if self.cursor.is_some() {
+                        if let Some(block) = log.block_number {
+                            self.cursor = Some((block, log.log_index));
                         }
-                        // load next page
-                        self.from_block = Some(to_block + 1);
+                    }
+                    return Poll::Ready(Some(Ok(log)))
+                }
+
+                // consumed all the logs
+                if !self.filter.is_paginatable() {
+                    return Poll::Ready(None)
+                }
+                if let Some(page_end) = self.page_end.take() {
+                    self.cursor = Some((page_end + 1, None));
+                }
 
-                        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
-                        let provider = self.provider;
-                        #[allow(clippy::redundant_async_block)]
-                        let fut = Box::pin(async move { provider.get_logs(&filter).await });
-                        rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                // load new logs if there are still more pages to go through
+                // can safely assume these will always be set in this state
+                let from_block = self.from_block.unwrap();
+                let last_block = self.last_block.unwrap();
+                if from_block > last_block {
+                    // no more pages to load, and everything is consumed
+                    if self.live_tail.is_some() && self.filter.get_to_block().is_none() {
+                        rewake_with_new_state!(ctx, self, LogQueryState::WaitForInterval);
                     }
-                } else {
-                    Poll::Ready(log.map(Ok))
+                    return Poll::Ready(None)
                 }
+
+                // load next page
+                let to_block = (from_block + self.page_size).min(last_block);
+                let state = self.load_page(from_block, to_block);
+                rewake_with_new_state!(ctx, self, state);
+            }
+            LogQueryState::WaitForInterval => {
+                // only entered with a live tail
+                let live_tail = self.live_tail.as_mut().unwrap();
+                let _ready = futures_util::ready!(live_tail.poll_next_unpin(ctx));
+                let fut = self.provider.get_block_number();
+                rewake_with_new_state!(ctx, self, LogQueryState::LoadLastBlock(fut));
             }
+            LogQueryState::Failed => Poll::Ready(None),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, MockResponse};
+
+    fn log(block: u64, index: u64) -> Log {
+        Log {
+            block_number: Some(block.into()),
+            log_index: Some(index.into()),
+            ..Default::default()
+        }
+    }
+
+    fn range(from_block: u64, to_block: u64) -> [Filter; 1] {
+        [Filter::new().from_block(from_block).to_block(to_block)]
+    }
+
+    #[tokio::test]
+    async fn splits_rejected_ranges() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(8, 0)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(2, 0)]).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32005,
+            message: "query returned more than 10000 results".to_string(),
+            data: None,
+        }));
+        mock.push(U64::from(10)).unwrap();
+
+        let query = LogQuery::new(&provider, &Filter::new().from_block(0))
+            .with_page_size(10)
+            .with_max_page_size(10);
+        let logs = query.collect::<Vec<_>>().await;
+        let logs = logs.into_iter().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(logs, vec![log(2, 0), log(8, 0)]);
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 10)).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 5)).unwrap();
+        // the page grows again, but not to the rejected size
+        mock.assert_request("eth_getLogs", range(6, 10)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn returns_other_errors() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(2, 0)]).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "header not found".to_string(),
+            data: None,
+        }));
+        mock.push(U64::from(10)).unwrap();
+
+        let query = LogQuery::new(&provider, &Filter::new().from_block(0))
+            .with_page_size(10)
+            .with_max_page_size(10);
+        let logs = query.collect::<Vec<_>>().await;
+        assert!(matches!(logs[0], Err(LogQueryError::LoadLogsError(_))));
+        assert_eq!(logs[1].as_ref().unwrap(), &log(2, 0));
+
+        // the same page is loaded again
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 10)).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 10)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ends_on_rejected_single_block() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32005,
+            message: "query returned more than 10000 results".to_string(),
+            data: None,
+        }));
+        mock.push(U64::from(10)).unwrap();
+
+        let query = LogQuery::new(&provider, &Filter::new().from_block(3)).with_page_size(0);
+        let logs = query.collect::<Vec<_>>().await;
+        assert_eq!(logs.len(), 1);
+        assert!(matches!(logs[0], Err(LogQueryError::LoadLogsError(_))));
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(3, 3)).unwrap();
+        assert!(mock.assert_request("eth_getLogs", range(3, 3)).is_err());
+    }
+
+    #[tokio::test]
+    async fn grows_sparse_pages() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        let query = LogQuery::new(&provider, &Filter::new().from_block(0))
+            .with_page_size(2)
+            .with_max_page_size(100);
+        assert!(query.collect::<Vec<_>>().await.is_empty());
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 2)).unwrap();
+        mock.assert_request("eth_getLogs", range(3, 7)).unwrap();
+        mock.assert_request("eth_getLogs", range(8, 10)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resumes_from_checkpoint() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(1, 0), log(1, 1)]).unwrap();
+        mock.push(U64::from(3)).unwrap();
+
+        let filter = Filter::new().from_block(0);
+        let mut query = LogQuery::new(&provider, &filter).with_page_size(1);
+        assert_eq!(query.next().await.unwrap().unwrap(), log(1, 0));
+        let checkpoint = query.checkpoint().unwrap();
+        assert_eq!(
+            checkpoint,
+            LogQueryCheckpoint { block: 1.into(), log_index: Some(0.into()), page_size: 1 }
+        );
+        let checkpoint = serde_json::to_string(&checkpoint).unwrap();
+
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(1, 0), log(1, 1), log(2, 0)]).unwrap();
+        mock.push(U64::from(3)).unwrap();
+
+        let mut query = LogQuery::new(&provider, &filter)
+            .with_page_size(1)
+            .with_checkpoint(serde_json::from_str(&checkpoint).unwrap());
+        assert_eq!(query.next().await.unwrap().unwrap(), log(1, 1));
+        assert_eq!(query.next().await.unwrap().unwrap(), log(2, 0));
+        assert!(query.next().await.is_none());
+        assert_eq!(
+            query.checkpoint().unwrap(),
+            LogQueryCheckpoint { block: 4.into(), log_index: None, page_size: 1 }
+        );
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(1, 2)).unwrap();
+        mock.assert_request("eth_getLogs", range(3, 3)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn follows_new_blocks() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(1, 0)]).unwrap();
+        mock.push(U64::from(1)).unwrap();
+        mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        mock.push(U64::from(0)).unwrap();
+
+        let mut query = LogQuery::new(&provider, &Filter::new().from_block(0))
+            .with_live_tail(Duration::from_millis(1));
+        assert_eq!(query.next().await.unwrap().unwrap(), log(1, 0));
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(0, 0)).unwrap();
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getLogs", range(1, 1)).unwrap();
+    }
+}
@@ -5,7 +5,7 @@ mod pending_escalator;
 pub use pending_escalator::EscalatingPending;
 
 mod log_query;
-pub use log_query::{LogQuery, LogQueryError};
+pub use log_query::{LogQuery, LogQueryCheckpoint, LogQueryError};
 
 pub mod call_raw;
 pub use call_raw::*;
@@ -9,6 +9,7 @@ use std::{future::Future, pin::Pin, task::Poll};
 
 use crate::{
     utils::PinBoxFut, JsonRpcClient, Middleware, PendingTransaction, Provider, ProviderError,
+    RpcError, RpcErrorKind,
 };
 
 /// States for the EscalatingPending future
@@ -122,13 +123,9 @@ macro_rules! completed {
     };
 }
 
-/// Tests Provider error for nonce too low issue through debug contents
+/// Tests Provider error for nonce too low issue
 fn is_nonce_too_low(e: &ProviderError) -> bool {
-    let debug_str = format!("{e:?}");
-
-    debug_str.contains("nonce too low") // Geth, Arbitrum, Optimism
-            || debug_str.contains("nonce is too low") // Parity
-            || debug_str.contains("invalid transaction nonce") // Arbitrum
+    e.error_kind() == Some(RpcErrorKind::NonceTooLow)
 }
 
 macro_rules! poll_broadcast_fut {
@@ -3,7 +3,11 @@
 use ethers_core::{
     k256::ecdsa::{Error as K256Error, Signature as KSig, VerifyingKey},
     types::{
-        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::Eip712,
+            eip7702::{Authorization, SignedAuthorization},
+        },
         Address, Signature as EthSig, H256,
     },
     utils::hash_message,
@@ -258,6 +262,19 @@ impl super::Signer for AwsSigner {
         Ok(sig)
     }
 
+    #[instrument(err)]
+    async fn sign_authorization(
+        &self,
+        authorization: &Authorization,
+    ) -> Result<SignedAuthorization, super::AuthorizationError<Self::Error>> {
+        let digest = authorization.signature_hash().into();
+
+        let sig = self.sign_digest(digest).await?;
+        let sig = utils::sig_from_digest_bytes_trial_recovery(&sig, digest, &self.pubkey);
+
+        Ok(authorization.clone().into_signed(sig))
+    }
+
     fn address(&self) -> Address {
         self.address
     }
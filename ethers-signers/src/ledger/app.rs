@@ -157,9 +157,10 @@ impl LedgerEthereum {
             };
 
             signature.v = match tx {
-                TypedTransaction::Eip2930(_) | TypedTransaction::Eip1559(_) => {
-                    (ecc_parity % 2 != 1) as u64
-                }
+                TypedTransaction::Eip2930(_) |
+                TypedTransaction::Eip1559(_) |
+                TypedTransaction::Eip4844(_) |
+                TypedTransaction::Eip7702(_) => (ecc_parity % 2 != 1) as u64,
                 TypedTransaction::Legacy(_) => eip155_chain_id + ecc_parity,
                 #[cfg(feature = "optimism")]
                 TypedTransaction::DepositTransaction(_) => 0,
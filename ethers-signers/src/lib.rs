@@ -41,11 +41,26 @@ pub use aws::{AwsSigner, AwsSignerError};
 
 use async_trait::async_trait;
 use ethers_core::types::{
-    transaction::{eip2718::TypedTransaction, eip712::Eip712},
+    transaction::{
+        eip2718::TypedTransaction,
+        eip712::Eip712,
+        eip7702::{Authorization, SignedAuthorization},
+    },
     Address, Signature,
 };
 use std::error::Error;
 
+/// Error returned by [`Signer::sign_authorization`]
+#[derive(Debug, thiserror::Error)]
+pub enum AuthorizationError<E> {
+    /// The signer can't sign EIP-7702 authorizations
+    #[error("signer does not support signing authorizations")]
+    Unsupported,
+    /// The signer failed to sign the authorization
+    #[error(transparent)]
+    Signer(#[from] E),
+}
+
 /// Applies [EIP155](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-155.md)
 pub fn to_eip155_v<T: Into<u8>>(recovery_id: T, chain_id: u64) -> u64 {
     (recovery_id.into() as u64) + 35 + chain_id * 2
@@ -74,6 +89,17 @@ pub trait Signer: std::fmt::Debug + Send + Sync {
         payload: &T,
     ) -> Result<Signature, Self::Error>;
 
+    /// Signs an EIP-7702 authorization, allowing the signer's account to delegate its code to
+    /// the authorized address.
+    ///
+    /// Returns [`AuthorizationError::Unsupported`] by default.
+    async fn sign_authorization(
+        &self,
+        _authorization: &Authorization,
+    ) -> Result<SignedAuthorization, AuthorizationError<Self::Error>> {
+        Err(AuthorizationError::Unsupported)
+    }
+
     /// Returns the signer's Ethereum Address
     fn address(&self) -> Address;
 
@@ -192,6 +192,9 @@ impl TrezorEthereum {
                 transaction.max_priority_fee_per_gas,
                 transaction.access_list,
             )?,
+            TypedTransaction::Eip4844(_) | TypedTransaction::Eip7702(_) => {
+                return Err(TrezorError::UnsupportedTxType)
+            }
             #[cfg(feature = "optimism")]
             TypedTransaction::DepositTransaction(tx) => {
                 trezor_client::client::Signature { r: 0.into(), s: 0.into(), v: 0 }
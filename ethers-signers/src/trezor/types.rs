@@ -51,6 +51,8 @@ pub enum TrezorError {
     UnsupportedFirmwareVersion(String),
     #[error("Does not support ENS.")]
     NoENSSupport,
+    #[error("Does not support this transaction type.")]
+    UnsupportedTxType,
     #[error("Unable to access trezor cached session.")]
     CacheError(String),
 }
@@ -135,6 +137,9 @@ impl TrezorTransaction {
                     access_list,
                 })
             }
+            TypedTransaction::Eip4844(_) | TypedTransaction::Eip7702(_) => {
+                Err(TrezorError::UnsupportedTxType)
+            }
             #[cfg(feature = "optimism")]
             TypedTransaction::DepositTransaction(_) => Ok(Self {
                 nonce,
@@ -7,7 +7,7 @@ pub use private_key::WalletError;
 #[cfg(all(feature = "yubihsm", not(target_arch = "wasm32")))]
 mod yubi;
 
-use crate::{to_eip155_v, Signer};
+use crate::{to_eip155_v, AuthorizationError, Signer};
 use ethers_core::{
     k256::{
         ecdsa::{signature::hazmat::PrehashSigner, RecoveryId, Signature as RecoverableSignature},
@@ -15,7 +15,11 @@ use ethers_core::{
         Secp256k1,
     },
     types::{
-        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::Eip712,
+            eip7702::{Authorization, SignedAuthorization},
+        },
         Address, Signature, H256, U256,
     },
     utils::hash_message,
@@ -111,6 +115,14 @@ impl<D: Sync + Send + PrehashSigner<(RecoverableSignature, RecoveryId)>> Signer
         self.sign_hash(H256::from(encoded))
     }
 
+    async fn sign_authorization(
+        &self,
+        authorization: &Authorization,
+    ) -> Result<SignedAuthorization, AuthorizationError<Self::Error>> {
+        let signature = self.sign_hash(authorization.signature_hash())?;
+        Ok(authorization.clone().into_signed(signature))
+    }
+
     fn address(&self) -> Address {
         self.address
     }
@@ -273,6 +273,20 @@ mod tests {
         assert_eq!(recovered2, address);
     }
 
+    #[tokio::test]
+    async fn signs_authorization() {
+        use ethers_core::types::{transaction::eip7702::Authorization, Address, U64};
+
+        let key = Wallet::<SigningKey>::new(&mut rand::thread_rng());
+        let authorization = Authorization::new(1u64, Address::random(), 3u64);
+
+        let signed = key.sign_authorization(&authorization).await.unwrap();
+
+        assert!(signed.y_parity <= U64::one());
+        assert_eq!(signed.authorization(), authorization);
+        assert_eq!(signed.recover_authority().unwrap(), key.address);
+    }
+
     #[tokio::test]
     #[cfg(not(feature = "celo"))]
     async fn signs_tx() {